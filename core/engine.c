@@ -4,11 +4,17 @@
 #include <math.h>
 
 #define MAX_OPEN_ORDERS 1024
+#define MAX_PENDING_FILLS MAX_OPEN_ORDERS
 
 // SCALING CONVENTION:
 // - Quantities (order->qty, position) are scaled by 1,000,000 from Rust side
 // - When doing financial calculations (notional, PnL), must divide by 1,000,000.0
 // - Price ticks and tick_size remain unscaled
+//
+// PRICE SIGN:
+// - Prices may be zero or negative (spreads, the 2020 oil contract)
+// - Fees, spreads and borrow costs scale with the price's magnitude, so they
+//   always cost the trader regardless of sign
 
 // Internal order tracking
 typedef struct {
@@ -33,6 +39,21 @@ struct engine_handle_s {
 
     // Last tick price for unrealized PnL calculation
     int64_t last_tick_price;
+    // Whether last_tick_price holds a real price (a trade, mark or seed);
+    // zero is a valid price, so it cannot double as "none yet"
+    int has_last_price;
+
+    // Fills not yet drained by the caller (at most one per order per tick)
+    fill_t fills[MAX_PENDING_FILLS];
+    int fill_count;
+
+    // Outcome of the most recent engine_place_order
+    reject_reason_t last_reject_reason;
+
+    // Called on every fill; belongs to the handle, not its state, so clones,
+    // state copies and resets leave it alone
+    fill_callback_t fill_callback;
+    void* fill_userdata;
 };
 
 // Helper function to calculate unrealized PnL
@@ -52,14 +73,14 @@ static double calculate_unrealized_pnl(engine_handle_t* h) {
 // Helper function to apply fees
 static double calculate_fee(engine_handle_t* h, double notional, int is_maker) {
     double fee_bps = is_maker ? h->config.maker_fee_bps : h->config.taker_fee_bps;
-    return notional * (fee_bps / 10000.0);
+    return fabs(notional) * (fee_bps / 10000.0);
 }
 
 // Helper function to get effective price after spread
 static int64_t apply_spread(engine_handle_t* h, int64_t price_tick, side_t side) {
     // Spread widens the market: buyers pay more, sellers receive less
     double spread_multiplier = h->config.spread_bps / 10000.0;
-    double spread_ticks = (double)price_tick * spread_multiplier;
+    double spread_ticks = fabs((double)price_tick) * spread_multiplier;
 
     if (side == SIDE_BUY) {
         // Buying: pay more (round up)
@@ -70,6 +91,19 @@ static int64_t apply_spread(engine_handle_t* h, int64_t price_tick, side_t side)
     }
 }
 
+// Helper function to get the price a market order pays when crossing the spread
+// The tick price is treated as mid: buys lift the ask at mid*(1 + spread/2),
+// sells hit the bid at mid*(1 - spread/2)
+static int64_t apply_half_spread(engine_handle_t* h, int64_t mid_tick, side_t side) {
+    double half_spread_ticks = fabs((double)mid_tick) * (h->config.spread_bps / 2.0 / 10000.0);
+
+    if (side == SIDE_BUY) {
+        return mid_tick + (int64_t)ceil(half_spread_ticks);
+    } else {
+        return mid_tick - (int64_t)ceil(half_spread_ticks);
+    }
+}
+
 // Helper function to execute a fill
 static int execute_fill(engine_handle_t* h, order_t* order, int64_t fill_price_tick) {
     int64_t fill_qty = order->qty;
@@ -132,25 +166,55 @@ static int execute_fill(engine_handle_t* h, order_t* order, int64_t fill_price_t
     }
 
     h->position = new_position;
+
+    // Record the fill for the caller
+    if (h->fill_count < MAX_PENDING_FILLS) {
+        fill_t* fill = &h->fills[h->fill_count++];
+        fill->order_id = order->order_id;
+        fill->ts_ms = h->current_ts_ms;
+        fill->side = order->side;
+        fill->qty = fill_qty;
+        fill->price_tick = fill_price_tick;
+        fill->fee = fee;
+        fill->client_tag = order->client_tag;
+
+        if (h->fill_callback) {
+            h->fill_callback(fill, h->fill_userdata);
+        }
+    }
+
     return 0;
 }
 
 // Helper function to check if an order should be filled at given tick
-static int should_fill_order(order_t* order, tick_event_t* tick) {
+static int should_fill_order(engine_handle_t* h, order_t* order, tick_event_t* tick) {
     if (order->type == ORDER_TYPE_MARKET) {
         return 1;  // Market orders always fill
     }
 
+    // Under trade-through, a trade at exactly the limit price does not fill
+    int through = h->config.limit_fill_model == LIMIT_FILL_ON_THROUGH;
+
     // Limit orders fill if price crosses
     if (order->side == SIDE_BUY) {
-        // Buy limit fills if tick price <= limit price
-        return tick->price_tick <= order->price_tick;
+        // Buy limit fills if tick price <= limit price (< on trade-through)
+        return through ? tick->price_tick < order->price_tick
+                       : tick->price_tick <= order->price_tick;
     } else {
-        // Sell limit fills if tick price >= limit price
-        return tick->price_tick >= order->price_tick;
+        // Sell limit fills if tick price >= limit price (> on trade-through)
+        return through ? tick->price_tick > order->price_tick
+                       : tick->price_tick >= order->price_tick;
     }
 }
 
+// Quantity an order on `side` can fill without growing or flipping the position
+static int64_t reducible_qty(engine_handle_t* h, side_t side) {
+    if (side == SIDE_BUY) {
+        return h->position < 0 ? -h->position : 0;
+    }
+    return h->position > 0 ? h->position : 0;
+}
+
 engine_handle_t* engine_new(config_t* cfg) {
     if (!cfg) {
         return NULL;
@@ -180,14 +244,46 @@ void engine_free(engine_handle_t* h) {
     }
 }
 
+engine_handle_t* engine_clone(const engine_handle_t* h) {
+    if (!h) {
+        return NULL;
+    }
+
+    engine_handle_t* copy = (engine_handle_t*)malloc(sizeof(engine_handle_t));
+    if (!copy) {
+        return NULL;
+    }
+
+    memcpy(copy, h, sizeof(engine_handle_t));
+    copy->fill_callback = NULL;
+    copy->fill_userdata = NULL;
+    return copy;
+}
+
+void engine_copy_state(engine_handle_t* dst, const engine_handle_t* src) {
+    if (!dst || !src || dst == src) {
+        return;
+    }
+
+    fill_callback_t callback = dst->fill_callback;
+    void* userdata = dst->fill_userdata;
+    memcpy(dst, src, sizeof(engine_handle_t));
+    dst->fill_callback = callback;
+    dst->fill_userdata = userdata;
+}
+
 void engine_reset(engine_handle_t* h) {
     if (!h) {
         return;
     }
 
     config_t cfg = h->config;  // Save config
+    fill_callback_t callback = h->fill_callback;
+    void* userdata = h->fill_userdata;
     memset(h, 0, sizeof(engine_handle_t));
     h->config = cfg;
+    h->fill_callback = callback;
+    h->fill_userdata = userdata;
     h->cash = cfg.initial_cash;
     h->current_ts_ms = 0;
     h->position = 0;
@@ -197,13 +293,61 @@ void engine_reset(engine_handle_t* h) {
     h->last_tick_price = 0;
 }
 
+void engine_reset_with_config(engine_handle_t* h, config_t* cfg) {
+    if (!h || !cfg) {
+        return;
+    }
+
+    h->config = *cfg;
+    engine_reset(h);
+}
+
+int engine_set_position(engine_handle_t* h, int64_t position, double avg_entry_price) {
+    if (!h) {
+        return -1;
+    }
+
+    if (position == 0) {
+        avg_entry_price = 0.0;
+    } else if (avg_entry_price == 0.0 || !isfinite(avg_entry_price)) {
+        return -1;
+    }
+
+    h->position = position;
+    h->avg_entry_price = avg_entry_price;
+
+    // Mark at entry until a tick arrives, rather than at price zero
+    if (!h->has_last_price && position != 0) {
+        h->last_tick_price = llround(avg_entry_price);
+        h->has_last_price = 1;
+    }
+
+    return 0;
+}
+
+// Charge borrow cost on a short position for the time since the last event
+static void accrue_borrow_cost(engine_handle_t* h, int64_t ts_ms) {
+    if (h->position >= 0 || h->config.borrow_fee_bps_per_day <= 0.0) {
+        return;
+    }
+    if (h->current_ts_ms <= 0 || ts_ms <= h->current_ts_ms) {
+        return;
+    }
+
+    double short_value = fabs((double)h->position / 1000000.0 * (double)h->last_tick_price * h->config.tick_size);
+    double days = (double)(ts_ms - h->current_ts_ms) / 86400000.0;
+    h->cash -= short_value * (h->config.borrow_fee_bps_per_day / 10000.0) * days;
+}
+
 int engine_step_tick(engine_handle_t* h, tick_event_t* tick) {
     if (!h || !tick) {
         return -1;
     }
 
+    accrue_borrow_cost(h, tick->ts_ms);
     h->current_ts_ms = tick->ts_ms;
     h->last_tick_price = tick->price_tick;
+    h->has_last_price = 1;
 
     // Check all open orders for fills
     for (int i = 0; i < h->order_count; i++) {
@@ -211,12 +355,25 @@ int engine_step_tick(engine_handle_t* h, tick_event_t* tick) {
             continue;
         }
 
-        if (should_fill_order(&h->orders[i].order, tick)) {
+        if (should_fill_order(h, &h->orders[i].order, tick)) {
+            // Reduce-only orders fill at most the opposing position, and are
+            // cancelled once there is nothing left to reduce
+            if (h->orders[i].order.reduce_only) {
+                int64_t reducible = reducible_qty(h, h->orders[i].order.side);
+                if (reducible == 0) {
+                    h->orders[i].active = 0;
+                    continue;
+                }
+                if (h->orders[i].order.qty > reducible) {
+                    h->orders[i].order.qty = reducible;
+                }
+            }
+
             // Determine fill price
             int64_t fill_price_tick;
             if (h->orders[i].order.type == ORDER_TYPE_MARKET) {
-                // Market orders fill at tick price with spread
-                fill_price_tick = apply_spread(h, tick->price_tick, h->orders[i].order.side);
+                // Market orders cross the half-spread around the tick price
+                fill_price_tick = apply_half_spread(h, tick->price_tick, h->orders[i].order.side);
             } else {
                 // Limit orders fill at limit price with spread
                 fill_price_tick = apply_spread(h, h->orders[i].order.price_tick, h->orders[i].order.side);
@@ -245,13 +402,76 @@ int engine_step_tick(engine_handle_t* h, tick_event_t* tick) {
     return 0;
 }
 
-int engine_place_order(engine_handle_t* h, order_t* order) {
-    if (!h || !order) {
+int engine_advance_clock(engine_handle_t* h, int64_t ts_ms) {
+    if (!h) {
         return -1;
     }
 
+    accrue_borrow_cost(h, ts_ms);
+    h->current_ts_ms = ts_ms;
+    return 0;
+}
+
+// Cash a buy order would take at its limit price, or at the last trade for
+// a market order, including the taker fee
+static double buy_cost(engine_handle_t* h, const order_t* order) {
+    int64_t price_tick = order->type == ORDER_TYPE_LIMIT ? order->price_tick : h->last_tick_price;
+    double notional = (double)price_tick * h->config.tick_size * ((double)order->qty / 1000000.0);
+    return notional + calculate_fee(h, notional, 0);
+}
+
+// Cash held back for open buy orders that could add to a long; reduce-only
+// buys never do
+static double committed_buy_cash(engine_handle_t* h) {
+    double committed = 0.0;
+    for (int i = 0; i < h->order_count; i++) {
+        const order_t* open = &h->orders[i].order;
+        if (h->orders[i].active && open->side == SIDE_BUY && !open->reduce_only) {
+            committed += buy_cost(h, open);
+        }
+    }
+    return committed;
+}
+
+// Why order can't be accepted right now, or REJECT_NONE
+static reject_reason_t check_order(engine_handle_t* h, order_t* order) {
+    if (order->qty <= 0) {
+        return REJECT_INVALID_ORDER;
+    }
+
+    for (int i = 0; i < h->order_count; i++) {
+        if (h->orders[i].active && h->orders[i].order.order_id == order->order_id) {
+            return REJECT_DUPLICATE_ID;
+        }
+    }
+
     if (h->order_count >= MAX_OPEN_ORDERS) {
-        return -2;  // Order book full
+        return REJECT_BOOK_FULL;
+    }
+
+    // Only buys that open or add to a long consume cash; covering a short is always allowed.
+    // Market orders are costed at the last trade, so they need one, and cash
+    // already committed to resting buys is not available again
+    if (order->side == SIDE_BUY && h->position >= 0) {
+        if (order->type == ORDER_TYPE_MARKET && !h->has_last_price) {
+            return REJECT_NO_PRICE;
+        }
+        if (buy_cost(h, order) + committed_buy_cash(h) > h->cash) {
+            return REJECT_INSUFFICIENT_CASH;
+        }
+    }
+
+    return REJECT_NONE;
+}
+
+int engine_place_order(engine_handle_t* h, order_t* order) {
+    if (!h) {
+        return -REJECT_INVALID_ORDER;
+    }
+
+    h->last_reject_reason = order ? check_order(h, order) : REJECT_INVALID_ORDER;
+    if (h->last_reject_reason != REJECT_NONE) {
+        return -(int)h->last_reject_reason;
     }
 
     // Add order to tracking
@@ -262,6 +482,10 @@ int engine_place_order(engine_handle_t* h, order_t* order) {
     return 0;
 }
 
+reject_reason_t engine_last_reject_reason(const engine_handle_t* h) {
+    return h ? h->last_reject_reason : REJECT_NONE;
+}
+
 int engine_cancel_order(engine_handle_t* h, uint64_t order_id) {
     if (!h) {
         return -1;
@@ -278,6 +502,38 @@ int engine_cancel_order(engine_handle_t* h, uint64_t order_id) {
     return -1;  // Order not found
 }
 
+int engine_get_open_orders(engine_handle_t* h, order_t* out, int max) {
+    if (!h || !out || max <= 0) {
+        return 0;
+    }
+
+    int n = 0;
+    for (int i = 0; i < h->order_count && n < max; i++) {
+        if (h->orders[i].active) {
+            out[n++] = h->orders[i].order;
+        }
+    }
+
+    return n;
+}
+
+int engine_drain_fills(engine_handle_t* h, fill_t* out, int max) {
+    if (!h || !out || max <= 0) {
+        return 0;
+    }
+
+    int n = h->fill_count < max ? h->fill_count : max;
+    memcpy(out, h->fills, (size_t)n * sizeof(fill_t));
+
+    // Keep any fills that did not fit, preserving order
+    if (n < h->fill_count) {
+        memmove(h->fills, h->fills + n, (size_t)(h->fill_count - n) * sizeof(fill_t));
+    }
+    h->fill_count -= n;
+
+    return n;
+}
+
 snapshot_t engine_get_snapshot(engine_handle_t* h) {
     snapshot_t snap;
     memset(&snap, 0, sizeof(snapshot_t));
@@ -296,3 +552,80 @@ snapshot_t engine_get_snapshot(engine_handle_t* h) {
 
     return snap;
 }
+
+int64_t engine_get_last_tick_price(const engine_handle_t* h) {
+    if (!h) {
+        return 0;
+    }
+    return h->last_tick_price;
+}
+
+int engine_has_last_price(const engine_handle_t* h) {
+    if (!h) {
+        return 0;
+    }
+    return h->has_last_price;
+}
+
+int engine_set_mark_price(engine_handle_t* h, int64_t price_tick) {
+    if (!h) {
+        return -1;
+    }
+
+    h->last_tick_price = price_tick;
+    h->has_last_price = 1;
+    return 0;
+}
+
+int engine_close_position(engine_handle_t* h, uint64_t order_id, int64_t price_tick) {
+    if (!h) {
+        return -1;
+    }
+    if (h->position == 0) {
+        return 0;
+    }
+
+    order_t order;
+    memset(&order, 0, sizeof(order));
+    order.order_id = order_id;
+    order.type = ORDER_TYPE_MARKET;
+    order.side = h->position > 0 ? SIDE_SELL : SIDE_BUY;
+    order.qty = llabs(h->position);
+
+    h->last_tick_price = price_tick;
+    h->has_last_price = 1;
+    execute_fill(h, &order, apply_half_spread(h, price_tick, order.side));
+    return 1;
+}
+
+void engine_set_fill_callback(engine_handle_t* h, fill_callback_t callback, void* userdata) {
+    if (!h) {
+        return;
+    }
+
+    h->fill_callback = callback;
+    h->fill_userdata = callback ? userdata : NULL;
+}
+
+uint32_t engine_abi_version(void) {
+    return ENGINE_ABI_VERSION;
+}
+
+abi_layout_t engine_abi_layout(void) {
+    abi_layout_t layout;
+    layout.tick_event_size = (uint32_t)sizeof(tick_event_t);
+    layout.order_size = (uint32_t)sizeof(order_t);
+    layout.fill_size = (uint32_t)sizeof(fill_t);
+    layout.snapshot_size = (uint32_t)sizeof(snapshot_t);
+    layout.config_size = (uint32_t)sizeof(config_t);
+    return layout;
+}
+
+int engine_apply_cash_flow(engine_handle_t* h, double amount) {
+    if (!h) {
+        return -1;
+    }
+
+    h->cash += amount;
+    return 0;
+}
@@ -3,6 +3,15 @@
 
 #include "types.h"
 
+// Bump whenever a struct in types.h or a function signature here changes
+#define ENGINE_ABI_VERSION 10
+
+// ABI version this engine was compiled with (ENGINE_ABI_VERSION)
+uint32_t engine_abi_version(void);
+
+// Struct sizes this engine was compiled with
+abi_layout_t engine_abi_layout(void);
+
 // Opaque handle for the engine
 typedef struct engine_handle_s engine_handle_t;
 
@@ -13,22 +22,81 @@ engine_handle_t* engine_new(config_t* cfg);
 // Free the engine and all its resources
 void engine_free(engine_handle_t* h);
 
+// Allocate a copy of the engine's full state (config, open orders, pending fills)
+// Returns NULL on failure
+engine_handle_t* engine_clone(const engine_handle_t* h);
+
+// Overwrite dst's full state with src's
+void engine_copy_state(engine_handle_t* dst, const engine_handle_t* src);
+
 // Reset the engine to initial state
 void engine_reset(engine_handle_t* h);
 
+// Replace the configuration, then reset the engine to initial state
+void engine_reset_with_config(engine_handle_t* h, config_t* cfg);
+
+// Seed the position (scaled by 1,000,000) and average entry price (in ticks, non-zero, may be negative)
+// Until the first tick the position is marked at its entry price
+// Returns 0 on success, -1 on invalid arguments
+int engine_set_position(engine_handle_t* h, int64_t position, double avg_entry_price);
+
 // Process a tick event
 // Returns 0 on success, negative on error
 int engine_step_tick(engine_handle_t* h, tick_event_t* tick);
 
-// Place an order
+// Move the engine clock to ts_ms without a trade
+// Marks to market at the last traded price; no orders are matched
 // Returns 0 on success, negative on error
+int engine_advance_clock(engine_handle_t* h, int64_t ts_ms);
+
+// Place an order
+// Returns 0 on success, or -reason (see reject_reason_t) if the order is refused
 int engine_place_order(engine_handle_t* h, order_t* order);
 
+// Reason the most recent engine_place_order call was refused
+// REJECT_NONE if it was accepted (or no order has been placed)
+reject_reason_t engine_last_reject_reason(const engine_handle_t* h);
+
 // Cancel an order by ID
 // Returns 0 on success, negative on error (-1 if order not found)
 int engine_cancel_order(engine_handle_t* h, uint64_t order_id);
 
+// Copy up to max open orders into out, in placement order
+// Returns the number of orders written
+int engine_get_open_orders(engine_handle_t* h, order_t* out, int max);
+
+// Move up to max pending fills into out, oldest first
+// Returns the number of fills written
+int engine_drain_fills(engine_handle_t* h, fill_t* out, int max);
+
 // Get current state snapshot
 snapshot_t engine_get_snapshot(engine_handle_t* h);
 
+// Add amount (may be negative) to cash, e.g. for funding payments
+// Returns 0 on success, negative on error
+int engine_apply_cash_flow(engine_handle_t* h, double amount);
+
+// Price (in ticks) of the last processed tick, 0 if none yet
+int64_t engine_get_last_tick_price(const engine_handle_t* h);
+
+// 1 once a tick, mark or seeded position has set the last price, else 0
+int engine_has_last_price(const engine_handle_t* h);
+
+// Mark the position at price_tick without a trade
+// Replaces the last tick price (so unrealized PnL and equity follow it) until the next tick
+// Returns 0 on success, negative on error
+int engine_set_mark_price(engine_handle_t* h, int64_t price_tick);
+
+// Close the whole position with a market fill at price_tick (plus half-spread and taker fee),
+// recorded under order_id, then mark at price_tick
+// Returns 1 if a fill was made, 0 if already flat, negative on error
+int engine_close_position(engine_handle_t* h, uint64_t order_id, int64_t price_tick);
+
+// Called with each fill as it is recorded, before it can be drained
+typedef void (*fill_callback_t)(const fill_t* fill, void* userdata);
+
+// Register callback (NULL to clear) to be called with userdata on every fill
+// Stays registered across resets and engine_copy_state; engine_clone copies have none
+void engine_set_fill_callback(engine_handle_t* h, fill_callback_t callback, void* userdata);
+
 #endif // AG_KERNEL_ENGINE_H
@@ -28,8 +28,37 @@ typedef struct {
     side_t side;          // Buy or sell
     int64_t qty;          // Quantity
     int64_t price_tick;   // Price in ticks (0 for market orders)
+    uint64_t client_tag;  // Caller-defined tag, echoed back untouched
+    int32_t reduce_only;  // 1 = may only shrink the position (capped at fill time)
 } order_t;
 
+// When a resting limit order fills against a trade at its own price
+typedef enum {
+    LIMIT_FILL_ON_TOUCH = 0,    // A trade at the limit price fills it
+    LIMIT_FILL_ON_THROUGH = 1   // Only a trade strictly through the limit fills it
+} limit_fill_model_t;
+
+// Why engine_place_order refused an order
+typedef enum {
+    REJECT_NONE = 0,               // Last order was accepted
+    REJECT_INVALID_ORDER = 1,      // Null order or non-positive quantity
+    REJECT_BOOK_FULL = 2,          // MAX_OPEN_ORDERS already open
+    REJECT_DUPLICATE_ID = 3,       // An open order already has this order_id
+    REJECT_INSUFFICIENT_CASH = 4,  // Buy would open or add to a long beyond cash not committed to open buys
+    REJECT_NO_PRICE = 5            // Market buy before any trade or mark to cost it at
+} reject_reason_t;
+
+// Fill record (one per executed order)
+typedef struct {
+    uint64_t order_id;    // Order that was filled
+    int64_t ts_ms;        // Timestamp of the tick that triggered the fill
+    side_t side;          // Buy or sell
+    int64_t qty;          // Filled quantity (scaled by 1,000,000)
+    int64_t price_tick;   // Effective fill price in ticks (spread included)
+    double fee;           // Fee charged in currency units
+    uint64_t client_tag;  // client_tag of the filled order
+} fill_t;
+
 // Snapshot type
 typedef struct {
     int64_t ts_ms;           // Timestamp in milliseconds
@@ -48,6 +77,17 @@ typedef struct {
     double spread_bps;       // Spread in basis points (applied to each side)
     double initial_cash;     // Initial cash balance
     double tick_size;        // Size of one tick in currency units
+    double borrow_fee_bps_per_day;  // Cost of holding a short, per day of notional
+    limit_fill_model_t limit_fill_model;  // Touch or trade-through for limit fills
 } config_t;
 
+// Sizes of the structs above as compiled, for ABI checks by bindings
+typedef struct {
+    uint32_t tick_event_size;
+    uint32_t order_size;
+    uint32_t fill_size;
+    uint32_t snapshot_size;
+    uint32_t config_size;
+} abi_layout_t;
+
 #endif // AG_KERNEL_TYPES_H
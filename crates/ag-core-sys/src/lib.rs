@@ -8,6 +8,9 @@
 
 use std::os::raw::{c_double, c_int, c_void};
 
+/// ABI version these bindings were written against (`ENGINE_ABI_VERSION` in engine.h)
+pub const ENGINE_ABI_VERSION: u32 = 10;
+
 // ========== Type Definitions ==========
 
 #[repr(C)]
@@ -41,6 +44,56 @@ pub struct order_t {
     pub side: side_t,
     pub qty: i64,
     pub price_tick: i64,
+    pub client_tag: u64,
+    /// Non-zero: may only shrink the position, capped at fill time
+    pub reduce_only: i32,
+}
+
+/// When a resting limit order fills against a trade at its own price
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum limit_fill_model_t {
+    LIMIT_FILL_ON_TOUCH = 0,
+    LIMIT_FILL_ON_THROUGH = 1,
+}
+
+/// Why `engine_place_order` refused an order (`engine_last_reject_reason`)
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum reject_reason_t {
+    REJECT_NONE = 0,
+    REJECT_INVALID_ORDER = 1,
+    REJECT_BOOK_FULL = 2,
+    REJECT_DUPLICATE_ID = 3,
+    REJECT_INSUFFICIENT_CASH = 4,
+    REJECT_NO_PRICE = 5,
+}
+
+impl reject_reason_t {
+    /// Decode a value returned by `engine_last_reject_reason`
+    pub fn from_raw(raw: c_int) -> Option<Self> {
+        Some(match raw {
+            0 => Self::REJECT_NONE,
+            1 => Self::REJECT_INVALID_ORDER,
+            2 => Self::REJECT_BOOK_FULL,
+            3 => Self::REJECT_DUPLICATE_ID,
+            4 => Self::REJECT_INSUFFICIENT_CASH,
+            5 => Self::REJECT_NO_PRICE,
+            _ => return None,
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct fill_t {
+    pub order_id: u64,
+    pub ts_ms: i64,
+    pub side: side_t,
+    pub qty: i64,
+    pub price_tick: i64,
+    pub fee: c_double,
+    pub client_tag: u64,
 }
 
 #[repr(C)]
@@ -63,8 +116,41 @@ pub struct config_t {
     pub spread_bps: c_double,
     pub initial_cash: c_double,
     pub tick_size: c_double,
+    pub borrow_fee_bps_per_day: c_double,
+    pub limit_fill_model: limit_fill_model_t,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct abi_layout_t {
+    pub tick_event_size: u32,
+    pub order_size: u32,
+    pub fill_size: u32,
+    pub snapshot_size: u32,
+    pub config_size: u32,
+}
+
+impl abi_layout_t {
+    /// Layout of the Rust definitions, to compare with `engine_abi_layout()`
+    pub const fn expected() -> Self {
+        Self {
+            tick_event_size: std::mem::size_of::<tick_event_t>() as u32,
+            order_size: std::mem::size_of::<order_t>() as u32,
+            fill_size: std::mem::size_of::<fill_t>() as u32,
+            snapshot_size: std::mem::size_of::<snapshot_t>() as u32,
+            config_size: std::mem::size_of::<config_t>() as u32,
+        }
+    }
+}
+
+// Sizes of the C structs on LP64 targets; a field change on either side
+// must update these and ENGINE_ABI_VERSION together
+const _: () = assert!(std::mem::size_of::<tick_event_t>() == 32);
+const _: () = assert!(std::mem::size_of::<order_t>() == 48);
+const _: () = assert!(std::mem::size_of::<fill_t>() == 56);
+const _: () = assert!(std::mem::size_of::<snapshot_t>() == 56);
+const _: () = assert!(std::mem::size_of::<config_t>() == 56);
+
 // Opaque handle type
 #[repr(C)]
 pub struct engine_handle_s {
@@ -73,6 +159,10 @@ pub struct engine_handle_s {
 
 pub type engine_handle_t = engine_handle_s;
 
+/// Called with each fill as the engine records it (`fill_callback_t`);
+/// `None` is NULL
+pub type fill_callback_t = Option<unsafe extern "C" fn(fill: *const fill_t, userdata: *mut c_void)>;
+
 // ========== C Function Bindings ==========
 
 extern "C" {
@@ -80,15 +170,48 @@ extern "C" {
 
     pub fn engine_free(h: *mut engine_handle_t);
 
+    pub fn engine_clone(h: *const engine_handle_t) -> *mut engine_handle_t;
+
+    pub fn engine_copy_state(dst: *mut engine_handle_t, src: *const engine_handle_t);
+
     pub fn engine_reset(h: *mut engine_handle_t);
 
+    pub fn engine_reset_with_config(h: *mut engine_handle_t, cfg: *const config_t);
+
+    pub fn engine_set_position(h: *mut engine_handle_t, position: i64, avg_entry_price: f64) -> c_int;
+
     pub fn engine_step_tick(h: *mut engine_handle_t, tick: *const tick_event_t) -> c_int;
 
+    pub fn engine_advance_clock(h: *mut engine_handle_t, ts_ms: i64) -> c_int;
+
     pub fn engine_place_order(h: *mut engine_handle_t, order: *const order_t) -> c_int;
 
+    /// Raw `reject_reason_t`; decode with [`reject_reason_t::from_raw`]
+    pub fn engine_last_reject_reason(h: *const engine_handle_t) -> c_int;
+
     pub fn engine_cancel_order(h: *mut engine_handle_t, order_id: u64) -> c_int;
 
+    pub fn engine_get_open_orders(h: *mut engine_handle_t, out: *mut order_t, max: c_int) -> c_int;
+
+    pub fn engine_drain_fills(h: *mut engine_handle_t, out: *mut fill_t, max: c_int) -> c_int;
+
     pub fn engine_get_snapshot(h: *const engine_handle_t) -> snapshot_t;
+
+    pub fn engine_apply_cash_flow(h: *mut engine_handle_t, amount: c_double) -> c_int;
+
+    pub fn engine_get_last_tick_price(h: *const engine_handle_t) -> i64;
+
+    pub fn engine_has_last_price(h: *const engine_handle_t) -> c_int;
+
+    pub fn engine_set_mark_price(h: *mut engine_handle_t, price_tick: i64) -> c_int;
+
+    pub fn engine_close_position(h: *mut engine_handle_t, order_id: u64, price_tick: i64) -> c_int;
+
+    pub fn engine_set_fill_callback(h: *mut engine_handle_t, callback: fill_callback_t, userdata: *mut c_void);
+
+    pub fn engine_abi_version() -> u32;
+
+    pub fn engine_abi_layout() -> abi_layout_t;
 }
 
 #[cfg(test)]
@@ -104,6 +227,8 @@ mod tests {
                 spread_bps: 2.0,
                 initial_cash: 10000.0,
                 tick_size: 1.0,
+                borrow_fee_bps_per_day: 0.0,
+                limit_fill_model: limit_fill_model_t::LIMIT_FILL_ON_TOUCH,
             };
 
             let handle = engine_new(&config);
@@ -116,4 +241,12 @@ mod tests {
             engine_free(handle);
         }
     }
+
+    #[test]
+    fn test_abi_matches_c_engine() {
+        unsafe {
+            assert_eq!(engine_abi_version(), ENGINE_ABI_VERSION);
+            assert_eq!(engine_abi_layout(), abi_layout_t::expected());
+        }
+    }
 }
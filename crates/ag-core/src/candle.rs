@@ -1,6 +1,8 @@
 //! OHLC Candle data structures with zero-copy optimization
 
+use crate::candle_parser::ParseError;
 use bytemuck::{Pod, Zeroable};
+use std::collections::VecDeque;
 
 /// OHLC Candle representation optimized for zero-copy deserialization
 ///
@@ -9,13 +11,16 @@ use bytemuck::{Pod, Zeroable};
 /// - All fields are POD types for bytemuck compatibility
 /// - Uses i64 for prices (tick-quantized) to avoid float precision issues
 ///
+/// Prices may be zero or negative (spread products, the April 2020 WTI
+/// contract); only their OHLC ordering is validated.
+///
 /// # Safety
 /// This struct is marked as `Pod` and `Zeroable`, meaning:
 /// - All bit patterns are valid
 /// - Can be safely cast from/to byte slices
 /// - No padding bytes contain uninitialized data
 #[repr(C)]
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Candle {
     /// Unix timestamp in milliseconds (start of candle)
     pub ts_open: i64,
@@ -39,10 +44,17 @@ pub struct Candle {
     /// Stored as i64 with 6 decimal precision (multiply by 1e6)
     pub volume_scaled: i64,
 
-    /// Number of trades in this candle
+    /// Number of trades in this candle, or [`TRADE_COUNT_UNKNOWN`] when the
+    /// source has no trade counts
     pub trade_count: i64,
 }
 
+/// `trade_count` of candles whose source doesn't report trade counts
+///
+/// Distinct from `0`, which is a genuine bar with no trades. Anything that
+/// weights by trade count should skip these (see [`Candle::known_trade_count`]).
+pub const TRADE_COUNT_UNKNOWN: i64 = -1;
+
 // SAFETY: Candle contains only POD types (i64)
 // All bit patterns are valid (though semantically some represent invalid data)
 unsafe impl Zeroable for Candle {}
@@ -56,9 +68,9 @@ impl Candle {
     ///
     /// Checks:
     /// - Timestamps are positive and ordered correctly
-    /// - OHLC relationship is valid: low <= open/close <= high
+    /// - OHLC relationship is valid: low <= open/close <= high (any sign)
     /// - Volume is non-negative
-    /// - Trade count is non-negative
+    /// - Trade count is non-negative or `TRADE_COUNT_UNKNOWN`
     ///
     /// Returns true if candle is valid, false otherwise.
     /// Does NOT panic on invalid data.
@@ -92,13 +104,68 @@ impl Candle {
         }
 
         // Trade count validation
-        if self.trade_count < 0 {
+        if self.trade_count < TRADE_COUNT_UNKNOWN {
             return false;
         }
 
         true
     }
 
+    /// Clamp open and close into `[low, high]` after quantization
+    ///
+    /// Unlike [`repair`](Self::repair), which widens the range to fit open
+    /// and close, this keeps the high and low and moves open/close, so each
+    /// price stays within a tick of its float value. Quantizing a valid float
+    /// candle never leaves the range inverted; if one is given anyway, high
+    /// and low are swapped first. Returns true if anything changed.
+    #[inline]
+    pub fn normalize_ticks(&mut self) -> bool {
+        let before = (self.open_tick, self.high_tick, self.low_tick, self.close_tick);
+        if self.low_tick > self.high_tick {
+            std::mem::swap(&mut self.low_tick, &mut self.high_tick);
+        }
+        self.open_tick = self.open_tick.clamp(self.low_tick, self.high_tick);
+        self.close_tick = self.close_tick.clamp(self.low_tick, self.high_tick);
+        before != (self.open_tick, self.high_tick, self.low_tick, self.close_tick)
+    }
+
+    /// Clamp high/low so they bracket open and close
+    ///
+    /// Sets `high = max(high, open, close)` and `low = min(low, open, close)`.
+    /// Useful for feeds whose high/low miss open/close by a tick due to rounding.
+    /// Returns true if anything changed. Timestamps, volume and trade count are
+    /// left untouched, so the candle may still fail `is_valid` for other reasons.
+    #[inline]
+    pub fn repair(&mut self) -> bool {
+        let high = self.high_tick.max(self.open_tick).max(self.close_tick);
+        let low = self.low_tick.min(self.open_tick).min(self.close_tick);
+        let changed = high != self.high_tick || low != self.low_tick;
+
+        self.high_tick = high;
+        self.low_tick = low;
+        changed
+    }
+
+    /// Trade count, or `None` if the source didn't report one
+    #[inline]
+    pub fn known_trade_count(&self) -> Option<i64> {
+        (self.trade_count != TRADE_COUNT_UNKNOWN).then_some(self.trade_count)
+    }
+
+    /// Compare candle shape, ignoring timestamps
+    ///
+    /// True if the four tick prices, volume and trade count all match.
+    /// Use `==` to compare timestamps as well.
+    #[inline]
+    pub fn prices_eq(&self, other: &Candle) -> bool {
+        self.open_tick == other.open_tick
+            && self.high_tick == other.high_tick
+            && self.low_tick == other.low_tick
+            && self.close_tick == other.close_tick
+            && self.volume_scaled == other.volume_scaled
+            && self.trade_count == other.trade_count
+    }
+
     /// Convert tick-quantized prices to float prices
     ///
     /// # Arguments
@@ -119,20 +186,611 @@ impl Candle {
 
     /// Create a candle from float prices
     ///
+    /// Assumes sane inputs: a price too large for the tick grid saturates
+    /// at the `i64` range instead of failing. Use
+    /// [`try_from_float_prices`](Self::try_from_float_prices) for untrusted
+    /// data.
+    ///
     /// # Arguments
     /// * `tick_size` - The tick size for quantization
     #[inline]
     pub fn from_float_prices(float_candle: &CandleFloat, tick_size: f64) -> Self {
-        Self {
+        Self::from_float_prices_with(float_candle, tick_size, RoundingMode::Nearest)
+    }
+
+    /// Create a candle from float prices using the given rounding mode
+    ///
+    /// The ticks are normalized with [`normalize_ticks`](Self::normalize_ticks),
+    /// so a valid float candle always quantizes to a valid candle, however
+    /// coarse the tick. Prices may collapse onto the same tick.
+    #[inline]
+    pub fn from_float_prices_with(float_candle: &CandleFloat, tick_size: f64, rounding: RoundingMode) -> Self {
+        let mut candle = Self {
             ts_open: float_candle.ts_open,
             ts_close: float_candle.ts_close,
-            open_tick: (float_candle.open / tick_size).round() as i64,
-            high_tick: (float_candle.high / tick_size).round() as i64,
-            low_tick: (float_candle.low / tick_size).round() as i64,
-            close_tick: (float_candle.close / tick_size).round() as i64,
+            open_tick: rounding.quantize(float_candle.open, tick_size),
+            high_tick: rounding.quantize(float_candle.high, tick_size),
+            low_tick: rounding.quantize(float_candle.low, tick_size),
+            close_tick: rounding.quantize(float_candle.close, tick_size),
             volume_scaled: (float_candle.volume * 1_000_000.0).round() as i64,
             trade_count: float_candle.trade_count,
+        };
+        candle.normalize_ticks();
+        candle
+    }
+
+    /// Like [`from_float_prices`](Self::from_float_prices), but fails instead
+    /// of saturating
+    #[inline]
+    pub fn try_from_float_prices(float_candle: &CandleFloat, tick_size: f64) -> Result<Self, ParseError> {
+        Self::try_from_float_prices_with(float_candle, tick_size, RoundingMode::Nearest)
+    }
+
+    /// Like [`from_float_prices_with`](Self::from_float_prices_with), but
+    /// fails with `InvalidValue` when the tick size is not positive and
+    /// finite, or a price in ticks or the scaled volume is not finite or
+    /// out of `i64` range
+    pub fn try_from_float_prices_with(
+        float_candle: &CandleFloat,
+        tick_size: f64,
+        rounding: RoundingMode,
+    ) -> Result<Self, ParseError> {
+        let invalid = |field: &str, value: f64| ParseError::InvalidValue {
+            field: field.to_string(),
+            value: value.to_string(),
+        };
+        if !(tick_size.is_finite() && tick_size > 0.0) {
+            return Err(invalid("tick_size", tick_size));
+        }
+
+        let scaled = [
+            ("open", float_candle.open, float_candle.open / tick_size),
+            ("high", float_candle.high, float_candle.high / tick_size),
+            ("low", float_candle.low, float_candle.low / tick_size),
+            ("close", float_candle.close, float_candle.close / tick_size),
+            ("volume", float_candle.volume, float_candle.volume * 1_000_000.0),
+        ];
+        for (field, value, scaled) in scaled {
+            // i64::MAX rounds up to 2^63 as a float, so the bound is exclusive
+            if !(scaled.is_finite() && scaled.abs() < i64::MAX as f64) {
+                return Err(invalid(field, value));
+            }
+        }
+
+        Ok(Self::from_float_prices_with(float_candle, tick_size, rounding))
+    }
+}
+
+/// How float prices are snapped to the tick grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Nearest tick, halves away from zero
+    #[default]
+    Nearest,
+
+    /// Truncate toward zero
+    TowardZero,
+
+    /// Away from the touch: bids round down, asks round up. Prices without a
+    /// side (candles, trades) round to nearest.
+    Passive,
+}
+
+/// Distance from a whole tick treated as float noise rather than a real offset,
+/// so e.g. `100.03 / 0.01 = 10002.999999999998` truncates to 10003
+const TICK_EPSILON: f64 = 1e-9;
+
+/// Integer units per price unit in [`price_to_tick`]
+pub const PRICE_NANOS_PER_UNIT: f64 = 1e9;
+
+/// Smallest tick, in nanounits, quantized with integer arithmetic; below it a
+/// nanounit is too large a fraction of a tick
+const MIN_TICK_NANOS: i128 = 1_000;
+
+/// Nearest tick to `price`, halves away from zero, with the same result on
+/// every platform
+///
+/// Dividing floats puts a decimal half tick on either side of the tie
+/// depending on representation error (`0.285 / 0.01` is
+/// `28.499999999999996`). Instead the price and the tick size are each
+/// rounded to whole nanounits (`1e-9`) with one IEEE-754 multiply and
+/// `round`, which are exact-rounded and so bit-identical everywhere, and
+/// the tick count is the `i128` quotient, rounded by comparing the
+/// remainder with half the tick. So `price_to_tick(0.285, 0.01)` is 29.
+///
+/// Halves round away from zero, matching [`RoundingMode::Nearest`]. Prices
+/// resolve to `1e-9`, so ticks finer than `1e-6` fall back to
+/// `(price / tick_size).round()`. Results saturate at the `i64` range.
+///
+/// Assumes a finite `price`: NaN maps to tick 0 and infinities saturate, so
+/// callers taking untrusted prices must check them first.
+pub fn price_to_tick(price: f64, tick_size: f64) -> i64 {
+    match nano_div(price, tick_size) {
+        Some((quotient, rem, tick_nanos)) => {
+            let away = rem.abs() >= tick_nanos - rem.abs();
+            saturate(if away { quotient + rem.signum() } else { quotient })
+        }
+        None => (price / tick_size).round() as i64,
+    }
+}
+
+/// `price / tick_size` in nanounits as `(quotient, remainder, tick)`, with
+/// the quotient truncated toward zero; `None` for ticks finer than
+/// [`MIN_TICK_NANOS`]
+#[inline]
+fn nano_div(price: f64, tick_size: f64) -> Option<(i128, i128, i128)> {
+    let tick_nanos = (tick_size * PRICE_NANOS_PER_UNIT).round() as i128;
+    if tick_nanos < MIN_TICK_NANOS {
+        return None;
+    }
+    let price_nanos = (price * PRICE_NANOS_PER_UNIT).round() as i128;
+    Some((price_nanos / tick_nanos, price_nanos % tick_nanos, tick_nanos))
+}
+
+#[inline]
+fn saturate(ticks: i128) -> i64 {
+    ticks.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+impl RoundingMode {
+    /// Quantize a price with no side, via [`price_to_tick`]'s integer
+    /// arithmetic
+    #[inline]
+    pub fn quantize(self, price: f64, tick_size: f64) -> i64 {
+        match self {
+            RoundingMode::Nearest | RoundingMode::Passive => price_to_tick(price, tick_size),
+            RoundingMode::TowardZero => match nano_div(price, tick_size) {
+                Some((quotient, _, _)) => saturate(quotient),
+                None => {
+                    let ticks = price / tick_size;
+                    snap(ticks).unwrap_or_else(|| ticks.trunc()) as i64
+                }
+            },
+        }
+    }
+
+    /// Quantize a decimal price string to ticks of `10^-decimals` with integer
+    /// arithmetic only, so prices like `0.285` land exactly where they read
+    ///
+    /// Accepts an optional sign, digits and an optional fraction, with no
+    /// exponent. Returns `None` for anything else or on overflow, leaving the
+    /// caller to fall back to [`quantize`](Self::quantize).
+    pub fn quantize_decimal(self, text: &str, decimals: u32) -> Option<i64> {
+        let (negative, digits) = match text.as_bytes().first()? {
+            b'-' => (true, &text[1..]),
+            b'+' => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        if !int_part.bytes().chain(frac_part.bytes()).all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut ticks: i64 = 0;
+        for b in int_part.bytes() {
+            ticks = ticks.checked_mul(10)?.checked_add((b - b'0') as i64)?;
+        }
+        let mut frac = frac_part.bytes();
+        for _ in 0..decimals {
+            let digit = frac.next().map_or(0, |b| (b - b'0') as i64);
+            ticks = ticks.checked_mul(10)?.checked_add(digit)?;
+        }
+
+        // The first dropped digit decides: 5 or more is at least half a tick
+        let round_away = match self {
+            RoundingMode::Nearest | RoundingMode::Passive => frac.next().is_some_and(|b| b >= b'5'),
+            RoundingMode::TowardZero => false,
+        };
+        if round_away {
+            ticks = ticks.checked_add(1)?;
+        }
+        Some(if negative { -ticks } else { ticks })
+    }
+
+    /// Quantize an order price; `Passive` rounds buys down and sells up
+    #[inline]
+    pub fn quantize_order(self, price: f64, tick_size: f64, side: crate::Side) -> i64 {
+        if self != RoundingMode::Passive {
+            return self.quantize(price, tick_size);
+        }
+
+        if let Some((quotient, rem, _)) = nano_div(price, tick_size) {
+            return saturate(match side {
+                crate::Side::Buy if rem < 0 => quotient - 1,
+                crate::Side::Sell if rem > 0 => quotient + 1,
+                _ => quotient,
+            });
+        }
+
+        let ticks = price / tick_size;
+        let rounded = snap(ticks).unwrap_or_else(|| match side {
+            crate::Side::Buy => ticks.floor(),
+            crate::Side::Sell => ticks.ceil(),
+        });
+        rounded as i64
+    }
+}
+
+/// `d` if `tick_size` is `10^-d` (for `d` up to 18), the tick sizes
+/// [`RoundingMode::quantize_decimal`] can handle
+pub fn tick_decimals(tick_size: f64) -> Option<u32> {
+    (0..=18u32).find(|&d| {
+        let power = 10f64.powi(-(d as i32));
+        ((tick_size - power) / power).abs() < TICK_EPSILON
+    })
+}
+
+/// The whole tick count `ticks` is within float noise of, if any
+#[inline]
+fn snap(ticks: f64) -> Option<f64> {
+    let nearest = ticks.round();
+    ((ticks - nearest).abs() < TICK_EPSILON).then_some(nearest)
+}
+
+/// Validate many candles at once
+///
+/// Returns `candles[i].is_valid()` for every `i`. With the `simd` feature the
+/// checks run four candles per lane group; otherwise this is a scalar loop.
+pub fn validate_batch(candles: &[Candle]) -> Vec<bool> {
+    #[cfg(feature = "simd")]
+    {
+        simd::validate_batch(candles)
+    }
+
+    #[cfg(not(feature = "simd"))]
+    {
+        candles.iter().map(Candle::is_valid).collect()
+    }
+}
+
+/// Quantize many float candles with one tick size (nearest-tick rounding)
+///
+/// Equivalent to calling [`Candle::from_float_prices`] on each element.
+pub fn quantize_all(floats: &[CandleFloat], tick_size: f64) -> Vec<Candle> {
+    floats.iter().map(|f| Candle::from_float_prices(f, tick_size)).collect()
+}
+
+/// Like [`quantize_all`], skipping candles that fail
+/// [`CandleFloat::is_valid`]
+///
+/// Returns the quantized valid candles and the indices of the skipped ones.
+pub fn quantize_valid(floats: &[CandleFloat], tick_size: f64) -> (Vec<Candle>, Vec<usize>) {
+    let mut candles = Vec::with_capacity(floats.len());
+    let mut invalid = Vec::new();
+    for (i, f) in floats.iter().enumerate() {
+        if f.is_valid() {
+            candles.push(Candle::from_float_prices(f, tick_size));
+        } else {
+            invalid.push(i);
+        }
+    }
+    (candles, invalid)
+}
+
+/// Whether `ts_open` never decreases from one candle to the next
+///
+/// Equal timestamps count as sorted. See
+/// [`crate::candle_parser::CandleParser::assert_sorted`] for the streaming check.
+pub fn sorted_by_ts(candles: &[Candle]) -> bool {
+    candles.windows(2).all(|pair| pair[0].ts_open <= pair[1].ts_open)
+}
+
+/// Most flat candles [`fill_gaps`] inserts into a single gap
+pub const MAX_GAP_FILL: usize = 10_000;
+
+/// Insert flat candles at the missing `interval_ms` slots between
+/// consecutive candles
+///
+/// A filler opens at every `prev.ts_open + k * interval_ms` before the next
+/// candle, spans as long as `prev`, and has all four prices at `prev`'s
+/// close with zero volume and zero trades. Input should be sorted by
+/// `ts_open`; out-of-order neighbours are left as they are. A gap needing
+/// more than [`MAX_GAP_FILL`] candles is an error, since it usually means a
+/// wrong interval or a hole that should not be papered over.
+///
+/// # Panics
+/// If `interval_ms` is not positive.
+pub fn fill_gaps(candles: &[Candle], interval_ms: i64) -> Result<Vec<Candle>, ParseError> {
+    assert!(interval_ms > 0, "interval must be positive");
+    let Some(first) = candles.first() else {
+        return Ok(Vec::new());
+    };
+
+    let mut out = Vec::with_capacity(candles.len());
+    out.push(*first);
+    for pair in candles.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        let gap = next.ts_open.saturating_sub(prev.ts_open);
+        let missing = if gap > interval_ms { (gap - 1) / interval_ms } else { 0 };
+        if missing > MAX_GAP_FILL as i64 {
+            return Err(ParseError::GapTooLarge {
+                after_ts: prev.ts_open,
+                missing,
+                max: MAX_GAP_FILL,
+            });
+        }
+
+        let span = prev.ts_close - prev.ts_open;
+        for k in 1..=missing {
+            let ts_open = prev.ts_open + k * interval_ms;
+            out.push(Candle {
+                ts_open,
+                ts_close: ts_open + span,
+                open_tick: prev.close_tick,
+                high_tick: prev.close_tick,
+                low_tick: prev.close_tick,
+                close_tick: prev.close_tick,
+                volume_scaled: 0,
+                trade_count: 0,
+            });
+        }
+        out.push(*next);
+    }
+    Ok(out)
+}
+
+/// Dataset-level statistics from [`summarize`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CandleSummary {
+    pub count: u64,
+    /// Earliest `ts_open` (0 when empty)
+    pub first_ts_ms: i64,
+    /// Latest `ts_close` (0 when empty)
+    pub last_ts_ms: i64,
+    /// Lowest low, in price units (0 when empty)
+    pub min_price: f64,
+    /// Highest high, in price units (0 when empty)
+    pub max_price: f64,
+    /// Sum of bar volumes, in base units
+    pub total_volume: f64,
+    /// Sum of known trade counts; bars with an unknown count are skipped
+    pub total_trades: i64,
+    /// Mean `high - low`, in price units (0 when empty)
+    pub avg_range: f64,
+}
+
+impl CandleSummary {
+    /// `last_ts_ms - first_ts_ms`
+    pub fn span_ms(&self) -> i64 {
+        self.last_ts_ms - self.first_ts_ms
+    }
+}
+
+/// Summarize candles in one streaming pass, keeping O(1) state
+///
+/// Prices are accumulated in ticks and converted with `tick_size` at the end.
+pub fn summarize(candles: impl Iterator<Item = Candle>, tick_size: f64) -> CandleSummary {
+    let mut count = 0u64;
+    let mut first_ts = i64::MAX;
+    let mut last_ts = i64::MIN;
+    let mut low = i64::MAX;
+    let mut high = i64::MIN;
+    let mut volume_scaled = 0i128;
+    let mut range_ticks = 0i128;
+    let mut trades = 0i64;
+
+    for candle in candles {
+        count += 1;
+        first_ts = first_ts.min(candle.ts_open);
+        last_ts = last_ts.max(candle.ts_close);
+        low = low.min(candle.low_tick);
+        high = high.max(candle.high_tick);
+        volume_scaled += candle.volume_scaled as i128;
+        range_ticks += (candle.high_tick - candle.low_tick) as i128;
+        trades += candle.known_trade_count().unwrap_or(0);
+    }
+
+    if count == 0 {
+        return CandleSummary::default();
+    }
+
+    CandleSummary {
+        count,
+        first_ts_ms: first_ts,
+        last_ts_ms: last_ts,
+        min_price: low as f64 * tick_size,
+        max_price: high as f64 * tick_size,
+        total_volume: volume_scaled as f64 / 1_000_000.0,
+        total_trades: trades,
+        avg_range: range_ticks as f64 / count as f64 * tick_size,
+    }
+}
+
+#[cfg(feature = "simd")]
+mod simd {
+    use super::Candle;
+    use wide::{i64x4, CmpGt, CmpLt};
+
+    const LANES: usize = 4;
+
+    /// Gather one field of four candles into a vector
+    #[inline(always)]
+    fn gather(chunk: &[Candle], field: impl Fn(&Candle) -> i64) -> i64x4 {
+        i64x4::new([field(&chunk[0]), field(&chunk[1]), field(&chunk[2]), field(&chunk[3])])
+    }
+
+    pub(super) fn validate_batch(candles: &[Candle]) -> Vec<bool> {
+        let mut out = Vec::with_capacity(candles.len());
+        let zero = i64x4::splat(0);
+        let one = i64x4::splat(1);
+        let unknown = i64x4::splat(super::TRADE_COUNT_UNKNOWN);
+
+        let mut chunks = candles.chunks_exact(LANES);
+        for chunk in &mut chunks {
+            let ts_open = gather(chunk, |c| c.ts_open);
+            let ts_close = gather(chunk, |c| c.ts_close);
+            let open = gather(chunk, |c| c.open_tick);
+            let high = gather(chunk, |c| c.high_tick);
+            let low = gather(chunk, |c| c.low_tick);
+            let close = gather(chunk, |c| c.close_tick);
+            let volume = gather(chunk, |c| c.volume_scaled);
+            let trades = gather(chunk, |c| c.trade_count);
+
+            // Lanes are all-ones wherever a check fails
+            let invalid = ts_open.cmp_lt(one)
+                | ts_close.cmp_lt(one)
+                | ts_close.cmp_lt(ts_open)
+                | low.cmp_gt(high)
+                | open.cmp_lt(low)
+                | open.cmp_gt(high)
+                | close.cmp_lt(low)
+                | close.cmp_gt(high)
+                | volume.cmp_lt(zero)
+                | trades.cmp_lt(unknown);
+
+            let mask = invalid.move_mask();
+            out.extend((0..LANES).map(|lane| mask & (1 << lane) == 0));
+        }
+
+        out.extend(chunks.remainder().iter().map(Candle::is_valid));
+        out
+    }
+}
+
+#[cfg(feature = "arrow")]
+pub use arrow_export::{candle_schema, from_record_batch, to_record_batch};
+
+#[cfg(feature = "arrow")]
+mod arrow_export {
+    use super::{Candle, CandleFloat, TRADE_COUNT_UNKNOWN};
+    use crate::candle_parser::ParseError;
+    use arrow_array::{Array, Float64Array, Int64Array, RecordBatch, TimestampMillisecondArray};
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    /// Schema of [`to_record_batch`] output
+    ///
+    /// `ts_open`/`ts_close` are millisecond timestamps, prices and volume are
+    /// floats, and `trade_count` is null where it is unknown.
+    pub fn candle_schema() -> Schema {
+        let ts = DataType::Timestamp(TimeUnit::Millisecond, None);
+        Schema::new(vec![
+            Field::new("ts_open", ts.clone(), false),
+            Field::new("ts_close", ts, false),
+            Field::new("open", DataType::Float64, false),
+            Field::new("high", DataType::Float64, false),
+            Field::new("low", DataType::Float64, false),
+            Field::new("close", DataType::Float64, false),
+            Field::new("volume", DataType::Float64, false),
+            Field::new("trade_count", DataType::Int64, true),
+        ])
+    }
+
+    /// Candles as an Arrow record batch with float prices
+    pub fn to_record_batch(candles: &[Candle], tick_size: f64) -> RecordBatch {
+        let floats: Vec<CandleFloat> = candles.iter().map(|c| c.to_float_prices(tick_size)).collect();
+        let prices = |field: fn(&CandleFloat) -> f64| -> Arc<dyn Array> {
+            Arc::new(floats.iter().map(field).collect::<Float64Array>())
+        };
+
+        let columns: Vec<Arc<dyn Array>> = vec![
+            Arc::new(TimestampMillisecondArray::from_iter_values(candles.iter().map(|c| c.ts_open))),
+            Arc::new(TimestampMillisecondArray::from_iter_values(candles.iter().map(|c| c.ts_close))),
+            prices(|c| c.open),
+            prices(|c| c.high),
+            prices(|c| c.low),
+            prices(|c| c.close),
+            prices(|c| c.volume),
+            Arc::new(candles.iter().map(Candle::known_trade_count).collect::<Int64Array>()),
+        ];
+
+        RecordBatch::try_new(Arc::new(candle_schema()), columns)
+            .expect("columns are built to match candle_schema")
+    }
+
+    /// Typed column `name`, or an error naming what is missing or mistyped
+    fn column<'a, A: Array + 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a A, ParseError> {
+        let array = batch
+            .column_by_name(name)
+            .ok_or_else(|| ParseError::MissingField(name.to_string()))?;
+        array.as_any().downcast_ref::<A>().ok_or_else(|| ParseError::InvalidValue {
+            field: name.to_string(),
+            value: format!("column of type {}", array.data_type()),
+        })
+    }
+
+    /// Quantize a record batch in the [`candle_schema`] layout back into candles
+    ///
+    /// Columns are looked up by name, so extra columns and a different order
+    /// are fine. Nulls are rejected except in `trade_count`, where they mean
+    /// [`TRADE_COUNT_UNKNOWN`].
+    pub fn from_record_batch(batch: &RecordBatch, tick_size: f64) -> Result<Vec<Candle>, ParseError> {
+        let ts_open = column::<TimestampMillisecondArray>(batch, "ts_open")?;
+        let ts_close = column::<TimestampMillisecondArray>(batch, "ts_close")?;
+        let open = column::<Float64Array>(batch, "open")?;
+        let high = column::<Float64Array>(batch, "high")?;
+        let low = column::<Float64Array>(batch, "low")?;
+        let close = column::<Float64Array>(batch, "close")?;
+        let volume = column::<Float64Array>(batch, "volume")?;
+        let trade_count = column::<Int64Array>(batch, "trade_count")?;
+
+        let required: [(&str, &dyn Array); 7] = [
+            ("ts_open", ts_open),
+            ("ts_close", ts_close),
+            ("open", open),
+            ("high", high),
+            ("low", low),
+            ("close", close),
+            ("volume", volume),
+        ];
+        if let Some((name, _)) = required.iter().find(|(_, array)| array.null_count() > 0) {
+            return Err(ParseError::InvalidValue {
+                field: name.to_string(),
+                value: "null".to_string(),
+            });
+        }
+
+        Ok((0..batch.num_rows())
+            .map(|i| {
+                let float = CandleFloat {
+                    ts_open: ts_open.value(i),
+                    ts_close: ts_close.value(i),
+                    open: open.value(i),
+                    high: high.value(i),
+                    low: low.value(i),
+                    close: close.value(i),
+                    volume: volume.value(i),
+                    trade_count: if trade_count.is_null(i) { TRADE_COUNT_UNKNOWN } else { trade_count.value(i) },
+                };
+                Candle::from_float_prices(&float, tick_size)
+            })
+            .collect())
+    }
+}
+
+/// How [`CandleFloat::is_valid_with_policy`] and the parsers treat a NaN or
+/// infinite volume
+///
+/// Only volume is covered: a non-finite open, high, low or close fails
+/// validation under every policy, and trade counts are integers so they
+/// cannot be non-finite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NanPolicy {
+    /// The candle is invalid
+    #[default]
+    Reject,
+    /// The volume reads as zero, so a parser's [`ZeroVolumePolicy`] applies
+    /// to it like any other zero-volume candle
+    ///
+    /// [`ZeroVolumePolicy`]: crate::candle_parser::ZeroVolumePolicy
+    TreatAsZero,
+    /// The volume is unknown: the float candle keeps it as is, and a
+    /// parser yields volume 0 without applying its zero-volume policy
+    Propagate,
+}
+
+impl NanPolicy {
+    /// Settle a non-finite volume for quantization, replacing it with zero;
+    /// returns whether the zero-volume policy should still see the candle
+    pub(crate) fn settle_volume(self, float_candle: &mut CandleFloat) -> bool {
+        if float_candle.volume.is_finite() {
+            return true;
         }
+        float_candle.volume = 0.0;
+        self != NanPolicy::Propagate
     }
 }
 
@@ -146,13 +804,27 @@ pub struct CandleFloat {
     pub low: f64,
     pub close: f64,
     pub volume: f64,
+    /// Trade count, or [`TRADE_COUNT_UNKNOWN`]
     pub trade_count: i64,
 }
 
 impl CandleFloat {
     /// Validate candle data (float version)
+    ///
+    /// Any non-finite field fails; see [`is_valid_with_policy`](Self::is_valid_with_policy)
+    /// to accept a NaN volume.
     #[inline]
     pub fn is_valid(&self) -> bool {
+        self.is_valid_with_policy(NanPolicy::Reject)
+    }
+
+    /// Validate candle data, with `policy` deciding a non-finite volume
+    ///
+    /// NaN or infinite OHLC always fails regardless of the policy. Under
+    /// `TreatAsZero` and `Propagate` a non-finite volume passes, even
+    /// negative infinity, since it says nothing about the actual volume.
+    #[inline]
+    pub fn is_valid_with_policy(&self, policy: NanPolicy) -> bool {
         // Timestamp validation
         if self.ts_open <= 0 || self.ts_close <= 0 {
             return false;
@@ -182,17 +854,211 @@ impl CandleFloat {
         }
 
         // Volume validation
-        if !self.volume.is_finite() || self.volume < 0.0 {
+        if !self.volume.is_finite() {
+            if policy == NanPolicy::Reject {
+                return false;
+            }
+        } else if self.volume < 0.0 {
             return false;
         }
 
         // Trade count validation
-        if self.trade_count < 0 {
+        if self.trade_count < TRADE_COUNT_UNKNOWN {
             return false;
         }
 
         true
     }
+
+    /// Float counterpart of [`Candle::repair`]
+    ///
+    /// Non-finite prices are never repaired (returns false), so NaN/inf candles
+    /// still fail validation.
+    #[inline]
+    pub fn repair(&mut self) -> bool {
+        if !self.open.is_finite() || !self.high.is_finite()
+           || !self.low.is_finite() || !self.close.is_finite() {
+            return false;
+        }
+
+        let high = self.high.max(self.open).max(self.close);
+        let low = self.low.min(self.open).min(self.close);
+        let changed = high != self.high || low != self.low;
+
+        self.high = high;
+        self.low = low;
+        changed
+    }
+}
+
+/// Which measure of a candle looked like a bad print
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// Close far from the rolling median close
+    Close,
+    /// High-low range far from the rolling median range
+    Range,
+}
+
+/// An outlier flagged by [`AnomalyDetector`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    /// Deviation from the rolling median in robust standard deviations
+    pub score: f64,
+}
+
+/// Rolling window of values with median and MAD
+#[derive(Debug, Clone)]
+struct RollingMedian {
+    window: usize,
+    recent: VecDeque<i64>,
+    sorted: Vec<i64>,
+}
+
+impl RollingMedian {
+    fn new(window: usize) -> Self {
+        Self {
+            window,
+            recent: VecDeque::with_capacity(window),
+            sorted: Vec::with_capacity(window),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.recent.len() == self.window
+    }
+
+    fn push(&mut self, value: i64) {
+        if self.recent.len() == self.window {
+            if let Some(old) = self.recent.pop_front() {
+                if let Ok(idx) = self.sorted.binary_search(&old) {
+                    self.sorted.remove(idx);
+                }
+            }
+        }
+        self.recent.push_back(value);
+        let idx = self.sorted.partition_point(|&v| v < value);
+        self.sorted.insert(idx, value);
+    }
+
+    fn median(&self) -> f64 {
+        let values: Vec<f64> = self.sorted.iter().map(|&v| v as f64).collect();
+        median_of_sorted(&values)
+    }
+
+    /// Robust z-score of `value`: distance from the median over the scaled MAD
+    ///
+    /// The scale is floored at one tick so a perfectly flat window doesn't
+    /// turn every one-tick move into an infinite score.
+    fn robust_z(&self, value: i64) -> f64 {
+        let median = self.median();
+        let mut deviations: Vec<f64> = self.sorted.iter().map(|&v| (v as f64 - median).abs()).collect();
+        deviations.sort_by(f64::total_cmp);
+        let sigma = (median_of_sorted(&deviations) * MAD_TO_SIGMA).max(1.0);
+        (value as f64 - median).abs() / sigma
+    }
+}
+
+fn median_of_sorted(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n == 0 {
+        return 0.0;
+    }
+    if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    }
+}
+
+/// Scales the median absolute deviation to a standard deviation for normal data
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+/// Streaming detector for outlier candles (likely bad prints)
+///
+/// Keeps the last `window` closes and high-low ranges, and flags a candle
+/// whose close or range is more than `threshold` robust standard deviations
+/// (MAD-based) from the rolling median. Nothing is flagged until the window
+/// has filled. Every candle, flagged or not, then joins the window; the
+/// median is robust enough that single spikes don't distort it.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    threshold: f64,
+    closes: RollingMedian,
+    ranges: RollingMedian,
+    anomalies: u64,
+    metrics: Option<std::sync::Arc<crate::market_event::IngestionMetrics>>,
+}
+
+impl AnomalyDetector {
+    /// # Panics
+    /// If `window` is zero.
+    pub fn new(window: usize, threshold: f64) -> Self {
+        assert!(window > 0, "anomaly window must be positive");
+        Self {
+            threshold,
+            closes: RollingMedian::new(window),
+            ranges: RollingMedian::new(window),
+            anomalies: 0,
+            metrics: None,
+        }
+    }
+
+    /// Also count anomalies in `metrics.candles_anomalous`, e.g. the handle
+    /// of the adapter feeding this detector
+    pub fn with_metrics(mut self, metrics: std::sync::Arc<crate::market_event::IngestionMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Check a candle against the window, then add it
+    ///
+    /// When both the close and the range are outliers, the larger score wins.
+    pub fn check(&mut self, candle: &Candle) -> Option<Anomaly> {
+        let range = candle.high_tick - candle.low_tick;
+        let mut anomaly = None;
+
+        if self.closes.is_full() {
+            let close_z = self.closes.robust_z(candle.close_tick);
+            let range_z = self.ranges.robust_z(range);
+            let (kind, score) = if close_z >= range_z {
+                (AnomalyKind::Close, close_z)
+            } else {
+                (AnomalyKind::Range, range_z)
+            };
+            if score > self.threshold {
+                anomaly = Some(Anomaly { kind, score });
+            }
+        }
+
+        self.closes.push(candle.close_tick);
+        self.ranges.push(range);
+
+        if anomaly.is_some() {
+            self.anomalies += 1;
+            if let Some(metrics) = &self.metrics {
+                metrics.candles_anomalous.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+        anomaly
+    }
+
+    /// Pair each candle with its anomaly, if any
+    pub fn annotate<I>(mut self, candles: I) -> impl Iterator<Item = (Candle, Option<Anomaly>)>
+    where
+        I: IntoIterator<Item = Candle>,
+    {
+        candles.into_iter().map(move |candle| {
+            let anomaly = self.check(&candle);
+            (candle, anomaly)
+        })
+    }
+
+    /// Number of candles flagged so far
+    pub fn anomaly_count(&self) -> u64 {
+        self.anomalies
+    }
 }
 
 #[cfg(test)]
@@ -420,10 +1286,16 @@ mod tests {
             low_tick: 4150,
             close_tick: 4220,
             volume_scaled: 1_500_000_000,
-            trade_count: -1,
+            trade_count: -2,
         };
 
         assert!(!candle.is_valid());
+
+        // -1 is the "unknown" sentinel, not a negative count
+        let unknown = Candle { trade_count: TRADE_COUNT_UNKNOWN, ..candle };
+        assert!(unknown.is_valid());
+        assert_eq!(unknown.known_trade_count(), None);
+        assert_eq!(Candle { trade_count: 0, ..candle }.known_trade_count(), Some(0));
     }
 
     #[test]
@@ -496,6 +1368,38 @@ mod tests {
         assert!(!neg_volume.is_valid());
     }
 
+    #[test]
+    fn test_nan_volume_policies() {
+        let valid = CandleFloat {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.5,
+            volume: 10.0,
+            trade_count: TRADE_COUNT_UNKNOWN,
+        };
+        let nan_volume = CandleFloat { volume: f64::NAN, ..valid.clone() };
+        let nan_close = CandleFloat { close: f64::NAN, ..valid.clone() };
+
+        assert!(!nan_volume.is_valid());
+        assert!(!nan_volume.is_valid_with_policy(NanPolicy::Reject));
+        assert!(nan_volume.is_valid_with_policy(NanPolicy::TreatAsZero));
+        assert!(nan_volume.is_valid_with_policy(NanPolicy::Propagate));
+        for policy in [NanPolicy::Reject, NanPolicy::TreatAsZero, NanPolicy::Propagate] {
+            assert!(!nan_close.is_valid_with_policy(policy), "{:?}", policy);
+            assert!(valid.is_valid_with_policy(policy), "{:?}", policy);
+        }
+
+        let mut zeroed = nan_volume.clone();
+        assert!(NanPolicy::TreatAsZero.settle_volume(&mut zeroed));
+        assert_eq!(zeroed.volume, 0.0);
+        let mut unknown = nan_volume.clone();
+        assert!(!NanPolicy::Propagate.settle_volume(&mut unknown));
+        assert_eq!(unknown.volume, 0.0);
+    }
+
     #[test]
     fn test_round_trip_conversion() {
         let original = CandleFloat {
@@ -529,4 +1433,493 @@ mod tests {
         assert_eq!(recovered.ts_close, original.ts_close);
         assert_eq!(recovered.trade_count, original.trade_count);
     }
+
+    #[test]
+    fn test_repair_near_miss_candle() {
+        // High misses close by one tick, low misses open by one tick
+        let mut candle = Candle {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open_tick: 4149,
+            high_tick: 4250,
+            low_tick: 4150,
+            close_tick: 4251,
+            volume_scaled: 1_500_000_000,
+            trade_count: 42,
+        };
+        assert!(!candle.is_valid());
+
+        assert!(candle.repair());
+        assert!(candle.is_valid());
+        assert_eq!(candle.high_tick, 4251);
+        assert_eq!(candle.low_tick, 4149);
+
+        // Already valid: nothing to do
+        assert!(!candle.repair());
+    }
+
+    #[test]
+    fn test_float_repair_skips_non_finite() {
+        let mut candle = CandleFloat {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open: 100.0,
+            high: f64::NAN,
+            low: 99.0,
+            close: 100.5,
+            volume: 10.0,
+            trade_count: 1,
+        };
+
+        assert!(!candle.repair());
+        assert!(!candle.is_valid());
+    }
+
+    #[test]
+    fn test_prices_eq_ignores_timestamps() {
+        let candle = Candle {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open_tick: 4200,
+            high_tick: 4250,
+            low_tick: 4150,
+            close_tick: 4220,
+            volume_scaled: 1_500_000_000,
+            trade_count: 42,
+        };
+        let shifted = Candle {
+            ts_open: candle.ts_open + 60000,
+            ts_close: candle.ts_close + 60000,
+            ..candle
+        };
+
+        assert!(candle.prices_eq(&shifted));
+        assert_ne!(candle, shifted);
+
+        let different_close = Candle { close_tick: 4221, ..candle };
+        assert!(!candle.prices_eq(&different_close));
+
+        let different_volume = Candle { volume_scaled: 1, ..candle };
+        assert!(!candle.prices_eq(&different_volume));
+    }
+
+    #[test]
+    fn test_full_equality() {
+        let candle = Candle {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open_tick: 4200,
+            high_tick: 4250,
+            low_tick: 4150,
+            close_tick: 4220,
+            volume_scaled: 1_500_000_000,
+            trade_count: 42,
+        };
+        let copy = candle;
+
+        assert_eq!(candle, copy);
+        assert_ne!(candle, Candle { trade_count: 43, ..candle });
+        assert_eq!(Candle::default(), Candle::default());
+    }
+
+    #[test]
+    fn test_rounding_modes_on_half_tick() {
+        use crate::Side;
+
+        // 100.25 is exactly 200.5 ticks of 0.5
+        let price = 100.25;
+        let tick_size = 0.5;
+
+        assert_eq!(RoundingMode::Nearest.quantize(price, tick_size), 201);
+        assert_eq!(RoundingMode::TowardZero.quantize(price, tick_size), 200);
+        assert_eq!(RoundingMode::Passive.quantize(price, tick_size), 201);
+
+        assert_eq!(RoundingMode::Passive.quantize_order(price, tick_size, Side::Buy), 200);
+        assert_eq!(RoundingMode::Passive.quantize_order(price, tick_size, Side::Sell), 201);
+        assert_eq!(RoundingMode::TowardZero.quantize_order(price, tick_size, Side::Sell), 200);
+        assert_eq!(RoundingMode::TowardZero.quantize(-price, tick_size), -200);
+    }
+
+    #[test]
+    fn test_coarse_tick_quantizes_to_valid_candle() {
+        // Every price lands on one of two ticks of 100
+        let float_candle = CandleFloat {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open: 4249.0,
+            high: 4251.0,
+            low: 4149.0,
+            close: 4150.0,
+            volume: 1.0,
+            trade_count: 1,
+        };
+        for rounding in [RoundingMode::Nearest, RoundingMode::TowardZero, RoundingMode::Passive] {
+            let candle = Candle::from_float_prices_with(&float_candle, 100.0, rounding);
+            assert!(candle.is_valid(), "{:?}: {:?}", rounding, candle);
+        }
+        let collapsed = Candle::from_float_prices(&CandleFloat { high: 4249.5, ..float_candle }, 1000.0);
+        assert_eq!((collapsed.open_tick, collapsed.high_tick, collapsed.low_tick, collapsed.close_tick), (4, 4, 4, 4));
+
+        // Open and close are pulled into the range; high and low are kept
+        let mut candle = Candle { open_tick: 43, high_tick: 42, low_tick: 41, close_tick: 40, ..Candle::default() };
+        assert!(candle.normalize_ticks());
+        assert_eq!((candle.open_tick, candle.high_tick, candle.low_tick, candle.close_tick), (42, 42, 41, 41));
+        assert!(!candle.normalize_ticks());
+
+        let mut inverted = Candle { open_tick: 5, high_tick: 3, low_tick: 7, close_tick: 9, ..Candle::default() };
+        assert!(inverted.normalize_ticks());
+        assert_eq!((inverted.open_tick, inverted.high_tick, inverted.low_tick, inverted.close_tick), (5, 7, 3, 7));
+    }
+
+    #[test]
+    fn test_decimal_quantize_is_exact() {
+        assert_eq!(RoundingMode::Nearest.quantize(0.285, 0.01), 29);
+        assert_eq!(RoundingMode::Nearest.quantize_decimal("0.285", 2), Some(29));
+        assert_eq!(RoundingMode::Nearest.quantize_decimal("-1.005", 2), Some(-101));
+        assert_eq!(RoundingMode::TowardZero.quantize_decimal("0.289", 2), Some(28));
+        assert_eq!(RoundingMode::Nearest.quantize_decimal("42000", 2), Some(4_200_000));
+        assert_eq!(RoundingMode::Nearest.quantize_decimal(".5", 1), Some(5));
+        assert_eq!(RoundingMode::Nearest.quantize_decimal("+7.", 0), Some(7));
+
+        for text in ["", "-", ".", "1e-3", "0x10", "1.2.3", "99999999999999999999"] {
+            assert_eq!(RoundingMode::Nearest.quantize_decimal(text, 2), None, "{:?}", text);
+        }
+
+        assert_eq!(tick_decimals(0.01), Some(2));
+        assert_eq!(tick_decimals(1.0), Some(0));
+        assert_eq!(tick_decimals(1e-8), Some(8));
+        assert_eq!(tick_decimals(0.25), None);
+        assert_eq!(tick_decimals(0.05), None);
+    }
+
+    #[test]
+    fn test_price_to_tick_half_ticks() {
+        // 0.285 / 0.01 is 28.499999999999996 in f64, so plain float division
+        // rounds this half tick down
+        assert_eq!((0.285f64 / 0.01).round() as i64, 28);
+        assert_eq!(price_to_tick(0.285, 0.01), 29);
+        assert_eq!(price_to_tick(-0.285, 0.01), -29);
+        assert_eq!(price_to_tick(1.005, 0.01), 101);
+        assert_eq!(price_to_tick(100.25, 0.5), 201);
+        assert_eq!(price_to_tick(0.28499, 0.01), 28);
+        assert_eq!(price_to_tick(4210.125, 0.25), 16841);
+
+        // Sub-micro ticks fall back to float division
+        assert_eq!(price_to_tick(3.0e-8, 1e-8), 3);
+        assert_eq!(price_to_tick(f64::INFINITY, 0.01), i64::MAX);
+        assert_eq!(price_to_tick(f64::NAN, 0.01), 0);
+    }
+
+    #[test]
+    fn test_rounding_ignores_float_noise() {
+        use crate::Side;
+
+        // 100.03 / 0.01 is 10002.999999999998 in f64
+        assert_eq!(RoundingMode::TowardZero.quantize(100.03, 0.01), 10003);
+        assert_eq!(RoundingMode::Passive.quantize_order(100.03, 0.01, Side::Buy), 10003);
+        assert_eq!(RoundingMode::Passive.quantize_order(100.03, 0.01, Side::Sell), 10003);
+
+        let float_candle = CandleFloat {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open: 100.25,
+            high: 100.75,
+            low: 100.0,
+            close: 100.5,
+            volume: 1.0,
+            trade_count: 1,
+        };
+        let nearest = Candle::from_float_prices_with(&float_candle, 0.5, RoundingMode::Nearest);
+        let truncated = Candle::from_float_prices_with(&float_candle, 0.5, RoundingMode::TowardZero);
+        assert_eq!((nearest.open_tick, nearest.high_tick), (201, 202));
+        assert_eq!((truncated.open_tick, truncated.high_tick), (200, 201));
+    }
+
+    #[test]
+    fn test_validate_batch_matches_is_valid() {
+        // Small xorshift so the values cluster around the boundaries of each check
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 7) as i64 - 3
+        };
+
+        let candles: Vec<Candle> = (0..10_003)
+            .map(|_| Candle {
+                ts_open: 100 + next() * 40,
+                ts_close: 100 + next() * 40,
+                open_tick: 50 + next(),
+                high_tick: 52 + next(),
+                low_tick: 48 + next(),
+                close_tick: 50 + next(),
+                volume_scaled: next(),
+                trade_count: next(),
+            })
+            .collect();
+
+        let expected: Vec<bool> = candles.iter().map(Candle::is_valid).collect();
+        assert!(expected.iter().any(|&v| v) && expected.iter().any(|&v| !v));
+        assert_eq!(validate_batch(&candles), expected);
+
+        for len in 0..8 {
+            assert_eq!(validate_batch(&candles[..len]), expected[..len].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_sorted_by_ts() {
+        let at = |ts_open| Candle { ts_open, ts_close: ts_open + 60_000, ..Candle::default() };
+
+        assert!(sorted_by_ts(&[]));
+        assert!(sorted_by_ts(&[at(5)]));
+        assert!(sorted_by_ts(&[at(1), at(2), at(2), at(3)]));
+        // Two sorted files concatenated in the wrong order
+        assert!(!sorted_by_ts(&[at(3), at(4), at(1), at(2)]));
+    }
+
+    #[test]
+    fn test_try_from_float_prices_rejects_overflow() {
+        let float_candle = CandleFloat {
+            ts_open: 1_000,
+            ts_close: 61_000,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.5,
+            volume: 2.5,
+            trade_count: 3,
+        };
+        assert_eq!(
+            Candle::try_from_float_prices(&float_candle, 0.5).unwrap(),
+            Candle::from_float_prices(&float_candle, 0.5)
+        );
+
+        let absurd = CandleFloat { high: 1e300, ..float_candle };
+        assert!(matches!(
+            Candle::try_from_float_prices(&absurd, 0.01),
+            Err(ParseError::InvalidValue { field, .. }) if field == "high"
+        ));
+        // The infallible conversion saturates instead
+        assert_eq!(Candle::from_float_prices(&absurd, 0.01).high_tick, i64::MAX);
+
+        let nan_close = CandleFloat { close: f64::NAN, ..float_candle };
+        assert!(Candle::try_from_float_prices(&nan_close, 0.01).is_err());
+        let huge_volume = CandleFloat { volume: 1e20, ..float_candle };
+        assert!(Candle::try_from_float_prices(&huge_volume, 0.01).is_err());
+        assert!(Candle::try_from_float_prices(&float_candle, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_fill_gaps_one_interval() {
+        let bar = |ts_open, close_tick| Candle {
+            ts_open,
+            ts_close: ts_open + 59_999,
+            open_tick: 100,
+            high_tick: 110,
+            low_tick: 90,
+            close_tick,
+            volume_scaled: 5_000_000,
+            trade_count: 12,
+        };
+
+        let filled = fill_gaps(&[bar(0, 105), bar(120_000, 107), bar(180_000, 104)], 60_000).unwrap();
+        assert_eq!(filled.len(), 4);
+        assert_eq!(filled[0], bar(0, 105));
+        assert_eq!(filled[2], bar(120_000, 107));
+
+        let flat = filled[1];
+        assert_eq!((flat.ts_open, flat.ts_close), (60_000, 119_999));
+        assert_eq!(
+            (flat.open_tick, flat.high_tick, flat.low_tick, flat.close_tick),
+            (105, 105, 105, 105)
+        );
+        assert_eq!((flat.volume_scaled, flat.trade_count), (0, 0));
+
+        assert!(fill_gaps(&[], 60_000).unwrap().is_empty());
+        let huge = fill_gaps(&[bar(0, 105), bar(60_000 * (MAX_GAP_FILL as i64 + 2), 105)], 60_000);
+        assert!(matches!(
+            huge,
+            Err(ParseError::GapTooLarge { missing, .. }) if missing == MAX_GAP_FILL as i64 + 1
+        ));
+    }
+
+    #[test]
+    fn test_quantize_all_matches_element_wise() {
+        let floats: Vec<CandleFloat> = (0..50)
+            .map(|i| CandleFloat {
+                ts_open: 1609459200000 + i * 60000,
+                ts_close: 1609459260000 + i * 60000,
+                open: 42000.5 + i as f64 * 0.37,
+                high: 42100.25 + i as f64 * 0.37,
+                low: 41900.0 + i as f64 * 0.37,
+                close: 42050.75 + i as f64 * 0.37,
+                volume: 1.5 + i as f64,
+                trade_count: i,
+            })
+            .collect();
+
+        let expected: Vec<Candle> = floats.iter().map(|f| Candle::from_float_prices(f, 0.25)).collect();
+        assert_eq!(quantize_all(&floats, 0.25), expected);
+
+        let mut with_bad = floats.clone();
+        with_bad[3].high = f64::NAN;
+        with_bad[7].low = with_bad[7].high + 1.0;
+        let (candles, invalid) = quantize_valid(&with_bad, 0.25);
+        assert_eq!(invalid, vec![3, 7]);
+        assert_eq!(candles.len(), 48);
+        assert_eq!(candles[3], expected[4]);
+    }
+
+    #[test]
+    fn test_anomaly_detector_flags_spike_only() {
+        let candles: Vec<Candle> = (0..60)
+            .map(|i| {
+                let close = 4200 + (i * 7) % 11 - 5;
+                Candle {
+                    ts_open: 1609459200000 + i * 60000,
+                    ts_close: 1609459260000 + i * 60000,
+                    open_tick: close,
+                    high_tick: close + 3 + i % 3,
+                    low_tick: close - 3,
+                    close_tick: close,
+                    volume_scaled: 1_000_000,
+                    trade_count: 1,
+                }
+            })
+            .collect();
+
+        let mut with_spikes = candles.clone();
+        // Bad print: the whole bar at 10x the surrounding level
+        let spike = &mut with_spikes[30];
+        spike.open_tick *= 10;
+        spike.high_tick = spike.open_tick + 3;
+        spike.low_tick = spike.open_tick - 3;
+        spike.close_tick = spike.open_tick;
+        // Range blowout with an ordinary close
+        with_spikes[45].high_tick += 400;
+
+        let metrics = std::sync::Arc::new(crate::market_event::IngestionMetrics::new());
+        let detector = AnomalyDetector::new(20, 5.0).with_metrics(metrics.clone());
+        let flagged: Vec<(usize, Anomaly)> = detector
+            .annotate(with_spikes)
+            .enumerate()
+            .filter_map(|(i, (_, anomaly))| anomaly.map(|a| (i, a)))
+            .collect();
+
+        assert_eq!(flagged.len(), 2, "{:?}", flagged);
+        assert_eq!(flagged[0].0, 30);
+        assert_eq!(flagged[0].1.kind, AnomalyKind::Close);
+        assert_eq!(flagged[1].0, 45);
+        assert_eq!(flagged[1].1.kind, AnomalyKind::Range);
+        assert_eq!(metrics.snapshot().candles_anomalous, 2);
+
+        let mut clean = AnomalyDetector::new(20, 5.0);
+        assert!(candles.iter().all(|c| clean.check(c).is_none()));
+        assert_eq!(clean.anomaly_count(), 0);
+    }
+
+    #[test]
+    fn test_negative_prices() {
+        // WTI May 2020 on 2020-04-20, at a 0.01 tick
+        let float_candle = CandleFloat {
+            ts_open: 1587340800000,
+            ts_close: 1587427200000,
+            open: 17.73,
+            high: 17.85,
+            low: -40.32,
+            close: -37.63,
+            volume: 247_947.0,
+            trade_count: TRADE_COUNT_UNKNOWN,
+        };
+        assert!(float_candle.is_valid());
+
+        let candle = Candle::from_float_prices(&float_candle, 0.01);
+        assert_eq!(candle.low_tick, -4032);
+        assert_eq!(candle.close_tick, -3763);
+        assert!(candle.is_valid());
+        assert_eq!(candle.to_float_prices(0.01).close, -3763.0 * 0.01);
+
+        // Halves round away from zero on both sides
+        assert_eq!(RoundingMode::Nearest.quantize(-0.125, 0.25), -1);
+        assert_eq!(RoundingMode::TowardZero.quantize(-0.3, 0.25), -1);
+        assert_eq!(RoundingMode::Passive.quantize_order(-0.3, 0.25, crate::Side::Buy), -2);
+        assert_eq!(RoundingMode::Passive.quantize_order(-0.3, 0.25, crate::Side::Sell), -1);
+
+        let inverted = Candle { high_tick: -4100, ..candle };
+        assert!(!inverted.is_valid());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let bar = |ts: i64, low: i64, high: i64, volume_scaled: i64, trade_count: i64| Candle {
+            ts_open: ts,
+            ts_close: ts + 60_000,
+            open_tick: low,
+            high_tick: high,
+            low_tick: low,
+            close_tick: high,
+            volume_scaled,
+            trade_count,
+        };
+        let candles = [
+            bar(1_000, 200, 210, 1_500_000, 3),
+            bar(61_000, 190, 220, 500_000, TRADE_COUNT_UNKNOWN),
+            bar(121_000, 205, 215, 2_000_000, 7),
+        ];
+
+        let summary = summarize(candles.iter().copied(), 0.5);
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.first_ts_ms, 1_000);
+        assert_eq!(summary.last_ts_ms, 181_000);
+        assert_eq!(summary.span_ms(), 180_000);
+        assert_eq!(summary.min_price, 95.0);
+        assert_eq!(summary.max_price, 110.0);
+        assert_eq!(summary.total_volume, 4.0);
+        assert_eq!(summary.total_trades, 10);
+        assert!((summary.avg_range - 25.0 / 3.0).abs() < 1e-12);
+
+        assert_eq!(summarize(std::iter::empty(), 0.5), CandleSummary::default());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_record_batch_round_trip() {
+        let candles = vec![
+            Candle {
+                ts_open: 1609459200000,
+                ts_close: 1609459260000,
+                open_tick: 84001,
+                high_tick: 85000,
+                low_tick: 83000,
+                close_tick: 84400,
+                volume_scaled: 1_500_500_000,
+                trade_count: 42,
+            },
+            Candle {
+                ts_open: 1609459260000,
+                ts_close: 1609459320000,
+                open_tick: 84400,
+                high_tick: 85600,
+                low_tick: 84200,
+                close_tick: 85400,
+                volume_scaled: 2_000_300_000,
+                trade_count: TRADE_COUNT_UNKNOWN,
+            },
+        ];
+
+        let batch = to_record_batch(&candles, 0.5);
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().as_ref(), &candle_schema());
+        assert_eq!(batch.column_by_name("trade_count").unwrap().null_count(), 1);
+
+        assert_eq!(from_record_batch(&batch, 0.5).unwrap(), candles);
+
+        let projected = batch.project(&[0, 1, 2, 3, 4, 5, 6]).unwrap();
+        assert!(matches!(
+            from_record_batch(&projected, 0.5),
+            Err(crate::candle_parser::ParseError::MissingField(f)) if f == "trade_count"
+        ));
+    }
 }
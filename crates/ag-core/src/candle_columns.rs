@@ -0,0 +1,230 @@
+//! Struct-of-arrays candle storage for analytics
+//!
+//! [`Candle`] (array-of-structs) stays the layout for the engine path. For
+//! indicators that only touch one or two fields, keeping each field in its
+//! own contiguous `Vec<i64>` avoids striding over 64-byte records and lets
+//! the compiler vectorize loops. Each column can also be handed to numpy as
+//! a plain `int64` buffer.
+
+use crate::candle::Candle;
+
+/// Candles stored as one array per field
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CandleColumns {
+    ts_open: Vec<i64>,
+    ts_close: Vec<i64>,
+    open_tick: Vec<i64>,
+    high_tick: Vec<i64>,
+    low_tick: Vec<i64>,
+    close_tick: Vec<i64>,
+    volume_scaled: Vec<i64>,
+    trade_count: Vec<i64>,
+}
+
+impl CandleColumns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ts_open: Vec::with_capacity(capacity),
+            ts_close: Vec::with_capacity(capacity),
+            open_tick: Vec::with_capacity(capacity),
+            high_tick: Vec::with_capacity(capacity),
+            low_tick: Vec::with_capacity(capacity),
+            close_tick: Vec::with_capacity(capacity),
+            volume_scaled: Vec::with_capacity(capacity),
+            trade_count: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append one candle to every column
+    pub fn push(&mut self, candle: &Candle) {
+        self.ts_open.push(candle.ts_open);
+        self.ts_close.push(candle.ts_close);
+        self.open_tick.push(candle.open_tick);
+        self.high_tick.push(candle.high_tick);
+        self.low_tick.push(candle.low_tick);
+        self.close_tick.push(candle.close_tick);
+        self.volume_scaled.push(candle.volume_scaled);
+        self.trade_count.push(candle.trade_count);
+    }
+
+    pub fn len(&self) -> usize {
+        self.ts_open.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ts_open.is_empty()
+    }
+
+    /// Reassemble the candle at `index`
+    pub fn get(&self, index: usize) -> Option<Candle> {
+        (index < self.len()).then(|| Candle {
+            ts_open: self.ts_open[index],
+            ts_close: self.ts_close[index],
+            open_tick: self.open_tick[index],
+            high_tick: self.high_tick[index],
+            low_tick: self.low_tick[index],
+            close_tick: self.close_tick[index],
+            volume_scaled: self.volume_scaled[index],
+            trade_count: self.trade_count[index],
+        })
+    }
+
+    /// Iterate candles in order, reassembled from the columns
+    pub fn iter(&self) -> impl Iterator<Item = Candle> + '_ {
+        (0..self.len()).filter_map(move |i| self.get(i))
+    }
+
+    pub fn ts_open(&self) -> &[i64] {
+        &self.ts_open
+    }
+
+    pub fn ts_close(&self) -> &[i64] {
+        &self.ts_close
+    }
+
+    pub fn open_tick(&self) -> &[i64] {
+        &self.open_tick
+    }
+
+    pub fn high_tick(&self) -> &[i64] {
+        &self.high_tick
+    }
+
+    pub fn low_tick(&self) -> &[i64] {
+        &self.low_tick
+    }
+
+    pub fn close_tick(&self) -> &[i64] {
+        &self.close_tick
+    }
+
+    pub fn volume_scaled(&self) -> &[i64] {
+        &self.volume_scaled
+    }
+
+    pub fn trade_count(&self) -> &[i64] {
+        &self.trade_count
+    }
+}
+
+impl FromIterator<Candle> for CandleColumns {
+    fn from_iter<I: IntoIterator<Item = Candle>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut columns = Self::with_capacity(iter.size_hint().0);
+        for candle in iter {
+            columns.push(&candle);
+        }
+        columns
+    }
+}
+
+impl<'a> FromIterator<&'a Candle> for CandleColumns {
+    fn from_iter<I: IntoIterator<Item = &'a Candle>>(iter: I) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_candles(n: i64) -> Vec<Candle> {
+        (0..n)
+            .map(|i| Candle {
+                ts_open: 1609459200000 + i * 60000,
+                ts_close: 1609459260000 + i * 60000,
+                open_tick: 4200 + i % 13,
+                high_tick: 4250 + i % 13,
+                low_tick: 4150 + i % 13,
+                close_tick: 4220 + i % 17,
+                volume_scaled: 1_500_000 * (i + 1),
+                trade_count: i,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_columns_agree_with_candles() {
+        let candles = sample_candles(100);
+        let columns: CandleColumns = candles.iter().collect();
+
+        assert_eq!(columns.len(), candles.len());
+        for (i, candle) in candles.iter().enumerate() {
+            assert_eq!(columns.ts_open()[i], candle.ts_open);
+            assert_eq!(columns.ts_close()[i], candle.ts_close);
+            assert_eq!(columns.open_tick()[i], candle.open_tick);
+            assert_eq!(columns.high_tick()[i], candle.high_tick);
+            assert_eq!(columns.low_tick()[i], candle.low_tick);
+            assert_eq!(columns.close_tick()[i], candle.close_tick);
+            assert_eq!(columns.volume_scaled()[i], candle.volume_scaled);
+            assert_eq!(columns.trade_count()[i], candle.trade_count);
+        }
+
+        assert_eq!(columns.iter().collect::<Vec<_>>(), candles);
+        assert_eq!(columns.get(100), None);
+    }
+
+    /// Reference rolling mean over windows of `window` values
+    fn rolling_mean(values: &[i64], window: usize) -> Vec<f64> {
+        values
+            .windows(window)
+            .map(|w| w.iter().sum::<i64>() as f64 / window as f64)
+            .collect()
+    }
+
+    /// Rolling mean of closes over each layout; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_rolling_mean_aos_vs_soa() {
+        use std::time::Instant;
+
+        let candles = sample_candles(2_000_000);
+        let columns: CandleColumns = candles.iter().collect();
+        let window = 20;
+
+        let start = Instant::now();
+        let mut aos = Vec::with_capacity(candles.len());
+        let mut sum = 0i64;
+        for (i, candle) in candles.iter().enumerate() {
+            sum += candle.close_tick;
+            if i >= window {
+                sum -= candles[i - window].close_tick;
+            }
+            if i + 1 >= window {
+                aos.push(sum as f64 / window as f64);
+            }
+        }
+        let aos_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let closes = columns.close_tick();
+        let mut soa = Vec::with_capacity(closes.len());
+        let mut sum = 0i64;
+        for (i, close) in closes.iter().enumerate() {
+            sum += close;
+            if i >= window {
+                sum -= closes[i - window];
+            }
+            if i + 1 >= window {
+                soa.push(sum as f64 / window as f64);
+            }
+        }
+        let soa_elapsed = start.elapsed();
+
+        let expected = rolling_mean(closes, window);
+        assert_eq!(aos, expected);
+        assert_eq!(soa, expected);
+
+        println!(
+            "rolling mean over {} candles: AoS {:?}, SoA {:?} ({:.2}x)",
+            candles.len(),
+            aos_elapsed,
+            soa_elapsed,
+            aos_elapsed.as_secs_f64() / soa_elapsed.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Binary candle files with memory-mapped, zero-copy reads
+//!
+//! The format is simply a sequence of raw [`Candle`] records (64 bytes each,
+//! native endianness, no header). Because `Candle` is `Pod`, a mapped file can
+//! be viewed directly as `&[Candle]` without parsing or copying.
+
+use crate::candle::Candle;
+use crate::candle_parser::ParseError;
+use memmap2::Mmap;
+use std::io::Write;
+use std::path::Path;
+
+/// Write candles as raw binary records readable by [`MmapCandles`]
+pub fn write_candles<W: Write>(mut writer: W, candles: &[Candle]) -> std::io::Result<()> {
+    writer.write_all(bytemuck::cast_slice(candles))?;
+    writer.flush()
+}
+
+/// Memory-mapped binary candle file
+pub struct MmapCandles {
+    mmap: Option<Mmap>,
+    tick_size: f64,
+}
+
+impl MmapCandles {
+    /// Map a binary candle file
+    ///
+    /// # Arguments
+    /// * `path` - File written by [`write_candles`]
+    /// * `tick_size` - Tick size the candles were quantized with (not stored in the file)
+    pub fn open(path: impl AsRef<Path>, tick_size: f64) -> Result<Self, ParseError> {
+        let file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+
+        if len % std::mem::size_of::<Candle>() as u64 != 0 {
+            return Err(ParseError::InvalidValue {
+                field: "file_length".to_string(),
+                value: format!("{} (not a multiple of {})", len, std::mem::size_of::<Candle>()),
+            });
+        }
+
+        // Mapping an empty file fails on some platforms
+        let mmap = if len == 0 {
+            None
+        } else {
+            // SAFETY: the file must not be truncated or modified while mapped
+            Some(unsafe { Mmap::map(&file)? })
+        };
+
+        Ok(Self { mmap, tick_size })
+    }
+
+    /// All candles in file order
+    pub fn as_slice(&self) -> &[Candle] {
+        match &self.mmap {
+            // Mappings are page-aligned and the length was checked in `open`
+            Some(mmap) => bytemuck::cast_slice(&mmap[..]),
+            None => &[],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    pub fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    /// Iterate candles from first to last
+    pub fn iter(&self) -> std::slice::Iter<'_, Candle> {
+        self.as_slice().iter()
+    }
+
+    /// Iterate candles from last to first
+    ///
+    /// Uses index arithmetic on the mapped slice, so nothing before the
+    /// candles actually visited is read. The text parsers (CSV/JSON) are
+    /// forward-only and have no equivalent.
+    pub fn iter_rev(&self) -> std::iter::Rev<std::slice::Iter<'_, Candle>> {
+        self.as_slice().iter().rev()
+    }
+
+    /// The last `n` candles (or all of them if the file is shorter)
+    pub fn last_n(&self, n: usize) -> &[Candle] {
+        let candles = self.as_slice();
+        &candles[candles.len().saturating_sub(n)..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_candles(n: i64) -> Vec<Candle> {
+        (0..n)
+            .map(|i| Candle {
+                ts_open: 1609459200000 + i * 60000,
+                ts_close: 1609459260000 + i * 60000,
+                open_tick: 4200 + i,
+                high_tick: 4250 + i,
+                low_tick: 4150 + i,
+                close_tick: 4220 + i,
+                volume_scaled: 1_500_000_000,
+                trade_count: 42,
+            })
+            .collect()
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ag_core_{}_{}.bin", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_mmap_round_trip_and_reverse() {
+        let path = temp_path("iter_rev");
+        let candles = sample_candles(5);
+        write_candles(std::fs::File::create(&path).unwrap(), &candles).unwrap();
+
+        let mapped = MmapCandles::open(&path, 1.0).unwrap();
+        assert_eq!(mapped.len(), 5);
+        assert_eq!(mapped.iter().next().unwrap().ts_open, candles[0].ts_open);
+
+        let reversed: Vec<i64> = mapped.iter_rev().map(|c| c.ts_open).collect();
+        let expected: Vec<i64> = candles.iter().rev().map(|c| c.ts_open).collect();
+        assert_eq!(reversed, expected);
+
+        let last_two = mapped.last_n(2);
+        assert_eq!(last_two.len(), 2);
+        assert_eq!(last_two[1].close_tick, candles[4].close_tick);
+        assert_eq!(mapped.last_n(100).len(), 5);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_rejects_truncated_file() {
+        let path = temp_path("truncated");
+        let candles = sample_candles(2);
+        let bytes: &[u8] = bytemuck::cast_slice(&candles);
+        std::fs::write(&path, &bytes[..100]).unwrap();
+
+        assert!(matches!(MmapCandles::open(&path, 1.0), Err(ParseError::InvalidValue { .. })));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
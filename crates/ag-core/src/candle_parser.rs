@@ -1,7 +1,11 @@
 //! Streaming OHLC candle parsers with zero-copy optimization
 
-use crate::candle::{Candle, CandleFloat};
+use crate::candle::{tick_decimals, Candle, CandleFloat, NanPolicy, RoundingMode, TRADE_COUNT_UNKNOWN};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
 use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Parse errors for candle ingestion
@@ -27,9 +31,34 @@ pub enum ParseError {
 
     #[error("Header mapping error: {0}")]
     HeaderMapping(String),
+
+    #[error("Unsupported data mode: {0}")]
+    Unsupported(String),
+
+    #[error("Trailing data after last record: {0}")]
+    TrailingData(String),
+
+    #[error("Truncated final record: {0}")]
+    TruncatedRecord(String),
+
+    #[error("Candles out of order: ts_open {cur_ts} follows {prev_ts}")]
+    OutOfOrder { prev_ts: i64, cur_ts: i64 },
+
+    #[error("Tick size mismatch in source {index}: expected {expected}, found {found}")]
+    TickSizeMismatch { index: usize, expected: f64, found: f64 },
+
+    #[error("Gap of {missing} candles after ts_open {after_ts} exceeds the fill limit of {max}")]
+    GapTooLarge { after_ts: i64, missing: i64, max: usize },
+
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
 }
 
 /// Trait for streaming candle parsers
+///
+/// Text parsers are forward-only; for reverse or random access, convert the
+/// data to a binary file and use [`crate::candle_file::MmapCandles`].
 pub trait CandleParser: Iterator<Item = Result<Candle, ParseError>> {
     /// Get the tick size used for quantization
     fn tick_size(&self) -> f64;
@@ -38,6 +67,307 @@ pub trait CandleParser: Iterator<Item = Result<Candle, ParseError>> {
     fn size_hint_total(&self) -> Option<usize> {
         None
     }
+
+    /// Number of candles repaired so far (see [`ParseOptions::repair_invalid`])
+    fn repaired_count(&self) -> u64 {
+        0
+    }
+
+    /// Number of candles flagged as halts so far (see [`ZeroVolumePolicy::MarkHalt`])
+    fn halted_count(&self) -> u64 {
+        0
+    }
+
+    /// Whether the candle most recently yielded was flagged as a halt
+    fn last_halted(&self) -> bool {
+        false
+    }
+
+    /// Stop after yielding `max` candles (errors count too)
+    ///
+    /// Unlike `Iterator::take`, the result is still a `CandleParser`, so it
+    /// can feed [`crate::market_event::CandleEventAdapter`] and its metrics
+    /// count only what was yielded. The source is not read past the limit,
+    /// which matters for live feeds that block waiting for the next candle.
+    fn limit(self, max: usize) -> Limit<Self>
+    where
+        Self: Sized,
+    {
+        Limit { parser: self, remaining: max }
+    }
+
+    /// Pass candles through unchanged, failing with
+    /// [`ParseError::OutOfOrder`] at the first whose `ts_open` is earlier
+    /// than its predecessor's
+    ///
+    /// Equal timestamps are allowed. Iteration ends after the error, since
+    /// everything downstream (resampling, the engine clock) assumes time
+    /// order.
+    fn assert_sorted(self) -> AssertSorted<Self>
+    where
+        Self: Sized,
+    {
+        AssertSorted {
+            parser: self,
+            prev_ts: None,
+            failed: false,
+        }
+    }
+}
+
+/// What to do with candles whose (scaled) volume is zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZeroVolumePolicy {
+    /// Treat them like any other candle
+    #[default]
+    Accept,
+    /// Yield `ParseError::InvalidCandle` instead
+    Reject,
+    /// Yield the candle but flag it as a trading halt via
+    /// [`CandleParser::last_halted`] and [`CandleParser::halted_count`]
+    MarkHalt,
+}
+
+impl ZeroVolumePolicy {
+    /// Apply the policy to a quantized candle, returning whether it is a halt
+    fn check(self, candle: &Candle) -> Result<bool, ParseError> {
+        if candle.volume_scaled != 0 {
+            return Ok(false);
+        }
+
+        match self {
+            ZeroVolumePolicy::Accept => Ok(false),
+            ZeroVolumePolicy::Reject => Err(ParseError::InvalidCandle(format!(
+                "Zero volume at ts_open {}",
+                candle.ts_open
+            ))),
+            ZeroVolumePolicy::MarkHalt => Ok(true),
+        }
+    }
+}
+
+/// Bar length assumed when only one of `ts_open`/`ts_close` is present
+pub const DEFAULT_INTERVAL_MS: i64 = 60_000;
+
+/// Options shared by the candle parsers
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Clamp high/low to bracket open/close (via `Candle::repair`) instead of
+    /// rejecting near-miss candles. Off by default.
+    pub repair_invalid: bool,
+    /// How prices are snapped to the tick grid
+    pub rounding: RoundingMode,
+    /// Handling of zero-volume candles (accepted by default)
+    pub treat_zero_volume: ZeroVolumePolicy,
+    /// Handling of NaN or infinite volume (rejected by default). Non-finite
+    /// prices are always rejected.
+    pub nan_volume: NanPolicy,
+    /// Bar length used to derive a missing `ts_open` or `ts_close`
+    /// ([`DEFAULT_INTERVAL_MS`], one minute, by default)
+    pub default_interval_ms: i64,
+    /// Derive missing timestamps from the gap between the first two records
+    /// instead of `default_interval_ms`, which remains the fallback when
+    /// there is no usable second record. Off by default.
+    pub infer_interval: bool,
+    /// CSV only: accept rows with more or fewer fields than the header,
+    /// reading columns by header index and ignoring extras. A missing
+    /// trade count reads as unknown; other missing fields are errors. Off
+    /// by default, so ragged rows fail with a CSV error. Fixed when the
+    /// parser is created (see [`CsvCandleIter::new_with_options`]).
+    pub flexible: bool,
+    /// CSV only: read timestamps that are not integer epoch millis as
+    /// ISO-8601 / RFC 3339 text (see [`parse_timestamp_ms`]). Off by
+    /// default, so only epoch millis are accepted.
+    pub iso_timestamps: bool,
+    /// CSV only: read prices and volume with [`parse_loose_f64`], accepting
+    /// surrounding whitespace and thousands separators. Off by default.
+    pub loose_numbers: bool,
+    /// With `loose_numbers`, read `,` as the decimal separator and `.` as
+    /// the thousands separator (`1.234,5`). Never guessed from the data,
+    /// since `1,234` means different things in different locales.
+    pub decimal_comma: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            repair_invalid: false,
+            rounding: RoundingMode::default(),
+            treat_zero_volume: ZeroVolumePolicy::default(),
+            nan_volume: NanPolicy::default(),
+            default_interval_ms: DEFAULT_INTERVAL_MS,
+            infer_interval: false,
+            flexible: false,
+            iso_timestamps: false,
+            loose_numbers: false,
+            decimal_comma: false,
+        }
+    }
+}
+
+/// Parse a timestamp as epoch millis, falling back to ISO-8601 text
+///
+/// Accepts `YYYY-MM-DD`, optionally followed by `T` (or a space) and
+/// `HH:MM:SS` with an optional fraction, then an optional `Z` or `+HH:MM` /
+/// `-HH:MM` offset. A time without an offset is read as UTC, and digits
+/// beyond milliseconds are truncated. So `2021-01-01T00:00:00Z` and
+/// `1609459200000` both give `1609459200000`.
+pub fn parse_timestamp_ms(text: &str) -> Option<i64> {
+    let text = text.trim();
+    text.parse::<i64>().ok().or_else(|| parse_iso8601_ms(text))
+}
+
+fn parse_iso8601_ms(text: &str) -> Option<i64> {
+    let bytes = text.as_bytes();
+    // Fixed-width unsigned field at `range`, all ASCII digits
+    let field = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = bytes.get(range)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        std::str::from_utf8(digits).ok()?.parse().ok()
+    };
+
+    if bytes.get(4) != Some(&b'-') || bytes.get(7) != Some(&b'-') {
+        return None;
+    }
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut ms = days_from_civil(year, month, day) * 86_400_000;
+    if bytes.len() == 10 {
+        return Some(ms);
+    }
+
+    if !matches!(bytes[10], b'T' | b't' | b' ') || bytes.get(13) != Some(&b':') || bytes.get(16) != Some(&b':') {
+        return None;
+    }
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    ms += (hour * 3_600 + minute * 60 + second) * 1_000;
+
+    let mut rest = &bytes[19..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let len = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+        if len == 0 {
+            return None;
+        }
+        let millis = fraction[..len.min(3)].iter().fold(0i64, |acc, &d| acc * 10 + (d - b'0') as i64);
+        ms += millis * 10i64.pow(3 - len.min(3) as u32);
+        rest = &fraction[len..];
+    }
+
+    match rest {
+        [] | [b'Z' | b'z'] => Some(ms),
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let end = bytes.len();
+            let (hours, minutes) = (field(end - 5..end - 3)?, field(end - 2..end)?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            // Local time = UTC + offset
+            let offset_ms = (hours * 60 + minutes) * 60_000;
+            Some(if *sign == b'+' { ms - offset_ms } else { ms + offset_ms })
+        }
+        _ => None,
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse a number as found in spreadsheet exports: `1,234.5`, `1.5e3`,
+/// `+42`, `  42.0  `
+///
+/// Surrounding whitespace and a leading `+` are accepted. Thousands
+/// separators (`,`, or `.` with `decimal_comma`) are only accepted in the
+/// integer part and in groups of exactly three digits, so `1,5` is
+/// rejected rather than read as 15 when the decimal separator was
+/// mistaken. `inf` and `nan` are rejected.
+pub fn parse_loose_f64(text: &str, decimal_comma: bool) -> Option<f64> {
+    normalize_number(text, decimal_comma)?.parse().ok()
+}
+
+/// `text` rewritten as a plain decimal Rust can parse, if it is a valid
+/// loose number (see [`parse_loose_f64`])
+fn normalize_number(text: &str, decimal_comma: bool) -> Option<String> {
+    let (group_sep, decimal_sep) = if decimal_comma { ('.', ',') } else { (',', '.') };
+    let text = text.trim();
+    let (negative, unsigned) = match text.as_bytes().first()? {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(at) => (&unsigned[..at], Some(&unsigned[at + 1..])),
+        None => (unsigned, None),
+    };
+    let (int_part, frac_part) = match mantissa.split_once(decimal_sep) {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (mantissa, None),
+    };
+
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let mut groups = int_part.split(group_sep);
+    let first = groups.next()?;
+    let mut digits = String::with_capacity(text.len());
+    digits.push_str(first);
+    for group in groups {
+        if first.is_empty() || first.len() > 3 || group.len() != 3 {
+            return None;
+        }
+        digits.push_str(group);
+    }
+    if !all_digits(&digits) || !frac_part.is_none_or(all_digits) {
+        return None;
+    }
+    if digits.is_empty() && frac_part.is_none_or(str::is_empty) {
+        return None;
+    }
+
+    let mut out = String::with_capacity(text.len() + 2);
+    if negative {
+        out.push('-');
+    }
+    out.push_str(if digits.is_empty() { "0" } else { &digits });
+    if let Some(frac_part) = frac_part.filter(|f| !f.is_empty()) {
+        out.push('.');
+        out.push_str(frac_part);
+    }
+    if let Some(exponent) = exponent {
+        let exp_digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if exp_digits.is_empty() || !all_digits(exp_digits) {
+            return None;
+        }
+        out.push('e');
+        out.push_str(exponent);
+    }
+    Some(out)
+}
+
+/// Bar length implied by two consecutive timestamps, if they increase
+fn interval_between(first: Option<i64>, second: Option<i64>) -> Option<i64> {
+    let gap = second? - first?;
+    (gap > 0).then_some(gap)
 }
 
 // ============================================================================
@@ -49,6 +379,19 @@ pub struct CsvCandleIter<R: Read> {
     reader: csv::Reader<R>,
     tick_size: f64,
     header_map: HeaderMap,
+    options: ParseOptions,
+    /// Decimal places of a power-of-ten tick size, for exact price parsing
+    price_decimals: Option<u32>,
+    repaired: u64,
+    halted: u64,
+    last_halted: bool,
+    /// Structural error from the most recent read, reported by `finish`
+    tail_error: Option<ParseError>,
+    /// Record read ahead to infer the bar interval, yielded next
+    peeked: Option<Result<csv::StringRecord, csv::Error>>,
+    /// Bar interval found by `infer_interval`, once probed
+    inferred_interval: Option<i64>,
+    interval_probed: bool,
     _current_position: usize,
 }
 
@@ -66,6 +409,16 @@ struct HeaderMap {
 }
 
 impl HeaderMap {
+    /// Whether one timestamp has to be derived from the other
+    fn derives_timestamp(&self) -> bool {
+        self.ts_open_idx.is_none() || self.ts_close_idx.is_none()
+    }
+
+    /// Index of the timestamp column present in the file, if any
+    fn timestamp_idx(&self) -> Option<usize> {
+        self.ts_open_idx.or(self.ts_close_idx)
+    }
+
     /// Build header map from CSV headers with flexible matching
     fn from_headers(headers: &csv::StringRecord) -> Result<Self, ParseError> {
         let mut ts_open_idx = None;
@@ -144,9 +497,15 @@ impl<R: Read> CsvCandleIter<R> {
     /// * `reader` - Buffered reader for CSV data
     /// * `tick_size` - Tick size for price quantization
     pub fn new(reader: R, tick_size: f64) -> Result<Self, ParseError> {
+        Self::new_with_options(reader, tick_size, ParseOptions::default())
+    }
+
+    /// Create a CSV candle iterator with `options`, including
+    /// [`ParseOptions::flexible`], which cannot be changed afterwards
+    pub fn new_with_options(reader: R, tick_size: f64, options: ParseOptions) -> Result<Self, ParseError> {
         let mut csv_reader = csv::ReaderBuilder::new()
             .has_headers(true)
-            .flexible(false) // Strict column count
+            .flexible(options.flexible)
             .trim(csv::Trim::All)
             .from_reader(reader);
 
@@ -158,10 +517,164 @@ impl<R: Read> CsvCandleIter<R> {
             reader: csv_reader,
             tick_size,
             header_map,
+            price_decimals: tick_decimals(tick_size),
+            options,
+            repaired: 0,
+            halted: 0,
+            last_halted: false,
+            tail_error: None,
+            peeked: None,
+            inferred_interval: None,
+            interval_probed: false,
             _current_position: 0,
         })
     }
 
+    /// Replace the parser options, keeping the `flexible` setting the
+    /// parser was created with
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = ParseOptions {
+            flexible: self.options.flexible,
+            ..options
+        };
+        self
+    }
+
+    /// Repair near-miss candles instead of rejecting them
+    pub fn repair_invalid(mut self, enabled: bool) -> Self {
+        self.options.repair_invalid = enabled;
+        self
+    }
+
+    /// Set the price rounding mode
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.options.rounding = rounding;
+        self
+    }
+
+    /// Set the handling of zero-volume candles
+    pub fn treat_zero_volume(mut self, policy: ZeroVolumePolicy) -> Self {
+        self.options.treat_zero_volume = policy;
+        self
+    }
+
+    /// Set the handling of NaN or infinite volume
+    pub fn nan_policy(mut self, policy: NanPolicy) -> Self {
+        self.options.nan_volume = policy;
+        self
+    }
+
+    /// Set the bar length used to derive a missing `ts_open`/`ts_close`
+    pub fn default_interval_ms(mut self, interval_ms: i64) -> Self {
+        self.options.default_interval_ms = interval_ms;
+        self
+    }
+
+    /// Derive missing timestamps from the gap between the first two records
+    pub fn infer_interval(mut self, enabled: bool) -> Self {
+        self.options.infer_interval = enabled;
+        self
+    }
+
+    /// Bar length used for derived timestamps
+    fn interval_ms(&self) -> i64 {
+        self.inferred_interval.unwrap_or(self.options.default_interval_ms)
+    }
+
+    /// Read the next record, taking the read-ahead one first
+    fn read_record(&mut self, record: &mut csv::StringRecord) -> Result<bool, csv::Error> {
+        match self.peeked.take() {
+            Some(peeked) => peeked.map(|peeked| {
+                *record = peeked;
+                true
+            }),
+            None => self.reader.read_record(record),
+        }
+    }
+
+    /// Read one record ahead of `first` and infer the bar interval from the
+    /// gap between their timestamps
+    fn probe_interval(&mut self, first: &csv::StringRecord) {
+        self.interval_probed = true;
+        let Some(idx) = self.header_map.timestamp_idx() else {
+            return;
+        };
+
+        let mut second = csv::StringRecord::new();
+        match self.reader.read_record(&mut second) {
+            Ok(true) => {
+                let iso = self.options.iso_timestamps;
+                let ts = |record: &csv::StringRecord| {
+                    let value = record.get(idx)?;
+                    if iso {
+                        parse_timestamp_ms(value)
+                    } else {
+                        value.parse::<i64>().ok()
+                    }
+                };
+                self.inferred_interval = interval_between(ts(first), ts(&second));
+                self.peeked = Some(Ok(second));
+            }
+            Ok(false) => {}
+            Err(e) => self.peeked = Some(Err(e)),
+        }
+    }
+
+    /// Check that the input ended cleanly
+    ///
+    /// Call after iteration. Returns `TruncatedRecord` if the last record had
+    /// fewer columns than the header, `TrailingData` if it was otherwise
+    /// malformed or if records were left unconsumed. Value-level errors
+    /// (bad numbers, invalid OHLC) are already reported by the iterator and
+    /// are not repeated here.
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        let mut record = csv::StringRecord::new();
+        let mut unconsumed = 0usize;
+
+        loop {
+            match self.read_record(&mut record) {
+                Ok(true) => {
+                    unconsumed += 1;
+                    self.tail_error = None;
+                }
+                Ok(false) => break,
+                Err(e) => self.tail_error = Some(classify_csv_error(&e)),
+            }
+        }
+
+        if let Some(e) = self.tail_error {
+            return Err(e);
+        }
+
+        if unconsumed > 0 {
+            return Err(ParseError::TrailingData(format!("{} unconsumed records", unconsumed)));
+        }
+
+        Ok(())
+    }
+
+    /// Open, high, low and close ticks read straight from the record's
+    /// decimal text, when the tick size is a power of ten and every price is
+    /// plain decimal
+    fn decimal_prices(&self, record: &csv::StringRecord) -> Option<[i64; 4]> {
+        let decimals = self.price_decimals?;
+        let ticks = |idx: usize| {
+            let text = record.get(idx)?;
+            if self.options.loose_numbers {
+                let text = normalize_number(text, self.options.decimal_comma)?;
+                self.options.rounding.quantize_decimal(&text, decimals)
+            } else {
+                self.options.rounding.quantize_decimal(text, decimals)
+            }
+        };
+        Some([
+            ticks(self.header_map.open_idx)?,
+            ticks(self.header_map.high_idx)?,
+            ticks(self.header_map.low_idx)?,
+            ticks(self.header_map.close_idx)?,
+        ])
+    }
+
     /// Parse a single record into a CandleFloat
     fn parse_record(&self, record: &csv::StringRecord) -> Result<CandleFloat, ParseError> {
         // Helper to parse field
@@ -170,7 +683,12 @@ impl<R: Read> CsvCandleIter<R> {
                 ParseError::MissingField(field_name.to_string())
             })?;
 
-            value_str.parse::<f64>().map_err(|_| ParseError::InvalidValue {
+            let value = if self.options.loose_numbers {
+                parse_loose_f64(value_str, self.options.decimal_comma)
+            } else {
+                value_str.parse::<f64>().ok()
+            };
+            value.ok_or_else(|| ParseError::InvalidValue {
                 field: field_name.to_string(),
                 value: value_str.to_string(),
             })
@@ -187,6 +705,20 @@ impl<R: Read> CsvCandleIter<R> {
             })
         };
 
+        let parse_ts = |idx: usize, field_name: &str| -> Result<i64, ParseError> {
+            if !self.options.iso_timestamps {
+                return parse_i64(idx, field_name);
+            }
+            let value_str = record.get(idx).ok_or_else(|| {
+                ParseError::MissingField(field_name.to_string())
+            })?;
+
+            parse_timestamp_ms(value_str).ok_or_else(|| ParseError::InvalidValue {
+                field: field_name.to_string(),
+                value: value_str.to_string(),
+            })
+        };
+
         // Parse OHLC
         let open = parse_f64(self.header_map.open_idx, "open")?;
         let high = parse_f64(self.header_map.high_idx, "high")?;
@@ -196,26 +728,26 @@ impl<R: Read> CsvCandleIter<R> {
 
         // Parse timestamps
         let ts_open = if let Some(idx) = self.header_map.ts_open_idx {
-            parse_i64(idx, "ts_open")?
+            parse_ts(idx, "ts_open")?
         } else {
             // If no open timestamp, use close timestamp or default
             if let Some(idx) = self.header_map.ts_close_idx {
-                parse_i64(idx, "ts_close")? - 60000 // Assume 1-minute candle
+                parse_ts(idx, "ts_close")? - self.interval_ms()
             } else {
                 return Err(ParseError::MissingField("timestamp".to_string()));
             }
         };
 
         let ts_close = if let Some(idx) = self.header_map.ts_close_idx {
-            parse_i64(idx, "ts_close")?
+            parse_ts(idx, "ts_close")?
         } else {
-            ts_open + 60000 // Default to 1-minute candle
+            ts_open + self.interval_ms()
         };
 
-        let trade_count = if let Some(idx) = self.header_map.trade_count_idx {
-            parse_i64(idx, "trade_count")?
-        } else {
-            0 // Unknown trade count
+        // A ragged row may stop before the optional trade count
+        let trade_count = match self.header_map.trade_count_idx {
+            Some(idx) if record.get(idx).is_some() => parse_i64(idx, "trade_count")?,
+            _ => TRADE_COUNT_UNKNOWN,
         };
 
         Ok(CandleFloat {
@@ -236,21 +768,44 @@ impl<R: Read> Iterator for CsvCandleIter<R> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut record = csv::StringRecord::new();
+        self.last_halted = false;
 
-        match self.reader.read_record(&mut record) {
+        match self.read_record(&mut record) {
             Ok(true) => {
+                self.tail_error = None;
+                if self.options.infer_interval && !self.interval_probed && self.header_map.derives_timestamp() {
+                    self.probe_interval(&record);
+                }
+
                 // Parse record
                 match self.parse_record(&record) {
-                    Ok(float_candle) => {
+                    Ok(mut float_candle) => {
+                        let mut repaired = self.options.repair_invalid && float_candle.repair();
+
                         // Validate
-                        if !float_candle.is_valid() {
+                        if !float_candle.is_valid_with_policy(self.options.nan_volume) {
                             return Some(Err(ParseError::InvalidCandle(
                                 format!("Invalid OHLC data at record: {:?}", record)
                             )));
                         }
+                        let volume_known = self.options.nan_volume.settle_volume(&mut float_candle);
 
                         // Convert to quantized candle
-                        let candle = Candle::from_float_prices(&float_candle, self.tick_size);
+                        let mut candle = match Candle::try_from_float_prices_with(&float_candle, self.tick_size, self.options.rounding) {
+                            Ok(candle) => candle,
+                            Err(e) => return Some(Err(e)),
+                        };
+                        // Exact ticks from the text avoid binary-float rounding
+                        if let Some([open, high, low, close]) = self.decimal_prices(&record) {
+                            candle.open_tick = open;
+                            candle.high_tick = high;
+                            candle.low_tick = low;
+                            candle.close_tick = close;
+                        }
+                        repaired |= self.options.repair_invalid && candle.repair();
+                        // Exact decimal ticks skip the float repair above and
+                        // can disagree with the float check past 17 digits
+                        candle.normalize_ticks();
 
                         // Double-check after quantization
                         if !candle.is_valid() {
@@ -259,14 +814,41 @@ impl<R: Read> Iterator for CsvCandleIter<R> {
                             )));
                         }
 
+                        let halted = match self.options.treat_zero_volume.check(&candle) {
+                            Ok(halted) => volume_known && halted,
+                            Err(_) if !volume_known => false,
+                            Err(e) => return Some(Err(e)),
+                        };
+
+                        if repaired {
+                            self.repaired += 1;
+                        }
+                        if halted {
+                            self.halted += 1;
+                            self.last_halted = true;
+                        }
+
                         Some(Ok(candle))
                     }
                     Err(e) => Some(Err(e)),
                 }
             }
             Ok(false) => None, // End of file
-            Err(e) => Some(Err(ParseError::Csv(e))),
+            Err(e) => {
+                self.tail_error = Some(classify_csv_error(&e));
+                Some(Err(ParseError::Csv(e)))
+            }
+        }
+    }
+}
+
+/// Classify a structural CSV error for `CsvCandleIter::finish`
+fn classify_csv_error(e: &csv::Error) -> ParseError {
+    match e.kind() {
+        csv::ErrorKind::UnequalLengths { expected_len, len, .. } if len < expected_len => {
+            ParseError::TruncatedRecord(e.to_string())
         }
+        _ => ParseError::TrailingData(e.to_string()),
     }
 }
 
@@ -274,6 +856,101 @@ impl<R: Read> CandleParser for CsvCandleIter<R> {
     fn tick_size(&self) -> f64 {
         self.tick_size
     }
+
+    fn repaired_count(&self) -> u64 {
+        self.repaired
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.halted
+    }
+
+    fn last_halted(&self) -> bool {
+        self.last_halted
+    }
+}
+
+/// Close-only CSV parser yielding `(ts_open, close_tick)` pairs
+///
+/// Created by [`CsvCandleIter::close_only`]. Only the timestamp and close
+/// columns are decoded; open/high/low/volume are neither parsed nor validated,
+/// which makes this noticeably faster on wide files.
+pub struct CsvCloseIter<R: Read> {
+    reader: csv::Reader<R>,
+    tick_size: f64,
+    rounding: RoundingMode,
+    ts_open_idx: Option<usize>,
+    ts_close_idx: Option<usize>,
+    close_idx: usize,
+    /// Bar length used to derive `ts_open` from `ts_close`
+    interval_ms: i64,
+    record: csv::ByteRecord,
+}
+
+impl<R: Read> CsvCandleIter<R> {
+    /// Switch to close-only parsing
+    ///
+    /// Consumes the full parser and returns an iterator over
+    /// `(ts_open, close_tick)` pairs, skipping OHLCV validation. A missing
+    /// `ts_open` is derived with `default_interval_ms`; `infer_interval` is
+    /// not applied.
+    pub fn close_only(self) -> CsvCloseIter<R> {
+        CsvCloseIter {
+            interval_ms: self.options.default_interval_ms,
+            reader: self.reader,
+            tick_size: self.tick_size,
+            rounding: self.options.rounding,
+            ts_open_idx: self.header_map.ts_open_idx,
+            ts_close_idx: self.header_map.ts_close_idx,
+            close_idx: self.header_map.close_idx,
+            record: csv::ByteRecord::new(),
+        }
+    }
+}
+
+impl<R: Read> CsvCloseIter<R> {
+    /// Get the tick size used for quantization
+    pub fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    fn field<T: std::str::FromStr>(&self, idx: usize, field_name: &str) -> Result<T, ParseError> {
+        let bytes = self.record.get(idx).ok_or_else(|| {
+            ParseError::MissingField(field_name.to_string())
+        })?;
+
+        std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|value_str| value_str.parse::<T>().ok())
+            .ok_or_else(|| ParseError::InvalidValue {
+                field: field_name.to_string(),
+                value: String::from_utf8_lossy(bytes).into_owned(),
+            })
+    }
+
+    fn parse_record(&self) -> Result<(i64, i64), ParseError> {
+        let ts_open = match (self.ts_open_idx, self.ts_close_idx) {
+            (Some(idx), _) => self.field::<i64>(idx, "ts_open")?,
+            (None, Some(idx)) => self.field::<i64>(idx, "ts_close")? - self.interval_ms,
+            (None, None) => return Err(ParseError::MissingField("timestamp".to_string())),
+        };
+
+        let close = self.field::<f64>(self.close_idx, "close")?;
+
+        Ok((ts_open, self.rounding.quantize(close, self.tick_size)))
+    }
+}
+
+impl<R: Read> Iterator for CsvCloseIter<R> {
+    type Item = Result<(i64, i64), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_byte_record(&mut self.record) {
+            Ok(true) => Some(self.parse_record()),
+            Ok(false) => None, // End of file
+            Err(e) => Some(Err(ParseError::Csv(e))),
+        }
+    }
 }
 
 // ============================================================================
@@ -281,9 +958,26 @@ impl<R: Read> CandleParser for CsvCandleIter<R> {
 // ============================================================================
 
 /// Streaming JSON candle parser
+///
+/// Reads newline-delimited objects or a single top-level array of objects,
+/// told apart by the first non-whitespace byte (`[` means an array). Array
+/// elements are streamed one at a time, never loaded as a whole.
 pub struct JsonCandleIter<R: Read> {
-    deserializer: serde_json::StreamDeserializer<'static, serde_json::de::IoRead<R>, CandleJson>,
+    deserializer: serde_json::StreamDeserializer<'static, serde_json::de::IoRead<JsonInput<R>>, CandleJson>,
+    /// Set while a top-level array has been opened but not closed
+    array_open: Arc<AtomicBool>,
     tick_size: f64,
+    options: ParseOptions,
+    repaired: u64,
+    halted: u64,
+    last_halted: bool,
+    /// Stream error that stopped deserialization, reported by `finish`
+    tail_error: Option<ParseError>,
+    /// Record read ahead to infer the bar interval, yielded next
+    peeked: Option<Result<CandleJson, serde_json::Error>>,
+    /// Bar interval found by `infer_interval`, once probed
+    inferred_interval: Option<i64>,
+    interval_probed: bool,
 }
 
 /// JSON representation of a candle for serde
@@ -317,32 +1011,235 @@ struct CandleJson {
 impl<R: Read> JsonCandleIter<R> {
     /// Create a new JSON candle iterator
     ///
-    /// Expects newline-delimited JSON (NDJSON) format
+    /// Expects newline-delimited JSON (NDJSON) or a JSON array of candles
     pub fn new(reader: R, tick_size: f64) -> Self {
-        let deserializer = serde_json::Deserializer::from_reader(reader)
-            .into_iter::<CandleJson>();
+        let input = JsonInput::new(reader);
+        let array_open = Arc::clone(&input.array_open);
+        Self {
+            deserializer: serde_json::Deserializer::from_reader(input).into_iter::<CandleJson>(),
+            array_open,
+            tick_size,
+            options: ParseOptions::default(),
+            repaired: 0,
+            halted: 0,
+            last_halted: false,
+            tail_error: None,
+            peeked: None,
+            inferred_interval: None,
+            interval_probed: false,
+        }
+    }
+
+    /// Replace the parser options
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Repair near-miss candles instead of rejecting them
+    pub fn repair_invalid(mut self, enabled: bool) -> Self {
+        self.options.repair_invalid = enabled;
+        self
+    }
+
+    /// Set the price rounding mode
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.options.rounding = rounding;
+        self
+    }
+
+    /// Set the handling of zero-volume candles
+    pub fn treat_zero_volume(mut self, policy: ZeroVolumePolicy) -> Self {
+        self.options.treat_zero_volume = policy;
+        self
+    }
+
+    /// Set the handling of NaN or infinite volume
+    pub fn nan_policy(mut self, policy: NanPolicy) -> Self {
+        self.options.nan_volume = policy;
+        self
+    }
+
+    /// Set the bar length used to derive a missing `ts_open`/`ts_close`
+    pub fn default_interval_ms(mut self, interval_ms: i64) -> Self {
+        self.options.default_interval_ms = interval_ms;
+        self
+    }
+
+    /// Derive missing timestamps from the gap between the first two records
+    pub fn infer_interval(mut self, enabled: bool) -> Self {
+        self.options.infer_interval = enabled;
+        self
+    }
+
+    /// Bar length used for derived timestamps
+    fn interval_ms(&self) -> i64 {
+        self.inferred_interval.unwrap_or(self.options.default_interval_ms)
+    }
+
+    /// Read one record ahead of `first` and infer the bar interval from the
+    /// gap between their timestamps
+    fn probe_interval(&mut self, first: &CandleJson) {
+        self.interval_probed = true;
+        if let Some(second) = self.deserializer.next() {
+            if let Ok(second) = &second {
+                self.inferred_interval = interval_between(first.ts_open, second.ts_open)
+                    .or_else(|| interval_between(first.ts_close, second.ts_close));
+            }
+            self.peeked = Some(second);
+        }
+    }
+
+    /// Check that the input ended cleanly
+    ///
+    /// Call after iteration. The stream stops at the first malformed value, so
+    /// this returns `TruncatedRecord` if the input ended mid-value and
+    /// `TrailingData` for anything else left behind: non-JSON bytes,
+    /// malformed records or records that were never consumed. Trailing
+    /// whitespace is fine. An array missing its closing `]` is truncated.
+    pub fn finish(mut self) -> Result<(), ParseError> {
+        let mut unconsumed = 0usize;
+
+        for result in self.peeked.take().into_iter().chain(self.deserializer.by_ref()) {
+            match result {
+                Ok(_) => unconsumed += 1,
+                Err(e) => self.tail_error = Some(classify_json_error(&e)),
+            }
+        }
+
+        if let Some(e) = self.tail_error {
+            return Err(e);
+        }
+
+        if unconsumed > 0 {
+            return Err(ParseError::TrailingData(format!("{} unconsumed records", unconsumed)));
+        }
+
+        if self.array_open.load(Ordering::Relaxed) {
+            return Err(ParseError::TruncatedRecord("JSON array is missing its closing ]".to_string()));
+        }
+
+        Ok(())
+    }
+}
 
+/// Layout of a JSON input, known once its first non-whitespace byte is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonLayout {
+    Unknown,
+    Lines,
+    Array,
+}
+
+/// Reader under [`JsonCandleIter`]'s stream deserializer
+///
+/// For an array input it blanks out the outer brackets and the commas
+/// between elements, so the deserializer sees the same whitespace-separated
+/// objects as in NDJSON. Brackets and commas inside elements, including
+/// inside strings, are left alone.
+struct JsonInput<R: Read> {
+    inner: R,
+    layout: JsonLayout,
+    /// Nesting depth inside the current array element
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    closed: bool,
+    array_open: Arc<AtomicBool>,
+}
+
+impl<R: Read> JsonInput<R> {
+    fn new(inner: R) -> Self {
         Self {
-            deserializer: unsafe {
-                // SAFETY: We ensure R: 'static or manage lifetime appropriately
-                std::mem::transmute(deserializer)
+            inner,
+            layout: JsonLayout::Unknown,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            closed: false,
+            array_open: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn translate(&mut self, byte: &mut u8) {
+        match self.layout {
+            JsonLayout::Unknown if byte.is_ascii_whitespace() => {}
+            JsonLayout::Unknown if *byte == b'[' => {
+                self.layout = JsonLayout::Array;
+                self.array_open.store(true, Ordering::Relaxed);
+                *byte = b' ';
+            }
+            JsonLayout::Unknown => self.layout = JsonLayout::Lines,
+            JsonLayout::Lines => {}
+            JsonLayout::Array if self.closed => {}
+            JsonLayout::Array if self.in_string => {
+                if self.escaped {
+                    self.escaped = false;
+                } else if *byte == b'\\' {
+                    self.escaped = true;
+                } else if *byte == b'"' {
+                    self.in_string = false;
+                }
+            }
+            JsonLayout::Array => match *byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => self.depth -= 1,
+                b']' => {
+                    self.closed = true;
+                    self.array_open.store(false, Ordering::Relaxed);
+                    *byte = b' ';
+                }
+                b',' if self.depth == 0 => *byte = b' ',
+                _ => {}
             },
-            tick_size,
         }
     }
 }
 
+impl<R: Read> Read for JsonInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        for byte in &mut buf[..n] {
+            self.translate(byte);
+        }
+        Ok(n)
+    }
+}
+
+/// Classify a stream error for `JsonCandleIter::finish`
+fn classify_json_error(e: &serde_json::Error) -> ParseError {
+    if e.is_eof() {
+        ParseError::TruncatedRecord(e.to_string())
+    } else {
+        ParseError::TrailingData(e.to_string())
+    }
+}
+
 impl<R: Read> Iterator for JsonCandleIter<R> {
     type Item = Result<Candle, ParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.deserializer.next() {
+        self.last_halted = false;
+
+        let next = self.peeked.take().or_else(|| self.deserializer.next());
+        match next {
             Some(Ok(candle_json)) => {
-                // Convert to CandleFloat
-                let ts_open = candle_json.ts_open.unwrap_or(0);
-                let ts_close = candle_json.ts_close.unwrap_or(ts_open + 60000);
+                let derives_timestamp = candle_json.ts_open.is_none() || candle_json.ts_close.is_none();
+                if self.options.infer_interval && !self.interval_probed && derives_timestamp {
+                    self.probe_interval(&candle_json);
+                }
+
+                // Convert to CandleFloat, deriving a missing timestamp from the other
+                let interval_ms = self.interval_ms();
+                let (ts_open, ts_close) = match (candle_json.ts_open, candle_json.ts_close) {
+                    (Some(ts_open), Some(ts_close)) => (ts_open, ts_close),
+                    (Some(ts_open), None) => (ts_open, ts_open + interval_ms),
+                    (None, Some(ts_close)) => (ts_close - interval_ms, ts_close),
+                    (None, None) => (0, interval_ms),
+                };
 
-                let float_candle = CandleFloat {
+                let mut float_candle = CandleFloat {
                     ts_open,
                     ts_close,
                     open: candle_json.open,
@@ -350,18 +1247,24 @@ impl<R: Read> Iterator for JsonCandleIter<R> {
                     low: candle_json.low,
                     close: candle_json.close,
                     volume: candle_json.volume,
-                    trade_count: candle_json.trade_count.unwrap_or(0),
+                    trade_count: candle_json.trade_count.unwrap_or(TRADE_COUNT_UNKNOWN),
                 };
+                let mut repaired = self.options.repair_invalid && float_candle.repair();
 
                 // Validate
-                if !float_candle.is_valid() {
+                if !float_candle.is_valid_with_policy(self.options.nan_volume) {
                     return Some(Err(ParseError::InvalidCandle(
                         format!("Invalid OHLC data: {:?}", candle_json)
                     )));
                 }
+                let volume_known = self.options.nan_volume.settle_volume(&mut float_candle);
 
                 // Convert to quantized candle
-                let candle = Candle::from_float_prices(&float_candle, self.tick_size);
+                let mut candle = match Candle::try_from_float_prices_with(&float_candle, self.tick_size, self.options.rounding) {
+                    Ok(candle) => candle,
+                    Err(e) => return Some(Err(e)),
+                };
+                repaired |= self.options.repair_invalid && candle.repair();
 
                 if !candle.is_valid() {
                     return Some(Err(ParseError::InvalidCandle(
@@ -369,9 +1272,26 @@ impl<R: Read> Iterator for JsonCandleIter<R> {
                     )));
                 }
 
+                let halted = match self.options.treat_zero_volume.check(&candle) {
+                    Ok(halted) => volume_known && halted,
+                    Err(_) if !volume_known => false,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                if repaired {
+                    self.repaired += 1;
+                }
+                if halted {
+                    self.halted += 1;
+                    self.last_halted = true;
+                }
+
                 Some(Ok(candle))
             }
-            Some(Err(e)) => Some(Err(ParseError::Json(e))),
+            Some(Err(e)) => {
+                self.tail_error = Some(classify_json_error(&e));
+                Some(Err(ParseError::Json(e)))
+            }
             None => None,
         }
     }
@@ -381,35 +1301,590 @@ impl<R: Read> CandleParser for JsonCandleIter<R> {
     fn tick_size(&self) -> f64 {
         self.tick_size
     }
+
+    fn repaired_count(&self) -> u64 {
+        self.repaired
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.halted
+    }
+
+    fn last_halted(&self) -> bool {
+        self.last_halted
+    }
+}
+
+// ============================================================================
+// Chaining
+// ============================================================================
+
+/// Parser yielding the candles of several parsers back to back
+///
+/// Created by [`chain`]. Each source is read lazily once the previous one is
+/// exhausted, so e.g. monthly files stream as one sequence.
+pub struct ChainedParser {
+    parsers: Vec<Box<dyn CandleParser>>,
+    current: usize,
+    tick_size: f64,
+}
+
+/// Concatenate parsers into one stream
+///
+/// All parsers must share the same tick size (`TickSizeMismatch` otherwise),
+/// and at least one is required. Candles are not re-sorted: sources should be
+/// given in chronological order.
+pub fn chain(parsers: Vec<Box<dyn CandleParser>>) -> Result<ChainedParser, ParseError> {
+    let tick_size = parsers
+        .first()
+        .map(|p| p.tick_size())
+        .ok_or_else(|| ParseError::InvalidValue {
+            field: "parsers".to_string(),
+            value: "no parsers to chain".to_string(),
+        })?;
+
+    if let Some((index, parser)) = parsers.iter().enumerate().find(|(_, p)| p.tick_size() != tick_size) {
+        return Err(ParseError::TickSizeMismatch {
+            index,
+            expected: tick_size,
+            found: parser.tick_size(),
+        });
+    }
+
+    Ok(ChainedParser {
+        parsers,
+        current: 0,
+        tick_size,
+    })
+}
+
+impl Iterator for ChainedParser {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(parser) = self.parsers.get_mut(self.current) {
+            match parser.next() {
+                Some(item) => return Some(item),
+                None => self.current += 1,
+            }
+        }
+
+        None
+    }
+}
+
+impl CandleParser for ChainedParser {
+    fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    /// Sum over all sources, or `None` if any source is unknown
+    fn size_hint_total(&self) -> Option<usize> {
+        self.parsers.iter().map(|p| p.size_hint_total()).sum()
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parsers.iter().map(|p| p.repaired_count()).sum()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parsers.iter().map(|p| p.halted_count()).sum()
+    }
+
+    fn last_halted(&self) -> bool {
+        self.parsers.get(self.current).is_some_and(|p| p.last_halted())
+    }
+}
+
+/// Parser interleaving several sources into one time-ordered stream
+///
+/// Created by [`merge`].
+pub struct MergedParser {
+    parsers: Vec<Box<dyn CandleParser>>,
+    /// Next candle of each source, once read
+    heads: Vec<Option<Candle>>,
+    /// `(ts_open, source index)` of every buffered head, smallest first
+    queue: BinaryHeap<Reverse<(i64, usize)>>,
+    /// Sources whose head must be read before the next candle can be chosen
+    refill: VecDeque<usize>,
+    /// Source of the candle most recently yielded
+    last: Option<usize>,
+    tick_size: f64,
+}
+
+/// Merge parsers into one stream ordered by `ts_open`
+///
+/// Each source must itself be in chronological order. Ties are broken
+/// deterministically: candles sharing a `ts_open` come out in source order
+/// (the index in `parsers`), and candles with the same timestamp in one
+/// source keep their input order. The result therefore only depends on the
+/// data and the order the sources are given in, never on read timing.
+///
+/// Parse errors are yielded as soon as they are read; the failing source
+/// keeps going afterwards. Tick sizes must match, as for [`chain`].
+pub fn merge(parsers: Vec<Box<dyn CandleParser>>) -> Result<MergedParser, ParseError> {
+    let ChainedParser { parsers, tick_size, .. } = chain(parsers)?;
+
+    Ok(MergedParser {
+        heads: vec![None; parsers.len()],
+        queue: BinaryHeap::with_capacity(parsers.len()),
+        refill: (0..parsers.len()).collect(),
+        parsers,
+        last: None,
+        tick_size,
+    })
+}
+
+impl Iterator for MergedParser {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&index) = self.refill.front() {
+            match self.parsers[index].next() {
+                Some(Ok(candle)) => {
+                    self.heads[index] = Some(candle);
+                    self.queue.push(Reverse((candle.ts_open, index)));
+                }
+                // Leave the source queued so it is read again next time
+                Some(Err(e)) => return Some(Err(e)),
+                None => {}
+            }
+            self.refill.pop_front();
+        }
+
+        let Reverse((_, index)) = self.queue.pop()?;
+        self.refill.push_back(index);
+        self.last = Some(index);
+        self.heads[index].take().map(Ok)
+    }
+}
+
+impl CandleParser for MergedParser {
+    fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    /// Sum over all sources, or `None` if any source is unknown
+    fn size_hint_total(&self) -> Option<usize> {
+        self.parsers.iter().map(|p| p.size_hint_total()).sum()
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parsers.iter().map(|p| p.repaired_count()).sum()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parsers.iter().map(|p| p.halted_count()).sum()
+    }
+
+    /// Each head is read only after the previous candle of its source was
+    /// yielded, so the source still reports on the candle just returned
+    fn last_halted(&self) -> bool {
+        self.last.is_some_and(|index| self.parsers[index].last_halted())
+    }
+}
+
+/// Parser yielding at most a fixed number of candles; see [`CandleParser::limit`]
+pub struct Limit<P: CandleParser> {
+    parser: P,
+    remaining: usize,
+}
+
+impl<P: CandleParser> Limit<P> {
+    /// Candles still allowed through
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+impl<P: CandleParser> Iterator for Limit<P> {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.parser.next()
+    }
+}
+
+impl<P: CandleParser> CandleParser for Limit<P> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+
+    fn size_hint_total(&self) -> Option<usize> {
+        self.parser.size_hint_total().map(|total| total.min(self.remaining))
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parser.repaired_count()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parser.halted_count()
+    }
+
+    fn last_halted(&self) -> bool {
+        self.parser.last_halted()
+    }
+}
+
+/// Parser checking time order; see [`CandleParser::assert_sorted`]
+pub struct AssertSorted<P: CandleParser> {
+    parser: P,
+    /// `ts_open` of the last candle yielded
+    prev_ts: Option<i64>,
+    failed: bool,
+}
+
+impl<P: CandleParser> AssertSorted<P> {
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+impl<P: CandleParser> Iterator for AssertSorted<P> {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let candle = match self.parser.next()? {
+            Ok(candle) => candle,
+            Err(e) => return Some(Err(e)),
+        };
+        if let Some(prev_ts) = self.prev_ts {
+            if candle.ts_open < prev_ts {
+                self.failed = true;
+                return Some(Err(ParseError::OutOfOrder {
+                    prev_ts,
+                    cur_ts: candle.ts_open,
+                }));
+            }
+        }
+
+        self.prev_ts = Some(candle.ts_open);
+        Some(Ok(candle))
+    }
+}
+
+impl<P: CandleParser> CandleParser for AssertSorted<P> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+
+    fn size_hint_total(&self) -> Option<usize> {
+        self.parser.size_hint_total()
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parser.repaired_count()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parser.halted_count()
+    }
+
+    fn last_halted(&self) -> bool {
+        self.parser.last_halted()
+    }
+}
+
+// ============================================================================
+// Chunking
+// ============================================================================
+
+/// Group a parser's candles into `Vec`s of `chunk_size`, for batch or
+/// vectorized consumers
+///
+/// Every chunk is full except possibly the last, which holds whatever was
+/// left at the end of input. A parse error is yielded as soon as it is
+/// read, ahead of the chunk being filled; the candles already collected
+/// are carried, not discarded, and the chunk keeps filling afterwards. So
+/// the chunks together hold every valid candle in order.
+///
+/// # Panics
+/// If `chunk_size` is zero.
+pub fn chunk_candles<P: CandleParser>(parser: P, chunk_size: usize) -> CandleChunks<P> {
+    assert!(chunk_size > 0, "chunk size must be positive");
+    CandleChunks {
+        parser,
+        chunk_size,
+        chunk: Vec::with_capacity(chunk_size),
+    }
+}
+
+/// Iterator over candle chunks; see [`chunk_candles`]
+pub struct CandleChunks<P: CandleParser> {
+    parser: P,
+    chunk_size: usize,
+    /// Candles read since the last chunk was yielded
+    chunk: Vec<Candle>,
+}
+
+impl<P: CandleParser> CandleChunks<P> {
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+impl<P: CandleParser> Iterator for CandleChunks<P> {
+    type Item = Result<Vec<Candle>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.chunk.len() < self.chunk_size {
+            match self.parser.next() {
+                Some(Ok(candle)) => self.chunk.push(candle),
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+
+        if self.chunk.is_empty() {
+            return None;
+        }
+        let next = Vec::with_capacity(self.chunk_size);
+        Some(Ok(std::mem::replace(&mut self.chunk, next)))
+    }
+}
+
+/// Only the candles whose `ts_open` is within `[start_ms, end_ms]`, inclusive
+///
+/// Assumes `parser` yields candles sorted by `ts_open`: iteration stops at
+/// the first candle past `end_ms`, so the rest of a large file is never
+/// read. On unsorted input, in-window candles after that point are lost;
+/// chain [`CandleParser::assert_sorted`] first to make that an error.
+/// Parse errors are passed through wherever they occur.
+pub fn filter_time_window<P: CandleParser>(parser: P, start_ms: i64, end_ms: i64) -> TimeWindow<P> {
+    TimeWindow {
+        parser,
+        start_ms,
+        end_ms,
+        done: false,
+    }
+}
+
+/// Parser limited to a time window; see [`filter_time_window`]
+pub struct TimeWindow<P: CandleParser> {
+    parser: P,
+    start_ms: i64,
+    end_ms: i64,
+    /// A candle past `end_ms` has been seen
+    done: bool,
+}
+
+impl<P: CandleParser> TimeWindow<P> {
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+impl<P: CandleParser> Iterator for TimeWindow<P> {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.parser.next()? {
+                Ok(candle) if candle.ts_open < self.start_ms => continue,
+                Ok(candle) if candle.ts_open > self.end_ms => self.done = true,
+                item => return Some(item),
+            }
+        }
+        None
+    }
+}
+
+impl<P: CandleParser> CandleParser for TimeWindow<P> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parser.repaired_count()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parser.halted_count()
+    }
+
+    fn last_halted(&self) -> bool {
+        self.parser.last_halted()
+    }
+}
+
+/// Boxed parsers (e.g. from [`from_file_path`]) work wherever a parser is expected
+impl<P: CandleParser + ?Sized> CandleParser for Box<P> {
+    fn tick_size(&self) -> f64 {
+        (**self).tick_size()
+    }
+
+    fn size_hint_total(&self) -> Option<usize> {
+        (**self).size_hint_total()
+    }
+
+    fn repaired_count(&self) -> u64 {
+        (**self).repaired_count()
+    }
+
+    fn halted_count(&self) -> u64 {
+        (**self).halted_count()
+    }
+
+    fn last_halted(&self) -> bool {
+        (**self).last_halted()
+    }
 }
 
 // ============================================================================
 // Convenience constructors
 // ============================================================================
 
+/// Text format of a candle stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Csv,
+    /// One JSON object per line
+    Json,
+}
+
+impl DataFormat {
+    /// Parse a format name or file extension: `"csv"`, or `"json"`,
+    /// `"jsonl"` or `"ndjson"`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(DataFormat::Csv),
+            "json" | "jsonl" | "ndjson" => Some(DataFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Create a candle parser over any reader, such as `std::io::stdin().lock()`
+///
+/// There is no extension to go by, so the format is explicit. The reader is
+/// streamed, not buffered up front; wrap unbuffered sources in a
+/// `BufReader`.
+pub fn from_reader<R: Read + 'static>(
+    reader: R,
+    format: DataFormat,
+    tick_size: f64,
+) -> Result<Box<dyn CandleParser>, ParseError> {
+    match format {
+        DataFormat::Csv => Ok(Box::new(CsvCandleIter::new(reader, tick_size)?)),
+        DataFormat::Json => Ok(Box::new(JsonCandleIter::new(reader, tick_size))),
+    }
+}
+
 /// Create a candle parser from a file path based on extension
+///
+/// The parser is `Send`, so it can be handed to a feeder thread.
 pub fn from_file_path(
     path: impl AsRef<std::path::Path>,
     tick_size: f64,
-) -> Result<Box<dyn CandleParser>, ParseError> {
+) -> Result<Box<dyn CandleParser + Send>, ParseError> {
     let path = path.as_ref();
-    let file = std::fs::File::open(path)?;
-    let reader = std::io::BufReader::new(file);
-
-    match path.extension().and_then(|s| s.to_str()) {
-        Some("csv") => {
-            Ok(Box::new(CsvCandleIter::new(reader, tick_size)?))
-        }
-        Some("json") | Some("jsonl") | Some("ndjson") => {
-            Ok(Box::new(JsonCandleIter::new(reader, tick_size)))
-        }
-        _ => Err(ParseError::InvalidValue {
+    let format = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .and_then(DataFormat::from_name)
+        .ok_or_else(|| ParseError::InvalidValue {
             field: "file_extension".to_string(),
             value: format!("{:?}", path.extension()),
+        })?;
+
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    match format {
+        DataFormat::Csv => Ok(Box::new(CsvCandleIter::new(reader, tick_size)?)),
+        DataFormat::Json => Ok(Box::new(JsonCandleIter::new(reader, tick_size))),
+    }
+}
+
+/// Create a candle parser over in-memory data in `format`
+///
+/// `format` is `"csv"`, `"json"` (or `"jsonl"`/`"ndjson"`), or `"parquet"`
+/// with the `parquet` feature. Parquet input is decoded up front; the text
+/// formats stream from the buffer.
+pub fn from_bytes(data: Vec<u8>, format: &str, tick_size: f64) -> Result<Box<dyn CandleParser>, ParseError> {
+    if let Some(text_format) = DataFormat::from_name(format) {
+        return from_reader(std::io::Cursor::new(data), text_format, tick_size);
+    }
+
+    match format {
+        #[cfg(feature = "parquet")]
+        "parquet" => Ok(Box::new(ParquetCandles::from_bytes(data, tick_size)?)),
+        #[cfg(not(feature = "parquet"))]
+        "parquet" => Err(ParseError::Unsupported(
+            "parquet input requires the `parquet` feature".to_string(),
+        )),
+        _ => Err(ParseError::InvalidValue {
+            field: "format".to_string(),
+            value: format.to_string(),
         }),
     }
 }
 
+#[cfg(feature = "parquet")]
+pub use parquet_input::ParquetCandles;
+
+#[cfg(feature = "parquet")]
+mod parquet_input {
+    use super::{CandleParser, ParseError};
+    use crate::candle::{from_record_batch, Candle};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    /// Candles decoded from a Parquet file with the columns of
+    /// [`crate::candle::candle_schema`]
+    pub struct ParquetCandles {
+        candles: std::vec::IntoIter<Candle>,
+        total: usize,
+        tick_size: f64,
+    }
+
+    impl ParquetCandles {
+        pub fn from_bytes(data: Vec<u8>, tick_size: f64) -> Result<Self, ParseError> {
+            let reader = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(data))?.build()?;
+
+            let mut candles = Vec::new();
+            for batch in reader {
+                let batch = batch.map_err(parquet::errors::ParquetError::from)?;
+                candles.extend(from_record_batch(&batch, tick_size)?);
+            }
+
+            Ok(Self {
+                total: candles.len(),
+                candles: candles.into_iter(),
+                tick_size,
+            })
+        }
+    }
+
+    impl Iterator for ParquetCandles {
+        type Item = Result<Candle, ParseError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.candles.next().map(Ok)
+        }
+    }
+
+    impl CandleParser for ParquetCandles {
+        fn tick_size(&self) -> f64 {
+            self.tick_size
+        }
+
+        fn size_hint_total(&self) -> Option<usize> {
+            Some(self.total)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +1911,291 @@ timestamp,open,high,low,close,volume
         assert!(parser.next().is_none());
     }
 
+    #[test]
+    fn test_from_bytes_dispatches_on_format() {
+        let csv_data = b"timestamp,open,high,low,close,volume\n1609459200000,42000,42500,41500,42200,1500\n";
+        let json_data = br#"{"timestamp":1609459200000,"open":42000,"high":42500,"low":41500,"close":42200,"volume":1500}"#;
+
+        let from_csv: Vec<Candle> = from_bytes(csv_data.to_vec(), "csv", 1.0).unwrap().map(Result::unwrap).collect();
+        let from_json: Vec<Candle> = from_bytes(json_data.to_vec(), "json", 1.0).unwrap().map(Result::unwrap).collect();
+        assert_eq!(from_csv.len(), 1);
+        assert_eq!(from_csv[0].close_tick, 42200);
+        assert_eq!(from_json[0].close_tick, 42200);
+
+        assert!(matches!(
+            from_bytes(csv_data.to_vec(), "xlsx", 1.0).err(),
+            Some(ParseError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_reader_streams_any_reader() {
+        let csv_data = "timestamp,open,high,low,close,volume\n1609459200000,42000,42500,41500,42200,1500\n1609459260000,42200,42800,42100,42700,2000\n";
+
+        let parser = from_reader(Cursor::new(csv_data.as_bytes()), DataFormat::Csv, 1.0).unwrap();
+        assert_eq!(parser.tick_size(), 1.0);
+        let candles: Vec<Candle> = parser.collect::<Result<_, _>>().unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[1].close_tick, 42700);
+
+        assert_eq!(DataFormat::from_name("ndjson"), Some(DataFormat::Json));
+        assert_eq!(DataFormat::from_name("xlsx"), None);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_from_bytes_reads_parquet() {
+        use crate::candle::to_record_batch;
+        use parquet::arrow::ArrowWriter;
+
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000.5,42500.0,41500.0,42200.0,1500.5
+1609459260000,42200.0,42800.0,42100.0,42700.0,2000.3
+";
+        let candles: Vec<Candle> = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let batch = to_record_batch(&candles, 0.5);
+
+        let mut data = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut data, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let parser = from_bytes(data, "parquet", 0.5).unwrap();
+        assert_eq!(parser.size_hint_total(), Some(2));
+        assert_eq!(parser.map(Result::unwrap).collect::<Vec<_>>(), candles);
+    }
+
+    #[test]
+    fn test_hourly_timestamps_from_interval() {
+        let hourly_csv = "\
+timestamp,open,high,low,close,volume
+1609459200000,100,101,99,100,10
+1609462800000,100,102,99,101,10
+1609466400000,101,103,100,102,10
+";
+        let ts_close = |parser: CsvCandleIter<Cursor<&[u8]>>| -> Vec<i64> {
+            parser.map(|c| c.unwrap().ts_close).collect()
+        };
+
+        // The one-minute default mislabels hourly bars
+        let default = CsvCandleIter::new(Cursor::new(hourly_csv.as_bytes()), 1.0).unwrap();
+        assert_eq!(ts_close(default)[0], 1609459260000);
+
+        let expected = vec![1609462800000, 1609466400000, 1609470000000];
+        let explicit = CsvCandleIter::new(Cursor::new(hourly_csv.as_bytes()), 1.0)
+            .unwrap()
+            .default_interval_ms(3_600_000);
+        assert_eq!(ts_close(explicit), expected);
+
+        let inferred = CsvCandleIter::new(Cursor::new(hourly_csv.as_bytes()), 1.0)
+            .unwrap()
+            .infer_interval(true);
+        assert_eq!(ts_close(inferred), expected);
+
+        let mut inferred = CsvCandleIter::new(Cursor::new(hourly_csv.as_bytes()), 1.0)
+            .unwrap()
+            .infer_interval(true);
+        inferred.next().unwrap().unwrap();
+        assert!(matches!(inferred.finish(), Err(ParseError::TrailingData(_))));
+
+        // JSON with close times only: open times are derived backwards
+        let hourly_json = r#"{"ts_close":1609462800000,"open":100,"high":101,"low":99,"close":100,"volume":10}
+{"ts_close":1609466400000,"open":100,"high":102,"low":99,"close":101,"volume":10}
+"#;
+        let ts_open: Vec<i64> = JsonCandleIter::new(Cursor::new(hourly_json.as_bytes()), 1.0)
+            .infer_interval(true)
+            .map(|c| c.unwrap().ts_open)
+            .collect();
+        assert_eq!(ts_open, vec![1609459200000, 1609462800000]);
+
+        let single = r#"{"ts":1609459200000,"open":100,"high":101,"low":99,"close":100,"volume":10}"#;
+        let candle = JsonCandleIter::new(Cursor::new(single.as_bytes()), 1.0)
+            .infer_interval(true)
+            .default_interval_ms(86_400_000)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(candle.ts_close, 1609459200000 + 86_400_000);
+    }
+
+    #[test]
+    fn test_limit_stops_after_max_candles() {
+        let mut csv_data = String::from("timestamp,open,high,low,close,volume\n");
+        for i in 0..10i64 {
+            csv_data.push_str(&format!("{},100,101,99,100,1\n", 1609459200000 + i * 60000));
+        }
+
+        let mut limited = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap().limit(4);
+        let candles: Vec<Candle> = limited.by_ref().map(Result::unwrap).collect();
+        assert_eq!(candles.len(), 4);
+        assert_eq!(candles[3].ts_open, 1609459200000 + 3 * 60000);
+        assert_eq!(limited.remaining(), 0);
+
+        // The source was not read past the limit
+        let rest = limited.into_inner().count();
+        assert_eq!(rest, 6);
+
+        let adapter = crate::market_event::CandleEventAdapter::new(
+            CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap().limit(7),
+        );
+        let metrics = adapter.metrics_handle();
+        assert_eq!(adapter.count(), 7);
+        assert_eq!(metrics.snapshot().candles_processed, 7);
+    }
+
+    #[test]
+    fn test_assert_sorted() {
+        let sorted = "\
+timestamp,open,high,low,close,volume
+1609459200000,100,101,99,100,1
+1609459260000,100,101,99,100,1
+1609459260000,100,101,99,100,1
+1609459320000,100,101,99,100,1
+";
+        let candles: Vec<_> = CsvCandleIter::new(Cursor::new(sorted.as_bytes()), 1.0)
+            .unwrap()
+            .assert_sorted()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(candles.len(), 4);
+
+        // Two files concatenated in the wrong order
+        let unsorted = "\
+timestamp,open,high,low,close,volume
+1609459320000,100,101,99,100,1
+1609459380000,100,101,99,100,1
+1609459200000,100,101,99,100,1
+1609459260000,100,101,99,100,1
+";
+        let results: Vec<_> = CsvCandleIter::new(Cursor::new(unsorted.as_bytes()), 1.0)
+            .unwrap()
+            .assert_sorted()
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(Result::is_ok));
+        assert!(matches!(
+            results[2],
+            Err(ParseError::OutOfOrder { prev_ts: 1609459380000, cur_ts: 1609459200000 })
+        ));
+    }
+
+    #[test]
+    fn test_chunk_candles() {
+        let mut csv_data = String::from("timestamp,open,high,low,close,volume\n");
+        for i in 0..10i64 {
+            csv_data.push_str(&format!("{},100,101,99,100,1\n", 1609459200000 + i * 60000));
+        }
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        let chunks: Vec<Vec<Candle>> = chunk_candles(parser, 4).collect::<Result<_, _>>().unwrap();
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![4, 4, 2]);
+        assert_eq!(chunks[2][1].ts_open, 1609459200000 + 9 * 60000);
+
+        // An error surfaces first; the partial chunk is carried into the next one
+        let with_error = csv_data.replacen("1609459320000,100", "1609459320000,oops", 1);
+        let parser = CsvCandleIter::new(Cursor::new(with_error.as_bytes()), 1.0).unwrap();
+        let results: Vec<_> = chunk_candles(parser, 4).collect();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], Err(ParseError::InvalidValue { .. })));
+        let lens: Vec<usize> = results[1..].iter().map(|r| r.as_ref().unwrap().len()).collect();
+        assert_eq!(lens, vec![4, 4, 1]);
+        assert_eq!(results[1].as_ref().unwrap()[2].ts_open, 1609459200000 + 3 * 60000);
+    }
+
+    #[test]
+    fn test_filter_time_window() {
+        let start = 1609459200000i64;
+        let mut csv_data = String::from("timestamp,open,high,low,close,volume\n");
+        for i in 0..1440i64 {
+            csv_data.push_str(&format!("{},100,101,99,100,1\n", start + i * 60000));
+        }
+
+        let hour_start = start + 10 * 3_600_000;
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        let mut window = filter_time_window(parser, hour_start, hour_start + 3_599_999);
+        let candles: Vec<Candle> = window.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(candles.len(), 60);
+        assert_eq!(candles[0].ts_open, hour_start);
+        assert_eq!(candles[59].ts_open, hour_start + 59 * 60000);
+
+        // Stopped at the first candle past the window, leaving the rest unread
+        assert_eq!(window.into_inner().count(), 1440 - 661);
+    }
+
+    #[test]
+    fn test_chain_concatenates_sources() {
+        let january = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+";
+        let february = "\
+timestamp,open,high,low,close,volume
+1612137600000,33000,33500,32500,33200,900
+";
+
+        let parsers: Vec<Box<dyn CandleParser>> = vec![
+            Box::new(CsvCandleIter::new(Cursor::new(january.as_bytes()), 1.0).unwrap()),
+            Box::new(CsvCandleIter::new(Cursor::new(february.as_bytes()), 1.0).unwrap()),
+        ];
+        let chained = chain(parsers).unwrap();
+        assert_eq!(chained.tick_size(), 1.0);
+        assert_eq!(chained.size_hint_total(), None);
+
+        let ts: Vec<i64> = chained.map(|c| c.unwrap().ts_open).collect();
+        assert_eq!(ts, vec![1609459200000, 1609459260000, 1612137600000]);
+    }
+
+    #[test]
+    fn test_merge_breaks_timestamp_ties_by_source_then_input_order() {
+        // close encodes (source, row) so the output order is visible
+        let first = "\
+timestamp,open,high,low,close,volume
+1000,100,200,100,101,1
+2000,100,200,100,102,1
+2000,100,200,100,103,1
+3000,100,200,100,104,1
+";
+        let second = "\
+timestamp,open,high,low,close,volume
+1000,100,200,100,111,1
+1000,100,200,100,112,1
+2000,100,200,100,113,1
+2500,100,200,100,114,1
+";
+
+        let merged = || {
+            let parsers: Vec<Box<dyn CandleParser>> = vec![
+                Box::new(CsvCandleIter::new(Cursor::new(first.as_bytes()), 1.0).unwrap()),
+                Box::new(CsvCandleIter::new(Cursor::new(second.as_bytes()), 1.0).unwrap()),
+            ];
+            merge(parsers).unwrap().map(|c| c.unwrap().close_tick).collect::<Vec<_>>()
+        };
+
+        let expected = vec![101, 111, 112, 102, 103, 113, 114, 104];
+        assert_eq!(merged(), expected);
+        assert_eq!(merged(), expected);
+    }
+
+    #[test]
+    fn test_chain_rejects_tick_size_mismatch() {
+        let csv_data = "timestamp,open,high,low,close,volume\n";
+
+        let parsers: Vec<Box<dyn CandleParser>> = vec![
+            Box::new(CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5).unwrap()),
+            Box::new(CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.01).unwrap()),
+        ];
+        assert!(matches!(
+            chain(parsers),
+            Err(ParseError::TickSizeMismatch { index: 1, .. })
+        ));
+        assert!(chain(Vec::new()).is_err());
+    }
+
     #[test]
     fn test_csv_parser_flexible_headers() {
         let csv_data = "\
@@ -485,4 +2245,445 @@ timestamp,open,high,low,close,volume
 
         assert!(parser.next().is_none());
     }
+
+    #[test]
+    fn test_csv_parser_repair_invalid() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42190,41500,42200,1500
+";
+
+        // Strict by default
+        let mut parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        assert!(parser.next().unwrap().is_err());
+
+        let mut parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0)
+            .unwrap()
+            .repair_invalid(true);
+        let candle = parser.next().unwrap().unwrap();
+        assert!(candle.is_valid());
+        assert_eq!(candle.high_tick, 42200);
+        assert_eq!(parser.repaired_count(), 1);
+    }
+
+    #[test]
+    fn test_json_parser_repair_invalid() {
+        let json_data = r#"{"ts_open":1609459200000,"open":42000,"high":42500,"low":42010,"close":42200,"volume":1500}"#;
+
+        let mut parser = JsonCandleIter::new(Cursor::new(json_data.as_bytes()), 1.0)
+            .repair_invalid(true);
+        let candle = parser.next().unwrap().unwrap();
+        assert!(candle.is_valid());
+        assert_eq!(candle.low_tick, 42000);
+        assert_eq!(parser.repaired_count(), 1);
+    }
+
+    #[test]
+    fn test_csv_close_only() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000.5,42500.0,41500.0,42200.0,1500.5
+1609459260000,42200.0,42100.0,42300.0,42700.0,-1
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let mut parser = CsvCandleIter::new(cursor, 0.5).unwrap().close_only();
+
+        assert_eq!(parser.next().unwrap().unwrap(), (1609459200000, 84400));
+        // OHLCV is not validated in close-only mode
+        assert_eq!(parser.next().unwrap().unwrap(), (1609459260000, 85400));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn test_csv_close_only_invalid_close() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,abc,1500
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let mut parser = CsvCandleIter::new(cursor, 1.0).unwrap().close_only();
+
+        assert!(matches!(parser.next().unwrap(), Err(ParseError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_csv_finish_clean_and_truncated() {
+        let header = "timestamp,open,high,low,close,volume\n";
+        let row = "1609459200000,42000,42500,41500,42200,1500\n";
+
+        let clean = format!("{}{}", header, row);
+        let mut parser = CsvCandleIter::new(Cursor::new(clean.into_bytes()), 1.0).unwrap();
+        assert!(parser.by_ref().all(|r| r.is_ok()));
+        assert!(parser.finish().is_ok());
+
+        let truncated = format!("{}{}1609459260000,42200,428", header, row);
+        let mut parser = CsvCandleIter::new(Cursor::new(truncated.into_bytes()), 1.0).unwrap();
+        assert_eq!(parser.by_ref().filter(|r| r.is_ok()).count(), 1);
+        assert!(matches!(parser.finish(), Err(ParseError::TruncatedRecord(_))));
+
+        let unconsumed = format!("{}{}{}", header, row, row);
+        let mut parser = CsvCandleIter::new(Cursor::new(unconsumed.into_bytes()), 1.0).unwrap();
+        parser.next().unwrap().unwrap();
+        assert!(matches!(parser.finish(), Err(ParseError::TrailingData(_))));
+    }
+
+    #[test]
+    fn test_csv_finish_trailing_junk() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1,2,3,4,5,6,7,8
+";
+
+        let mut parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        assert_eq!(parser.by_ref().filter(|r| r.is_ok()).count(), 1);
+        assert!(matches!(parser.finish(), Err(ParseError::TrailingData(_))));
+    }
+
+    #[test]
+    fn test_csv_decimal_prices_are_exact() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,0.28,0.295,0.275,0.285,10
+";
+
+        let candle = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.01).unwrap().next().unwrap().unwrap();
+        // The float path would put the 0.285 close at 28 ticks
+        assert_eq!(
+            (candle.open_tick, candle.high_tick, candle.low_tick, candle.close_tick),
+            (28, 30, 28, 29)
+        );
+
+        // Not a power of ten: the float path is used
+        let candle = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.005).unwrap().next().unwrap().unwrap();
+        assert_eq!(candle.close_tick, 57);
+    }
+
+    #[test]
+    fn test_parse_timestamp_ms() {
+        assert_eq!(parse_timestamp_ms("1609459200000"), Some(1_609_459_200_000));
+        assert_eq!(parse_timestamp_ms("2021-01-01T00:00:00Z"), Some(1_609_459_200_000));
+        assert_eq!(parse_timestamp_ms("2021-01-01"), Some(1_609_459_200_000));
+        assert_eq!(parse_timestamp_ms("2021-01-01 00:01:00.5"), Some(1_609_459_260_500));
+        assert_eq!(parse_timestamp_ms("2021-01-01T00:00:00.123456Z"), Some(1_609_459_200_123));
+        assert_eq!(parse_timestamp_ms("2021-01-01T02:00:00+02:00"), Some(1_609_459_200_000));
+        assert_eq!(parse_timestamp_ms("2020-12-31T19:00:00-05:00"), Some(1_609_459_200_000));
+        assert_eq!(parse_timestamp_ms("2024-02-29T12:00:00Z"), Some(1_709_208_000_000));
+        assert_eq!(parse_timestamp_ms("1969-12-31T23:59:59Z"), Some(-1_000));
+
+        for bad in ["2023-02-29", "2021-13-01", "2021-01-01T24:00:00Z", "2021-01-01T00:00", "2021-01-01T00:00:00.Z", "01/01/2021", ""] {
+            assert_eq!(parse_timestamp_ms(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_loose_f64() {
+        assert_eq!(parse_loose_f64("1,234.5", false), Some(1234.5));
+        assert_eq!(parse_loose_f64("1.5e3", false), Some(1500.0));
+        assert_eq!(parse_loose_f64("  42.0  ", false), Some(42.0));
+        assert_eq!(parse_loose_f64("+42", false), Some(42.0));
+        assert_eq!(parse_loose_f64("-1,234,567.25", false), Some(-1_234_567.25));
+        assert_eq!(parse_loose_f64(".5", false), Some(0.5));
+        assert_eq!(parse_loose_f64("2.5E-2", false), Some(0.025));
+        assert_eq!(parse_loose_f64("1.234,5", true), Some(1234.5));
+        assert_eq!(parse_loose_f64("0,25", true), Some(0.25));
+
+        // Separators in the wrong place are refused rather than guessed
+        for bad in ["1,5", "12,34.5", "1234,567", ",123", "1,234.5,6", "1.5", "inf", "NaN", "", "+", ".", "1e", "1,234e3,0"] {
+            let decimal_comma = bad == "1.5";
+            assert_eq!(parse_loose_f64(bad, decimal_comma), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_csv_loose_numbers() {
+        let csv_data = "timestamp,open,high,low,close,volume\n1609459200000,\"1,234.5\", 1.2355e3 ,\"1,230\",+1234.00,\"12,000\"\n";
+        let options = ParseOptions {
+            loose_numbers: true,
+            ..ParseOptions::default()
+        };
+
+        let mut strict = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.01).unwrap();
+        assert!(matches!(strict.next(), Some(Err(ParseError::InvalidValue { .. }))));
+
+        let candle = CsvCandleIter::new_with_options(Cursor::new(csv_data.as_bytes()), 0.01, options.clone())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (candle.open_tick, candle.high_tick, candle.low_tick, candle.close_tick),
+            (123_450, 123_550, 123_000, 123_400)
+        );
+        assert_eq!(candle.volume_scaled, 12_000_000_000);
+
+        // A comma-decimal export reads the same only when asked to
+        let comma_decimal = "timestamp,open,high,low,close,volume\n1609459200000,\"1.234,5\",\"1.235,5\",\"1.230\",\"1.234\",\"12.000\"\n";
+        let options = ParseOptions { decimal_comma: true, ..options };
+        let same = CsvCandleIter::new_with_options(Cursor::new(comma_decimal.as_bytes()), 0.01, options)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(same, candle);
+    }
+
+    #[test]
+    fn test_csv_nan_volume_policies() {
+        let csv_data = "timestamp,open,high,low,close,volume\n1609459200000,100,101,99,100.5,NaN\n1609459260000,100.5,102,100,101,5\n";
+        let parse = |policy: NanPolicy| {
+            CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5)
+                .unwrap()
+                .nan_policy(policy)
+                .treat_zero_volume(ZeroVolumePolicy::MarkHalt)
+        };
+
+        let mut rejected = parse(NanPolicy::Reject);
+        assert!(matches!(rejected.next(), Some(Err(ParseError::InvalidCandle(_)))));
+        assert_eq!(rejected.next().unwrap().unwrap().volume_scaled, 5_000_000);
+
+        // Zero volume is a halt under MarkHalt; unknown volume is not
+        let mut zeroed = parse(NanPolicy::TreatAsZero);
+        assert_eq!(zeroed.next().unwrap().unwrap().volume_scaled, 0);
+        assert!(zeroed.last_halted());
+
+        let mut unknown = parse(NanPolicy::Propagate);
+        let candle = unknown.next().unwrap().unwrap();
+        assert_eq!((candle.volume_scaled, candle.close_tick), (0, 201));
+        assert!(!unknown.last_halted());
+        assert_eq!(unknown.halted_count(), 0);
+
+        let nan_price = "timestamp,open,high,low,close,volume\n1609459200000,100,101,99,NaN,5\n";
+        let mut parser = CsvCandleIter::new(Cursor::new(nan_price.as_bytes()), 0.5)
+            .unwrap()
+            .nan_policy(NanPolicy::Propagate);
+        assert!(matches!(parser.next(), Some(Err(ParseError::InvalidCandle(_)))));
+    }
+
+    #[test]
+    fn test_csv_iso_timestamps() {
+        let epoch = "timestamp,open,high,low,close,volume\n1609459200000,100,101,99,100,1\n1609459260000,100,101,99,100,1\n";
+        let iso = "timestamp,open,high,low,close,volume\n2021-01-01T00:00:00Z,100,101,99,100,1\n2021-01-01T00:01:00Z,100,101,99,100,1\n";
+        let options = ParseOptions {
+            iso_timestamps: true,
+            infer_interval: true,
+            ..ParseOptions::default()
+        };
+
+        let expected: Vec<Candle> = CsvCandleIter::new(Cursor::new(epoch.as_bytes()), 1.0).unwrap().collect::<Result<_, _>>().unwrap();
+        for csv_data in [epoch, iso] {
+            let candles: Vec<Candle> = CsvCandleIter::new_with_options(Cursor::new(csv_data.as_bytes()), 1.0, options.clone())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(candles, expected);
+        }
+        assert_eq!(expected[0].ts_open, 1_609_459_200_000);
+
+        // Without the flag ISO text is an invalid value
+        let mut strict = CsvCandleIter::new(Cursor::new(iso.as_bytes()), 1.0).unwrap();
+        assert!(matches!(strict.next(), Some(Err(ParseError::InvalidValue { .. }))));
+    }
+
+    #[test]
+    fn test_csv_flexible_ragged_rows() {
+        // Export with a trailing empty column on some rows and a short last row
+        let csv_data = "\
+timestamp,open,high,low,close,volume,trades
+1609459200000,42000,42500,41500,42200,1500,10,
+1609459260000,42200,42800,42100,42700,2000,12
+1609459320000,42700,42900,42600,42800,1800
+";
+
+        let strict: Vec<_> = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap().collect();
+        assert!(matches!(strict[0], Err(ParseError::Csv(_))));
+
+        let options = ParseOptions {
+            flexible: true,
+            ..ParseOptions::default()
+        };
+        let mut parser = CsvCandleIter::new_with_options(Cursor::new(csv_data.as_bytes()), 1.0, options.clone()).unwrap();
+        let candles: Vec<Candle> = parser.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[0].trade_count, 10);
+        assert_eq!(candles[1].close_tick, 42700);
+        assert_eq!(candles[2].trade_count, TRADE_COUNT_UNKNOWN);
+        assert!(parser.finish().is_ok());
+
+        // A row missing a required column is still an error
+        let short = "timestamp,open,high,low,close,volume\n1609459200000,42000,42500,41500,42200\n";
+        let mut parser = CsvCandleIter::new_with_options(Cursor::new(short.as_bytes()), 1.0, options).unwrap();
+        assert!(matches!(parser.next(), Some(Err(ParseError::MissingField(_)))));
+    }
+
+    #[test]
+    fn test_json_finish() {
+        let row = r#"{"ts":1609459200000,"o":42000,"h":42500,"l":41500,"c":42200,"v":1500}"#;
+
+        let clean = format!("{}\n  \n", row);
+        let mut parser = JsonCandleIter::new(Cursor::new(clean.into_bytes()), 1.0);
+        assert!(parser.by_ref().all(|r| r.is_ok()));
+        assert!(parser.finish().is_ok());
+
+        let truncated = format!("{}\n{}", row, &row[..30]);
+        let mut parser = JsonCandleIter::new(Cursor::new(truncated.into_bytes()), 1.0);
+        assert_eq!(parser.by_ref().filter(|r| r.is_ok()).count(), 1);
+        assert!(matches!(parser.finish(), Err(ParseError::TruncatedRecord(_))));
+
+        let junk = format!("{}\n<html>", row);
+        let mut parser = JsonCandleIter::new(Cursor::new(junk.into_bytes()), 1.0);
+        assert_eq!(parser.by_ref().filter(|r| r.is_ok()).count(), 1);
+        assert!(matches!(parser.finish(), Err(ParseError::TrailingData(_))));
+    }
+
+    #[test]
+    fn test_json_array_and_ndjson() {
+        let rows = [
+            r#"{"ts":1609459200000,"o":42000,"h":42500,"l":41500,"c":42200,"v":1500,"note":"a, [b]"}"#,
+            r#"{"ts":1609459260000,"o":42200,"h":42800,"l":42100,"c":42700,"v":2000,"tags":[1,2]}"#,
+        ];
+        let parse = |input: String| {
+            let mut parser = JsonCandleIter::new(Cursor::new(input.into_bytes()), 1.0);
+            let candles: Vec<Candle> = parser.by_ref().map(|r| r.unwrap()).collect();
+            parser.finish().unwrap();
+            candles
+        };
+
+        let ndjson = parse(format!("{}\n{}\n", rows[0], rows[1]));
+        let array = parse(format!("\n  [\n  {},\n  {}\n]\n", rows[0], rows[1]));
+        assert_eq!(ndjson.len(), 2);
+        assert_eq!(array, ndjson);
+        assert_eq!(array[1].close_tick, 42700);
+        assert!(parse("[]".to_string()).is_empty());
+
+        let unclosed = format!("[{},{}", rows[0], rows[1]);
+        let mut parser = JsonCandleIter::new(Cursor::new(unclosed.into_bytes()), 1.0);
+        assert_eq!(parser.by_ref().filter(|r| r.is_ok()).count(), 2);
+        assert!(matches!(parser.finish(), Err(ParseError::TruncatedRecord(_))));
+    }
+
+    /// Timing comparison on a wide file; run with `cargo test -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_close_only_vs_full_parse() {
+        use std::time::Instant;
+
+        let extra_cols = 20;
+        let rows = 200_000;
+        let mut csv_data = String::from("timestamp,open,high,low,close,volume");
+        for i in 0..extra_cols {
+            csv_data.push_str(&format!(",extra_{}", i));
+        }
+        csv_data.push('\n');
+        for i in 0..rows {
+            csv_data.push_str(&format!("{},42000.5,42500.0,41500.0,42200.0,1500.5", 1609459200000i64 + i * 60000));
+            for _ in 0..extra_cols {
+                csv_data.push_str(",123.456");
+            }
+            csv_data.push('\n');
+        }
+
+        let start = Instant::now();
+        let full = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5).unwrap().count();
+        let full_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let close_only = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5)
+            .unwrap()
+            .close_only()
+            .count();
+        let close_elapsed = start.elapsed();
+
+        assert_eq!(full, close_only);
+        println!(
+            "full parse: {:?}, close-only: {:?} ({:.2}x)",
+            full_elapsed,
+            close_elapsed,
+            full_elapsed.as_secs_f64() / close_elapsed.as_secs_f64()
+        );
+    }
+
+    const ZERO_VOLUME_CSV: &str = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42200,42200,42200,0
+1609459320000,42200,42800,42100,42700,2000
+";
+
+    #[test]
+    fn test_zero_volume_accept() {
+        let cursor = Cursor::new(ZERO_VOLUME_CSV.as_bytes());
+        let mut parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let candles: Vec<Candle> = parser.by_ref().map(|c| c.unwrap()).collect();
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles[1].volume_scaled, 0);
+        assert_eq!(parser.halted_count(), 0);
+    }
+
+    #[test]
+    fn test_zero_volume_reject() {
+        let cursor = Cursor::new(ZERO_VOLUME_CSV.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0)
+            .unwrap()
+            .treat_zero_volume(ZeroVolumePolicy::Reject);
+
+        let results: Vec<_> = parser.collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(ParseError::InvalidCandle(_))));
+        assert!(results[2].is_ok());
+    }
+
+    #[test]
+    fn test_zero_volume_mark_halt() {
+        let json_data = r#"{"ts":1609459200000,"o":100,"h":101,"l":99,"c":100,"v":5}
+{"ts":1609459260000,"o":100,"h":100,"l":100,"c":100,"v":0}
+{"ts":1609459320000,"o":100,"h":102,"l":100,"c":101,"v":7}"#;
+
+        let mut parser = JsonCandleIter::new(Cursor::new(json_data.as_bytes()), 1.0)
+            .treat_zero_volume(ZeroVolumePolicy::MarkHalt);
+
+        let mut flags = Vec::new();
+        while let Some(candle) = parser.next() {
+            candle.unwrap();
+            flags.push(parser.last_halted());
+        }
+
+        assert_eq!(flags, vec![false, true, false]);
+        assert_eq!(parser.halted_count(), 1);
+    }
+
+    #[test]
+    fn test_missing_trade_count_is_unknown_not_zero() {
+        let without_column = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+";
+        let with_zero = "\
+timestamp,open,high,low,close,volume,trades
+1609459200000,42000,42500,41500,42200,0,0
+";
+
+        let unknown = CsvCandleIter::new(Cursor::new(without_column.as_bytes()), 1.0)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(unknown.trade_count, TRADE_COUNT_UNKNOWN);
+        assert_eq!(unknown.known_trade_count(), None);
+
+        let zero = CsvCandleIter::new(Cursor::new(with_zero.as_bytes()), 1.0)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(zero.known_trade_count(), Some(0));
+
+        let json = r#"{"ts":1609459200000,"o":100,"h":101,"l":99,"c":100,"v":5}"#;
+        let from_json = JsonCandleIter::new(Cursor::new(json.as_bytes()), 1.0).next().unwrap().unwrap();
+        assert_eq!(from_json.known_trade_count(), None);
+    }
 }
@@ -0,0 +1,49 @@
+//! Ordered record of the calls that drove an engine
+//!
+//! With [`crate::Engine::enable_event_log`] on, every accepted tick, order,
+//! cancel, clock advance, funding payment and finalize is appended as an
+//! [`EngineCommand`]. Since the engine is deterministic, feeding the log to
+//! [`crate::Engine::replay_log`] on a fresh engine with the same config
+//! rebuilds the same state, which makes a divergent backtest reproducible
+//! and lets a failing case be minimized by deleting commands.
+//!
+//! Calls that fail (rejected orders, unknown order ids) change nothing and
+//! are not recorded.
+
+use crate::{OrderType, Side, TimeInForce};
+
+/// One call into the engine, with the arguments it was given
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineCommand {
+    /// A trade, as stepped (including synthetic ticks from candles and
+    /// batches), with quantity scaled by 1e6
+    Tick {
+        ts_ms: i64,
+        price_tick: i64,
+        qty_scaled: i64,
+        side: Side,
+    },
+    /// An order as submitted, before quantization; its id is the one the
+    /// engine assigned, which a replay reproduces
+    PlaceOrder {
+        order_id: u64,
+        order_type: OrderType,
+        side: Side,
+        qty: f64,
+        price: f64,
+        client_tag: u64,
+        time_in_force: TimeInForce,
+        reduce_only: bool,
+    },
+    CancelOrder { order_id: u64 },
+    AdvanceClock { ts_ms: i64 },
+    /// A manual [`crate::Engine::apply_funding`]; scheduled payments are
+    /// re-applied by the replaying engine's own schedule
+    ApplyFunding { rate: f64 },
+    /// A [`crate::Engine::mark_to`], as the quantized mark price
+    MarkTo { price_tick: i64 },
+    Finalize,
+    /// The closing fill of a [`crate::Engine::finalize_at`], recorded after
+    /// its `Finalize`
+    ClosePosition { price_tick: i64 },
+}
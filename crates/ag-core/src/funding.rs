@@ -0,0 +1,89 @@
+//! Funding payments for perpetual futures
+
+/// One funding payment applied to the account
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FundingEvent {
+    pub ts_ms: i64,
+    /// Funding rate for the period (positive = longs pay shorts)
+    pub rate: f64,
+    /// Mark price the payment was computed at
+    pub mark_price: f64,
+    /// Position at the time of funding, in base units
+    pub position: f64,
+    /// Cash change: `-position * mark_price * rate`
+    pub amount: f64,
+}
+
+/// Funding times and rates, applied by the engine as its clock passes them
+///
+/// Attach with [`crate::Engine::set_funding_schedule`]. A payment scheduled
+/// at `T` is applied before the first tick (or `advance_clock`) with
+/// `ts_ms >= T`, at the last traded price before that tick.
+#[derive(Debug, Clone, Default)]
+pub struct FundingSchedule {
+    /// `(ts_ms, rate)`, sorted by time
+    payments: Vec<(i64, f64)>,
+    /// Index of the next payment not yet applied
+    next: usize,
+}
+
+impl FundingSchedule {
+    /// Schedule from explicit `(ts_ms, rate)` pairs, in any order
+    pub fn new(mut payments: Vec<(i64, f64)>) -> Self {
+        payments.sort_by_key(|&(ts_ms, _)| ts_ms);
+        Self { payments, next: 0 }
+    }
+
+    /// `count` payments at a constant `rate`, every `interval_ms` from `start_ms`
+    pub fn periodic(start_ms: i64, interval_ms: i64, count: usize, rate: f64) -> Self {
+        let payments = (0..count as i64)
+            .map(|i| (start_ms + i * interval_ms, rate))
+            .collect();
+        Self { payments, next: 0 }
+    }
+
+    /// All payments, applied or not, in time order
+    pub fn payments(&self) -> &[(i64, f64)] {
+        &self.payments
+    }
+
+    /// Payments not yet applied
+    pub fn remaining(&self) -> &[(i64, f64)] {
+        &self.payments[self.next..]
+    }
+
+    /// Mark every payment as not yet applied
+    pub fn rewind(&mut self) {
+        self.next = 0;
+    }
+
+    /// Take the next payment due at or before `ts_ms`
+    pub(crate) fn pop_due(&mut self, ts_ms: i64) -> Option<(i64, f64)> {
+        let payment = *self.payments.get(self.next)?;
+        if payment.0 > ts_ms {
+            return None;
+        }
+        self.next += 1;
+        Some(payment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_pops_due_payments_in_order() {
+        let mut schedule = FundingSchedule::new(vec![(300, 0.2), (100, 0.1), (200, -0.1)]);
+
+        assert_eq!(schedule.pop_due(50), None);
+        assert_eq!(schedule.pop_due(250), Some((100, 0.1)));
+        assert_eq!(schedule.pop_due(250), Some((200, -0.1)));
+        assert_eq!(schedule.pop_due(250), None);
+        assert_eq!(schedule.remaining(), &[(300, 0.2)]);
+
+        schedule.rewind();
+        assert_eq!(schedule.remaining().len(), 3);
+        assert_eq!(FundingSchedule::periodic(0, 10, 3, 0.5).payments(), &[(0, 0.5), (10, 0.5), (20, 0.5)]);
+    }
+}
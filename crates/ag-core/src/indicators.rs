@@ -0,0 +1,249 @@
+//! Streaming technical indicators computed in tick space
+//!
+//! Indicators consume candles one at a time and keep only the state needed
+//! for their window. Values stay in integer ticks so results are
+//! deterministic across platforms.
+
+use crate::candle::Candle;
+use std::collections::VecDeque;
+
+/// Rolling N-bar highest high and lowest low
+///
+/// Uses monotonic deques, so each update is O(1) amortized regardless of the
+/// period.
+#[derive(Debug, Clone)]
+pub struct DonchianChannel {
+    period: usize,
+    /// Bars seen so far, used as the index of the next bar
+    count: usize,
+    /// `(index, high_tick)` with strictly decreasing highs
+    highs: VecDeque<(usize, i64)>,
+    /// `(index, low_tick)` with strictly increasing lows
+    lows: VecDeque<(usize, i64)>,
+}
+
+impl DonchianChannel {
+    /// # Panics
+    /// If `period` is zero.
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "Donchian period must be positive");
+        Self {
+            period,
+            count: 0,
+            highs: VecDeque::with_capacity(period),
+            lows: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Add a bar, returning `(highest high_tick, lowest low_tick)` over the
+    /// last `period` bars, or `None` until `period` bars have been seen
+    pub fn update(&mut self, candle: &Candle) -> Option<(i64, i64)> {
+        self.update_ticks(candle.high_tick, candle.low_tick)
+    }
+
+    /// Like [`update`](Self::update), from a bar's high and low ticks
+    pub fn update_ticks(&mut self, high_tick: i64, low_tick: i64) -> Option<(i64, i64)> {
+        let index = self.count;
+        self.count += 1;
+
+        while self.highs.back().is_some_and(|&(_, high)| high <= high_tick) {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((index, high_tick));
+
+        while self.lows.back().is_some_and(|&(_, low)| low >= low_tick) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((index, low_tick));
+
+        // Drop extremes that have left the window
+        let oldest = (index + 1).saturating_sub(self.period);
+        while self.highs.front().is_some_and(|&(i, _)| i < oldest) {
+            self.highs.pop_front();
+        }
+        while self.lows.front().is_some_and(|&(i, _)| i < oldest) {
+            self.lows.pop_front();
+        }
+
+        self.value()
+    }
+
+    /// Current channel, or `None` until the window is full
+    pub fn value(&self) -> Option<(i64, i64)> {
+        if self.count < self.period {
+            return None;
+        }
+        Some((self.highs.front()?.1, self.lows.front()?.1))
+    }
+
+    /// Forget all bars, as if newly created
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.highs.clear();
+        self.lows.clear();
+    }
+}
+
+/// An indicator held by an [`IndicatorSet`]
+#[derive(Debug, Clone)]
+pub enum Indicator {
+    Donchian(DonchianChannel),
+}
+
+impl Indicator {
+    /// Add a bar
+    pub fn update(&mut self, candle: &Candle) {
+        match self {
+            Indicator::Donchian(channel) => {
+                channel.update(candle);
+            }
+        }
+    }
+
+    /// Forget all bars, as if newly created
+    pub fn reset(&mut self) {
+        match self {
+            Indicator::Donchian(channel) => channel.reset(),
+        }
+    }
+}
+
+impl From<DonchianChannel> for Indicator {
+    fn from(channel: DonchianChannel) -> Self {
+        Indicator::Donchian(channel)
+    }
+}
+
+/// Indicators updated and reset together
+///
+/// Register with [`crate::Engine::add_indicator`] so that every candle the
+/// engine steps updates them and `reset` clears them. Without that, a sweep
+/// that resets between runs would start each run on the previous run's
+/// windows instead of warming up from scratch.
+#[derive(Debug, Clone, Default)]
+pub struct IndicatorSet {
+    indicators: Vec<Indicator>,
+}
+
+impl IndicatorSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an indicator, returning its index in the set
+    pub fn add(&mut self, indicator: impl Into<Indicator>) -> usize {
+        self.indicators.push(indicator.into());
+        self.indicators.len() - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Indicator> {
+        self.indicators.get(index)
+    }
+
+    /// The Donchian channel at `index`, if that is what it holds
+    pub fn donchian(&self, index: usize) -> Option<&DonchianChannel> {
+        match self.indicators.get(index)? {
+            Indicator::Donchian(channel) => Some(channel),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.indicators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indicators.is_empty()
+    }
+
+    /// Add a bar to every indicator
+    pub fn update(&mut self, candle: &Candle) {
+        for indicator in &mut self.indicators {
+            indicator.update(candle);
+        }
+    }
+
+    /// Reset every indicator, keeping them registered
+    pub fn reset(&mut self) {
+        for indicator in &mut self.indicators {
+            indicator.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(high_tick: i64, low_tick: i64) -> Candle {
+        Candle {
+            ts_open: 1,
+            ts_close: 2,
+            open_tick: low_tick,
+            high_tick,
+            low_tick,
+            close_tick: high_tick,
+            volume_scaled: 1,
+            trade_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_donchian_matches_naive_on_random_data() {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut mid = 10_000i64;
+        let bars: Vec<Candle> = (0..2_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                mid += (state % 21) as i64 - 10;
+                let spread = ((state >> 8) % 15) as i64;
+                bar(mid + spread, mid - spread)
+            })
+            .collect();
+
+        for period in [1, 2, 3, 7, 20, 64] {
+            let mut channel = DonchianChannel::new(period);
+
+            for (i, candle) in bars.iter().enumerate() {
+                let got = channel.update(candle);
+                let expected = (i + 1 >= period).then(|| {
+                    let window = &bars[i + 1 - period..=i];
+                    (
+                        window.iter().map(|c| c.high_tick).max().unwrap(),
+                        window.iter().map(|c| c.low_tick).min().unwrap(),
+                    )
+                });
+                assert_eq!(got, expected, "period {} bar {}", period, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_donchian_three_bar_window() {
+        let mut channel = DonchianChannel::new(3);
+        assert_eq!(channel.update(&bar(120, 80)), None);
+        assert_eq!(channel.update(&bar(110, 95)), None);
+        assert_eq!(channel.update(&bar(105, 90)), Some((120, 80)));
+        // The 120 high and 80 low both leave the window here
+        assert_eq!(channel.update(&bar(100, 97)), Some((110, 90)));
+        assert_eq!(channel.update(&bar(108, 99)), Some((108, 90)));
+        assert_eq!(channel.update(&bar(101, 98)), Some((108, 97)));
+    }
+
+    #[test]
+    fn test_donchian_reset() {
+        let mut channel = DonchianChannel::new(2);
+        channel.update(&bar(110, 90));
+        assert_eq!(channel.update(&bar(105, 95)), Some((110, 90)));
+
+        channel.reset();
+        assert_eq!(channel.value(), None);
+        assert_eq!(channel.update(&bar(101, 99)), None);
+        assert_eq!(channel.update(&bar(102, 98)), Some((102, 98)));
+    }
+}
@@ -1,271 +1,5152 @@
 //! Safe Rust wrapper around the C engine with Python bindings
+//!
+//! With the `tracing` feature, the engine emits `tracing` events that any
+//! subscriber can collect: engine creation (`info`), tick batches (`debug`),
+//! order placements (`trace`) and order rejections (`warn`, with a `reason`
+//! field). Per-tick and per-order paths only log at `trace`.
+
+/// Emit a `tracing` event at `$level`; compiles to nothing without the
+/// `tracing` feature
+#[cfg(feature = "tracing")]
+macro_rules! log_event {
+    ($level:ident, $($arg:tt)+) => {
+        tracing::$level!(target: "ag_core", $($arg)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_event {
+    ($level:ident, $($arg:tt)+) => {};
+}
 
 pub mod candle;
+pub mod candle_columns;
+pub mod candle_file;
 pub mod candle_parser;
+pub mod event_log;
+pub mod funding;
+pub mod indicators;
 pub mod market_event;
+pub mod metrics;
+pub mod queue;
+pub mod session;
+pub mod source;
+pub mod sweep;
+pub mod testing;
+pub mod trade_parser;
 
 use ag_core_sys::*;
+use candle::{Candle, RoundingMode};
+use candle_parser::ParseError;
+use event_log::EngineCommand;
+use funding::{FundingEvent, FundingSchedule};
+use indicators::{Indicator, IndicatorSet};
+use market_event::BarToTickPolicy;
+use metrics::DrawdownTracker;
+use queue::QueueTracker;
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::os::raw::c_void;
 use std::ptr;
+use thiserror::Error;
 
-// ========== Safe Rust Wrapper ==========
+// ========== Errors ==========
 
-/// Safe wrapper around the C engine
-pub struct Engine {
-    handle: *mut engine_handle_t,
-    tick_size: f64,
+/// Errors returned by the safe engine wrapper
+#[derive(Debug, Error)]
+pub enum EngineError {
+    #[error("Failed to create engine")]
+    CreateFailed,
+
+    #[error("Invalid side: {0}")]
+    InvalidSide(String),
+
+    #[error("Invalid side value: {0} (must be 0 or 1)")]
+    InvalidSideValue(u8),
+
+    #[error("Invalid order type: {0}")]
+    InvalidOrderType(String),
+
+    #[error("Order {field} must be finite, got {value}")]
+    NonFiniteOrder { field: &'static str, value: f64 },
+
+    #[error("Vector length mismatch: timestamps={timestamps}, price_ticks={price_ticks}, qtys={qtys}, sides={sides}")]
+    LengthMismatch {
+        timestamps: usize,
+        price_ticks: usize,
+        qtys: usize,
+        sides: usize,
+    },
+
+    #[error("Engine step failed with code: {0}")]
+    StepFailed(i32),
+
+    #[error("Engine step failed at tick {index} with code: {code}")]
+    BatchStepFailed { index: usize, code: i32 },
+
+    #[error("Place order failed with code: {0}")]
+    PlaceOrderFailed(i32),
+
+    #[error("Order rejected: {0}")]
+    OrderRejected(RejectReason),
+
+    #[error("Order not found: {0}")]
+    OrderNotFound(u64),
+
+    #[error("Clock advance failed with code: {0}")]
+    AdvanceClockFailed(i32),
+
+    #[error("Invalid initial position: {0}")]
+    InvalidInitialPosition(String),
+
+    #[error("Invalid {param}: {reason}")]
+    InvalidConfig { param: &'static str, reason: String },
+
+    #[error("Cannot change tick_size from {old} to {new} while holding a position")]
+    TickSizeChangeWithPosition { old: f64, new: f64 },
+
+    #[error("Limit price {price} is outside the {band_bps} bps band around last trade {last_price}")]
+    PriceOutsideBand { price: f64, last_price: f64, band_bps: f64 },
+
+    #[error("Order {field} {value} is below the minimum {minimum}")]
+    BelowMinimum { field: &'static str, value: f64, minimum: f64 },
+
+    #[error("Reduce-only order has no opposing position to reduce")]
+    ReduceOnlyNoPosition,
+
+    #[error("Funding failed with code: {0}")]
+    FundingFailed(i32),
+
+    #[error("Invalid funding rate: {0}")]
+    InvalidFundingRate(f64),
+
+    #[error("Invalid mark price: {0}")]
+    InvalidMarkPrice(f64),
+
+    #[error("Marking failed with code: {0}")]
+    MarkFailed(i32),
+
+    #[error("Closing the position failed with code: {0}")]
+    ClosePositionFailed(i32),
+
+    #[error("C engine ABI mismatch: {0}")]
+    AbiMismatch(String),
+
+    #[error("Tick size mismatch: engine uses {engine}, parser uses {parser}")]
+    TickSizeMismatch { engine: f64, parser: f64 },
+
+    #[error("Ingestion error: {0}")]
+    Ingestion(#[from] ParseError),
 }
 
-impl Engine {
-    pub fn new(
-        initial_cash: f64,
-        maker_fee_bps: f64,
-        taker_fee_bps: f64,
-        spread_bps: f64,
-        tick_size: f64,
-    ) -> Result<Self, String> {
-        let config = config_t {
-            maker_fee_bps,
-            taker_fee_bps,
-            spread_bps,
-            initial_cash,
-            tick_size,
-        };
+impl EngineError {
+    /// Why the order was refused, if this error is an order rejection
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        match self {
+            EngineError::OrderRejected(reason) => Some(*reason),
+            EngineError::NonFiniteOrder { .. } => Some(RejectReason::InvalidOrder),
+            EngineError::PriceOutsideBand { .. } => Some(RejectReason::PriceOutsideBand),
+            EngineError::BelowMinimum { .. } => Some(RejectReason::BelowMinimum),
+            EngineError::ReduceOnlyNoPosition => Some(RejectReason::ReduceOnlyNoPosition),
+            _ => None,
+        }
+    }
+}
+
+impl From<EngineError> for PyErr {
+    fn from(err: EngineError) -> PyErr {
+        match err {
+            EngineError::InvalidConfig { .. } => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+            _ => pyo3::exceptions::PyRuntimeError::new_err(err.to_string()),
+        }
+    }
+}
 
-        let handle = unsafe { engine_new(&config) };
+// ========== Safe Rust Wrapper ==========
 
-        if handle.is_null() {
-            return Err("Failed to create engine".to_string());
+/// When a resting limit order fills against a trade at its own price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitFillModel {
+    /// A trade at or through the limit price fills the order
+    #[default]
+    OnTouch,
+    /// Only a trade strictly through the limit price fills the order, as
+    /// if the queue at that level were never reached
+    OnThrough,
+}
+
+impl LimitFillModel {
+    /// Parse a model name ("touch" or "through"), as accepted from Python
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "touch" => Some(LimitFillModel::OnTouch),
+            "through" => Some(LimitFillModel::OnThrough),
+            _ => None,
         }
+    }
 
-        Ok(Engine { handle, tick_size })
+    /// Name accepted by [`from_name`](Self::from_name)
+    pub fn name(self) -> &'static str {
+        match self {
+            LimitFillModel::OnTouch => "touch",
+            LimitFillModel::OnThrough => "through",
+        }
     }
 
-    pub fn reset(&mut self) {
-        unsafe { engine_reset(self.handle) }
+    fn to_ffi(self) -> limit_fill_model_t {
+        match self {
+            LimitFillModel::OnTouch => limit_fill_model_t::LIMIT_FILL_ON_TOUCH,
+            LimitFillModel::OnThrough => limit_fill_model_t::LIMIT_FILL_ON_THROUGH,
+        }
     }
+}
 
-    pub fn step_tick(&mut self, ts_ms: i64, price_tick_i64: i64, qty: f64, side: &str) -> Result<(), String> {
-        let side_enum = match side.to_uppercase().as_str() {
-            "BUY" => side_t::SIDE_BUY,
-            "SELL" => side_t::SIDE_SELL,
-            _ => return Err(format!("Invalid side: {}", side)),
-        };
+/// Engine configuration (fees and spread in basis points)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EngineConfig {
+    pub initial_cash: f64,
+    pub maker_fee_bps: f64,
+    pub taker_fee_bps: f64,
+    pub spread_bps: f64,
+    pub tick_size: f64,
+    /// Order latency: an order placed at engine time T can only fill on ticks
+    /// with `ts_ms >= T + latency_ms`. Handled on the Rust side.
+    pub latency_ms: i64,
+    /// Position held at start (and after every reset), in base units.
+    /// Positive = long, negative = short.
+    pub initial_position: f64,
+    /// Average entry price of `initial_position`, in currency units.
+    /// `initial_cash` is the balance after that position was opened, and
+    /// must cover the cost of a long.
+    pub initial_entry_price: f64,
+    /// How order prices are snapped to the tick grid
+    pub rounding: RoundingMode,
+    /// Reject limit orders priced more than this many bps from the last
+    /// trade, like an exchange price band. `None` disables the check.
+    pub price_band_bps: Option<f64>,
+    /// Borrow cost of a short position, in bps of its notional per day.
+    /// Accrued from cash as time passes (ticks and `advance_clock`).
+    pub borrow_fee_bps_per_day: f64,
+    /// Reject orders smaller than this quantity, in base units. `None`
+    /// disables the check.
+    pub min_order_qty: Option<f64>,
+    /// Reject orders whose notional (`qty * |price|`) is below this. Market
+    /// orders are valued at the last trade and skip the check before the
+    /// first tick. `None` disables the check.
+    pub min_order_notional: Option<f64>,
+    /// Whether a trade at exactly a resting limit's price fills it
+    pub limit_fill_model: LimitFillModel,
+    /// Accept negative (rebate) fees down to `-max_rebate_bps`. Off by
+    /// default, so a negative fee is rejected as a likely sign error.
+    pub allow_rebates: bool,
+    /// Highest maker or taker fee [`EngineConfig::validate`] accepts
+    pub max_fee_bps: f64,
+    /// Largest rebate (negative fee) accepted with `allow_rebates`
+    pub max_rebate_bps: f64,
+}
 
-        let tick = tick_event_t {
-            ts_ms,
-            price_tick: price_tick_i64,
-            qty: (qty * 1000000.0) as i64, // Convert to integer representation
-            side: side_enum,
-        };
+/// Default `EngineConfig::max_fee_bps` (10%)
+pub const MAX_FEE_BPS: f64 = 1_000.0;
 
-        let result = unsafe { engine_step_tick(self.handle, &tick) };
+/// Default `EngineConfig::max_rebate_bps` (1%)
+pub const MAX_REBATE_BPS: f64 = 100.0;
 
-        if result < 0 {
-            return Err(format!("Engine step failed with code: {}", result));
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            initial_cash: 100_000.0,
+            maker_fee_bps: 1.0,
+            taker_fee_bps: 2.0,
+            spread_bps: 2.0,
+            tick_size: 0.01,
+            latency_ms: 0,
+            initial_position: 0.0,
+            initial_entry_price: 0.0,
+            rounding: RoundingMode::Nearest,
+            price_band_bps: None,
+            borrow_fee_bps_per_day: 0.0,
+            min_order_qty: None,
+            min_order_notional: None,
+            limit_fill_model: LimitFillModel::OnTouch,
+            allow_rebates: false,
+            max_fee_bps: MAX_FEE_BPS,
+            max_rebate_bps: MAX_REBATE_BPS,
         }
-
-        Ok(())
     }
+}
 
-    /// Process a batch of ticks efficiently - accepts integer sides (0=BUY, 1=SELL)
-    pub fn process_tick_batch(
-        &mut self,
-        timestamps: Vec<i64>,
-        price_ticks: Vec<i64>,
-        qtys: Vec<f64>,
-        sides: Vec<u8>,
-    ) -> Result<(), String> {
-        // Validate all vectors have same length
-        let n = timestamps.len();
-        if price_ticks.len() != n || qtys.len() != n || sides.len() != n {
-            return Err(format!(
-                "Vector length mismatch: timestamps={}, price_ticks={}, qtys={}, sides={}",
-                n, price_ticks.len(), qtys.len(), sides.len()
-            ));
+impl EngineConfig {
+    /// Check tick size, fees, spread and cash are in a sane range
+    ///
+    /// `tick_size` must be finite and positive. Fees must be finite and at
+    /// most `max_fee_bps`, and not negative unless `allow_rebates` is set,
+    /// in which case they may go down to `-max_rebate_bps`. `spread_bps`,
+    /// `initial_cash` and the fee limits themselves must be finite and
+    /// non-negative. Run by [`Engine::with_config`] and
+    /// [`Engine::reset_with_config`]; the error names the offending field.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        if !(self.tick_size.is_finite() && self.tick_size > 0.0) {
+            return Err(EngineError::InvalidConfig {
+                param: "tick_size",
+                reason: format!("must be finite and positive, got {}", self.tick_size),
+            });
         }
 
-        // Process all ticks in the batch
-        for i in 0..n {
-            let side_enum = match sides[i] {
-                0 => side_t::SIDE_BUY,
-                1 => side_t::SIDE_SELL,
-                _ => return Err(format!("Invalid side value: {} (must be 0 or 1)", sides[i])),
-            };
+        for (param, value) in [("max_fee_bps", self.max_fee_bps), ("max_rebate_bps", self.max_rebate_bps)] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(EngineError::InvalidConfig {
+                    param,
+                    reason: format!("must be finite and non-negative, got {}", value),
+                });
+            }
+        }
 
-            let tick = tick_event_t {
-                ts_ms: timestamps[i],
-                price_tick: price_ticks[i],
-                qty: (qtys[i] * 1000000.0) as i64, // Convert to integer representation
-                side: side_enum,
+        let min_fee_bps = if self.allow_rebates { -self.max_rebate_bps } else { 0.0 };
+        for (param, fee_bps) in [("maker_fee_bps", self.maker_fee_bps), ("taker_fee_bps", self.taker_fee_bps)] {
+            let reason = if !fee_bps.is_finite() {
+                format!("must be finite, got {}", fee_bps)
+            } else if fee_bps > self.max_fee_bps {
+                format!("{} exceeds the maximum of {} bps", fee_bps, self.max_fee_bps)
+            } else if fee_bps < 0.0 && !self.allow_rebates {
+                format!("{} is negative; set allow_rebates for a rebate", fee_bps)
+            } else if fee_bps < min_fee_bps {
+                format!("rebate {} exceeds the maximum of {} bps", -fee_bps, self.max_rebate_bps)
+            } else {
+                continue;
             };
+            return Err(EngineError::InvalidConfig { param, reason });
+        }
 
-            let result = unsafe { engine_step_tick(self.handle, &tick) };
-
-            if result < 0 {
-                return Err(format!("Engine step failed at tick {} with code: {}", i, result));
+        for (param, value) in [("spread_bps", self.spread_bps), ("initial_cash", self.initial_cash)] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(EngineError::InvalidConfig {
+                    param,
+                    reason: format!("must be finite and non-negative, got {}", value),
+                });
             }
         }
 
         Ok(())
     }
 
-    pub fn place_order(
-        &mut self,
-        order_type: &str,
-        side: &str,
-        qty: f64,
-        price: f64,
-    ) -> Result<(), String> {
-        let type_enum = match order_type.to_uppercase().as_str() {
-            "MARKET" => order_type_t::ORDER_TYPE_MARKET,
-            "LIMIT" => order_type_t::ORDER_TYPE_LIMIT,
-            _ => return Err(format!("Invalid order type: {}", order_type)),
-        };
+    fn to_ffi(self) -> config_t {
+        config_t {
+            maker_fee_bps: self.maker_fee_bps,
+            taker_fee_bps: self.taker_fee_bps,
+            spread_bps: self.spread_bps,
+            initial_cash: self.initial_cash,
+            tick_size: self.tick_size,
+            borrow_fee_bps_per_day: self.borrow_fee_bps_per_day,
+            limit_fill_model: self.limit_fill_model.to_ffi(),
+        }
+    }
 
-        let side_enum = match side.to_uppercase().as_str() {
-            "BUY" => side_t::SIDE_BUY,
-            "SELL" => side_t::SIDE_SELL,
-            _ => return Err(format!("Invalid side: {}", side)),
-        };
+    /// Validate the initial position and encode it for the C engine
+    ///
+    /// Returns the scaled quantity and the entry price in ticks.
+    fn initial_position_ffi(self) -> Result<(i64, f64), EngineError> {
+        if self.initial_position == 0.0 {
+            return Ok((0, 0.0));
+        }
 
-        let price_tick = (price / self.tick_size).round() as i64;
-        let qty_i64 = (qty * 1000000.0) as i64;
+        if !self.initial_position.is_finite() {
+            return Err(EngineError::InvalidInitialPosition(format!(
+                "quantity must be finite, got {}",
+                self.initial_position
+            )));
+        }
 
-        let order = order_t {
-            order_id: 0, // Auto-assigned
-            type_: type_enum,
-            side: side_enum,
-            qty: qty_i64,
-            price_tick,
-        };
+        if !self.initial_cash.is_finite() {
+            return Err(EngineError::InvalidInitialPosition(format!(
+                "initial_cash must be finite, got {}",
+                self.initial_cash
+            )));
+        }
 
-        let result = unsafe { engine_place_order(self.handle, &order) };
+        // Negative prices are valid; zero is rejected as a likely unset price
+        if !(self.initial_entry_price.is_finite() && self.initial_entry_price != 0.0) {
+            return Err(EngineError::InvalidInitialPosition(format!(
+                "entry price must be finite and non-zero, got {}",
+                self.initial_entry_price
+            )));
+        }
 
-        if result < 0 {
-            return Err(format!("Place order failed with code: {}", result));
+        // Sanity check against unit mistakes (scaled qty, price in ticks):
+        // a long may not cost more than the cash left beside it
+        let cost = self.initial_position * self.initial_entry_price;
+        if self.initial_position > 0.0 && cost > self.initial_cash {
+            return Err(EngineError::InvalidInitialPosition(format!(
+                "long costs {} at entry, more than initial_cash {}",
+                cost, self.initial_cash
+            )));
         }
 
-        Ok(())
+        let position = (self.initial_position * 1000000.0).round() as i64;
+        if position == 0 {
+            return Err(EngineError::InvalidInitialPosition(format!(
+                "quantity {} rounds to zero",
+                self.initial_position
+            )));
+        }
+
+        Ok((position, self.initial_entry_price / self.tick_size))
+    }
+}
+
+/// Builder for [`Engine`], starting from `EngineConfig::default()`
+#[derive(Debug, Clone, Default)]
+pub struct EngineBuilder {
+    config: EngineConfig,
+}
+
+impl EngineBuilder {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn get_snapshot(&self) -> Snapshot {
-        let snap = unsafe { engine_get_snapshot(self.handle) };
+    pub fn initial_cash(mut self, initial_cash: f64) -> Self {
+        self.config.initial_cash = initial_cash;
+        self
+    }
 
-        Snapshot {
-            ts_ms: snap.ts_ms,
-            cash: snap.cash,
-            position: snap.position as f64 / 1000000.0, // Convert back from integer
-            avg_entry_price: snap.avg_entry_price,
-            realized_pnl: snap.realized_pnl,
-            unrealized_pnl: snap.unrealized_pnl,
-            equity: snap.equity,
+    pub fn maker_fee_bps(mut self, maker_fee_bps: f64) -> Self {
+        self.config.maker_fee_bps = maker_fee_bps;
+        self
+    }
+
+    pub fn taker_fee_bps(mut self, taker_fee_bps: f64) -> Self {
+        self.config.taker_fee_bps = taker_fee_bps;
+        self
+    }
+
+    pub fn spread_bps(mut self, spread_bps: f64) -> Self {
+        self.config.spread_bps = spread_bps;
+        self
+    }
+
+    pub fn tick_size(mut self, tick_size: f64) -> Self {
+        self.config.tick_size = tick_size;
+        self
+    }
+
+    pub fn latency_ms(mut self, latency_ms: i64) -> Self {
+        self.config.latency_ms = latency_ms;
+        self
+    }
+
+    /// Start with an existing position instead of flat
+    ///
+    /// `qty` is signed (negative = short) and `avg_entry_price` is in currency
+    /// units. The position is marked at its entry price until the first tick.
+    ///
+    /// `initial_cash` is the balance left after opening the position, and a
+    /// long costing more than that at entry (`qty * avg_entry_price >
+    /// initial_cash`) is rejected as a likely units mistake. Shorts are not
+    /// checked.
+    pub fn initial_position(mut self, qty: f64, avg_entry_price: f64) -> Self {
+        self.config.initial_position = qty;
+        self.config.initial_entry_price = avg_entry_price;
+        self
+    }
+
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.config.rounding = rounding;
+        self
+    }
+
+    pub fn price_band_bps(mut self, band_bps: f64) -> Self {
+        self.config.price_band_bps = Some(band_bps);
+        self
+    }
+
+    pub fn borrow_fee_bps_per_day(mut self, fee_bps: f64) -> Self {
+        self.config.borrow_fee_bps_per_day = fee_bps;
+        self
+    }
+
+    pub fn min_order_qty(mut self, min_qty: f64) -> Self {
+        self.config.min_order_qty = Some(min_qty);
+        self
+    }
+
+    pub fn min_order_notional(mut self, min_notional: f64) -> Self {
+        self.config.min_order_notional = Some(min_notional);
+        self
+    }
+
+    pub fn limit_fill_model(mut self, model: LimitFillModel) -> Self {
+        self.config.limit_fill_model = model;
+        self
+    }
+
+    pub fn allow_rebates(mut self, allow: bool) -> Self {
+        self.config.allow_rebates = allow;
+        self
+    }
+
+    /// Highest fee accepted, in bps (default [`MAX_FEE_BPS`])
+    pub fn max_fee_bps(mut self, max_fee_bps: f64) -> Self {
+        self.config.max_fee_bps = max_fee_bps;
+        self
+    }
+
+    /// Largest rebate accepted with `allow_rebates`, in bps (default
+    /// [`MAX_REBATE_BPS`])
+    pub fn max_rebate_bps(mut self, max_rebate_bps: f64) -> Self {
+        self.config.max_rebate_bps = max_rebate_bps;
+        self
+    }
+
+    pub fn build(self) -> Result<Engine, EngineError> {
+        Engine::with_config(self.config)
+    }
+}
+
+/// Order side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
         }
     }
 }
 
-impl Drop for Engine {
-    fn drop(&mut self) {
-        if !self.handle.is_null() {
-            unsafe { engine_free(self.handle) };
-            self.handle = ptr::null_mut();
+impl From<side_t> for Side {
+    fn from(side: side_t) -> Self {
+        match side {
+            side_t::SIDE_BUY => Side::Buy,
+            side_t::SIDE_SELL => Side::Sell,
         }
     }
 }
 
-// Ensure Engine is Send (safe to move between threads)
-unsafe impl Send for Engine {}
+/// Order type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    Market,
+    Limit,
+}
 
-#[derive(Debug, Clone)]
-pub struct Snapshot {
-    pub ts_ms: i64,
-    pub cash: f64,
-    pub position: f64,
-    pub avg_entry_price: f64,
-    pub realized_pnl: f64,
-    pub unrealized_pnl: f64,
-    pub equity: f64,
+impl OrderType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderType::Market => "MARKET",
+            OrderType::Limit => "LIMIT",
+        }
+    }
 }
 
-// ========== Python Bindings ==========
+impl From<order_type_t> for OrderType {
+    fn from(order_type: order_type_t) -> Self {
+        match order_type {
+            order_type_t::ORDER_TYPE_MARKET => OrderType::Market,
+            order_type_t::ORDER_TYPE_LIMIT => OrderType::Limit,
+        }
+    }
+}
 
-#[pyclass(name = "Engine")]
-struct PyEngine {
-    inner: Engine,
+/// How long an order stays at the matcher
+///
+/// The engine matches only against trades, so "immediately" means the first
+/// tick an order sees once it has waited out `latency_ms`. Fills are never
+/// partial, so a `Fok` order is matched like an `Ioc` one, but a `Fok` limit
+/// is rejected at placement unless it would fill against a trade at the last
+/// price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeInForce {
+    /// Good till cancelled
+    #[default]
+    Gtc,
+    /// Immediate or cancel: matched against one tick, then cancelled
+    Ioc,
+    /// Fill or kill: like `Ioc`, but a limit that does not cross the last
+    /// trade is rejected up front
+    Fok,
+    /// Good till date: cancelled once the clock moves past the given `ts_ms`
+    Gtd(i64),
 }
 
-#[pymethods]
-impl PyEngine {
-    #[new]
-    #[pyo3(signature = (initial_cash=100_000.0, maker_fee=0.0001, taker_fee=0.0002, spread_bps=2.0, tick_size=0.01))]
-    fn new(
-        initial_cash: f64,
-        maker_fee: f64,
-        taker_fee: f64,
-        spread_bps: f64,
-        tick_size: f64,
-    ) -> PyResult<Self> {
-        let maker_fee_bps = maker_fee * 10000.0;
-        let taker_fee_bps = taker_fee * 10000.0;
+impl TimeInForce {
+    /// Parse "GTC", "IOC" or "FOK" (any case), as accepted from Python; a
+    /// good-till-date order is built from its timestamp instead
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "GTC" => Some(TimeInForce::Gtc),
+            "IOC" => Some(TimeInForce::Ioc),
+            "FOK" => Some(TimeInForce::Fok),
+            _ => None,
+        }
+    }
+}
 
-        let engine = Engine::new(
-            initial_cash,
-            maker_fee_bps,
-            taker_fee_bps,
-            spread_bps,
-            tick_size,
-        )
-        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e))?;
+/// What [`Engine::process_tick_batch_with_policy`] does when a tick fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchErrorPolicy {
+    /// Return the error at the first failing tick (ticks before it stay applied)
+    #[default]
+    AbortAtFirst,
+    /// Skip failing ticks, count them in [`BatchResult::skipped`], and go on
+    SkipAndContinue,
+    /// Stop at the first failing tick and report its index in an `Ok` result
+    StopAndReport,
+}
 
-        Ok(PyEngine { inner: engine })
+impl BatchErrorPolicy {
+    /// Parse `"abort"`, `"skip"` or `"stop"` (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "abort" => Some(BatchErrorPolicy::AbortAtFirst),
+            "skip" => Some(BatchErrorPolicy::SkipAndContinue),
+            "stop" => Some(BatchErrorPolicy::StopAndReport),
+            _ => None,
+        }
     }
+}
 
-    fn reset(&mut self) {
-        self.inner.reset();
-    }
+/// Outcome of a tick batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchResult {
+    /// Ticks applied to the engine
+    pub processed: usize,
+    /// Failing ticks skipped under `SkipAndContinue`
+    pub skipped: usize,
+    /// Index of the tick that stopped the batch under `StopAndReport`
+    pub failed_index: Option<usize>,
+    /// Index of the first failing tick under either `SkipAndContinue` or
+    /// `StopAndReport`
+    pub first_error_index: Option<usize>,
+}
 
-    fn step_tick(&mut self, ts_ms: i64, price_tick_i64: i64, qty: f64, side: &str) -> PyResult<()> {
-        self.inner
-            .step_tick(ts_ms, price_tick_i64, qty, side)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e))
-    }
+/// Why an order was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// Non-positive quantity
+    InvalidOrder,
+    /// The C engine's order book already holds `MAX_OPEN_ORDERS` orders
+    BookFull,
+    /// An open order already has the same id
+    DuplicateId,
+    /// A buy opening or adding to a long costs more than the cash not
+    /// already committed to open buy orders
+    InsufficientCash,
+    /// A market buy before any trade or mark, with no price to cost it at
+    NoPrice,
+    /// Limit price outside `EngineConfig::price_band_bps` (checked in Rust)
+    PriceOutsideBand,
+    /// Quantity or notional under `EngineConfig::min_order_qty` /
+    /// `min_order_notional` (checked in Rust)
+    BelowMinimum,
+    /// Reduce-only order while flat or on the same side as the position
+    /// (checked in Rust)
+    ReduceOnlyNoPosition,
+    /// Fill-or-kill limit that would not fill against a trade at the last
+    /// price, or placed before any price (checked in Rust)
+    FokNotMarketable,
+}
 
-    fn step_batch(
-        &mut self,
-        timestamps: Vec<i64>,
-        price_ticks: Vec<i64>,
-        qtys: Vec<f64>,
-        sides: Vec<u8>,
-    ) -> PyResult<()> {
-        self.inner
-            .process_tick_batch(timestamps, price_ticks, qtys, sides)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e))
+impl RejectReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RejectReason::InvalidOrder => "invalid order",
+            RejectReason::BookFull => "order book full",
+            RejectReason::DuplicateId => "duplicate order id",
+            RejectReason::InsufficientCash => "insufficient cash",
+            RejectReason::NoPrice => "no price to cost a market buy",
+            RejectReason::PriceOutsideBand => "price outside band",
+            RejectReason::BelowMinimum => "below minimum order size",
+            RejectReason::ReduceOnlyNoPosition => "no position to reduce",
+            RejectReason::FokNotMarketable => "fill-or-kill limit does not cross the last trade",
+        }
     }
+}
 
-    fn place_order(&mut self, order_type: &str, side: &str, qty: f64, price: f64) -> PyResult<()> {
-        self.inner
-            .place_order(order_type, side, qty, price)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e))
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
+}
 
-    fn get_snapshot(&self) -> PyResult<HashMap<String, f64>> {
-        let snap = self.inner.get_snapshot();
+/// A latency-delayed order the C engine refused when its release time came,
+/// e.g. a market buy that the price moved out of reach of the cash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectedOrder {
+    pub order_id: u64,
+    pub client_tag: u64,
+    /// Time of the tick or clock advance that released the order
+    pub ts_ms: i64,
+    pub reason: RejectReason,
+}
 
-        let mut result = HashMap::new();
-        result.insert("cash".to_string(), snap.cash);
-        result.insert("position".to_string(), snap.position);
-        result.insert("avg_entry_price".to_string(), snap.avg_entry_price);
-        result.insert("realized_pnl".to_string(), snap.realized_pnl);
-        result.insert("unrealized_pnl".to_string(), snap.unrealized_pnl);
-        result.insert("equity".to_string(), snap.equity);
+/// An order that has been placed but not yet filled or cancelled
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenOrder {
+    pub order_id: u64,
+    pub client_tag: u64,
+    pub order_type: OrderType,
+    pub side: Side,
+    pub qty: f64,
+    /// Limit price in ticks (0 for market orders)
+    pub price_tick: i64,
+    /// Still waiting out `latency_ms`, not yet visible to the matcher
+    pub pending: bool,
+    /// May only shrink the position; see [`Engine::place_order_reduce_only`]
+    pub reduce_only: bool,
+}
 
-        Ok(result)
+/// An executed order, as recorded in the engine's fill log
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub order_id: u64,
+    /// Tag given to `place_order_with_tag`, 0 if none
+    pub client_tag: u64,
+    pub ts_ms: i64,
+    pub side: Side,
+    pub qty: f64,
+    /// Effective fill price in ticks, spread included
+    pub price_tick: i64,
+    /// Effective fill price in currency units, spread included
+    pub price: f64,
+    pub fee: f64,
+}
+
+impl Fill {
+    fn from_raw(raw: &fill_t, tick_size: f64) -> Self {
+        Fill {
+            order_id: raw.order_id,
+            client_tag: raw.client_tag,
+            ts_ms: raw.ts_ms,
+            side: raw.side.into(),
+            qty: raw.qty as f64 / 1000000.0,
+            price_tick: raw.price_tick,
+            price: raw.price_tick as f64 * tick_size,
+            fee: raw.fee,
+        }
     }
 }
 
-#[pymodule]
-fn _ag_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_class::<PyEngine>()?;
+/// Resting limit-order depth, from [`Engine::book_snapshot`]
+///
+/// Levels are `(price, qty)` in currency and base units, best first: bids
+/// by descending price, asks by ascending price.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookSnapshot {
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+/// Result of [`Engine::finalize`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinalReport {
+    /// State once every dangling order is gone, marked at the last trade
+    pub snapshot: Snapshot,
+    /// Orders cancelled because the data ended, by order id. Orders still
+    /// waiting out `latency_ms` are flagged `pending`.
+    pub cancelled: Vec<OpenOrder>,
+}
+
+/// Result of [`Engine::benchmark`]
+#[derive(Debug, Clone, Copy)]
+pub struct BenchResult {
+    pub n_ticks: usize,
+    pub elapsed: std::time::Duration,
+    pub ticks_per_sec: f64,
+    pub avg_ns_per_tick: f64,
+}
+
+/// Deterministic synthetic ticks for benchmarking: a bounded random walk
+/// around 100.00 (at tick size 0.01) with alternating sides
+fn synthetic_ticks(n: usize) -> Vec<tick_event_t> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut price_tick: i64 = 10_000;
+
+    (0..n)
+        .map(|i| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            price_tick = (price_tick + (state % 5) as i64 - 2).clamp(9_000, 11_000);
+            tick_event_t {
+                ts_ms: 1_000 + i as i64,
+                price_tick,
+                qty: 1_000_000,
+                side: if i % 2 == 0 { side_t::SIDE_BUY } else { side_t::SIDE_SELL },
+            }
+        })
+        .collect()
+}
+
+/// Number of fills pulled from the C engine per FFI call
+const FILL_DRAIN_CHUNK: usize = 32;
+
+/// Capacity of the C engine's order book (`MAX_OPEN_ORDERS` in engine.c)
+const MAX_OPEN_ORDERS: usize = 1024;
+
+/// Verify the linked C engine was built from the headers the bindings match
+///
+/// A stale `engine.c` with different struct layouts would otherwise corrupt
+/// every value crossing the FFI boundary without any error.
+fn check_abi() -> Result<(), EngineError> {
+    let version = unsafe { engine_abi_version() };
+    if version != ENGINE_ABI_VERSION {
+        return Err(EngineError::AbiMismatch(format!(
+            "engine version {}, bindings expect {}",
+            version, ENGINE_ABI_VERSION
+        )));
+    }
+
+    let layout = unsafe { engine_abi_layout() };
+    let expected = abi_layout_t::expected();
+    if layout != expected {
+        return Err(EngineError::AbiMismatch(format!(
+            "engine struct sizes {:?}, bindings expect {:?}",
+            layout, expected
+        )));
+    }
+
     Ok(())
 }
+
+/// When the equity log appends a point, set with
+/// [`Engine::enable_sampled_equity_log`]
+///
+/// The first point of an empty log (after enabling or `reset`) is always
+/// taken, so a sampled log still starts where the run does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EquitySampling {
+    /// After every tick and clock advance
+    #[default]
+    EveryTick,
+    /// After every `n`th tick or clock advance
+    EveryEvents(u64),
+    /// After the first tick or clock advance at least `ms` of simulated
+    /// time after the last logged point
+    EveryMs(i64),
+}
+
+/// Safe wrapper around the C engine
+pub struct Engine {
+    handle: *mut engine_handle_t,
+    config: EngineConfig,
+    next_order_id: u64,
+    fills: Vec<Fill>,
+    /// Orders waiting out `latency_ms`, sorted by release timestamp
+    pending_orders: VecDeque<(i64, order_t)>,
+    /// `(valid_until_ms, order_id)` of good-till-date orders
+    gtd_orders: Vec<(i64, u64)>,
+    /// Immediate-or-cancel and fill-or-kill orders not yet matched
+    ioc_orders: Vec<u64>,
+    /// Delayed orders refused on release, oldest first
+    rejected_orders: Vec<RejectedOrder>,
+    /// `(ts_ms, equity)` after every tick, when enabled
+    equity_log: Option<Vec<(i64, f64)>>,
+    /// Most points the equity log may hold before it is compacted
+    equity_log_cap: Option<usize>,
+    equity_log_sampling: EquitySampling,
+    /// Events seen since the last logged point, for `EveryEvents`
+    equity_log_skipped: u64,
+    /// Ticks that moved price outside `price_band_bps` of the previous tick
+    band_breaches: u64,
+    /// Funding payments applied automatically as the clock passes them
+    funding_schedule: Option<FundingSchedule>,
+    /// Every funding payment applied, oldest first
+    funding_log: Vec<FundingEvent>,
+    /// Base quantity and notional filled so far, kept across `take_fills`
+    cumulative_volume: f64,
+    turnover: f64,
+    /// Queue-position estimates for resting limit orders, when enabled
+    queue_tracker: Option<QueueTracker>,
+    /// Callback for [`Engine::on_snapshot_change`] and the values it last saw
+    snapshot_watch: Option<SnapshotWatch>,
+    /// Closure registered with [`Engine::on_fill`], owned here and lent to
+    /// the C engine as its callback userdata
+    fill_callback: Option<*mut FillCallback>,
+    /// Every accepted call, oldest first, when enabled
+    event_log: Option<Vec<EngineCommand>>,
+    /// Indicators updated by every stepped candle and cleared by `reset`
+    indicators: IndicatorSet,
+    /// Running drawdown of equity after every tick, when enabled
+    drawdown: Option<DrawdownTracker>,
+    /// Last snapshot read from the core; cleared before anything that can
+    /// change core state
+    snapshot_cache: Cell<Option<Snapshot>>,
+}
+
+impl Engine {
+    pub fn new(
+        initial_cash: f64,
+        maker_fee_bps: f64,
+        taker_fee_bps: f64,
+        spread_bps: f64,
+        tick_size: f64,
+    ) -> Result<Self, EngineError> {
+        Self::with_config(EngineConfig {
+            initial_cash,
+            maker_fee_bps,
+            taker_fee_bps,
+            spread_bps,
+            tick_size,
+            ..EngineConfig::default()
+        })
+    }
+
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::new()
+    }
+
+    pub fn with_config(config: EngineConfig) -> Result<Self, EngineError> {
+        check_abi()?;
+        config.validate()?;
+        let (position, avg_entry_tick) = config.initial_position_ffi()?;
+        let handle = unsafe { engine_new(&config.to_ffi()) };
+
+        if handle.is_null() {
+            return Err(EngineError::CreateFailed);
+        }
+
+        let engine = Engine {
+            handle,
+            config,
+            next_order_id: 1,
+            fills: Vec::new(),
+            pending_orders: VecDeque::new(),
+            gtd_orders: Vec::new(),
+            ioc_orders: Vec::new(),
+            rejected_orders: Vec::new(),
+            equity_log: None,
+            equity_log_cap: None,
+            equity_log_sampling: EquitySampling::EveryTick,
+            equity_log_skipped: 0,
+            band_breaches: 0,
+            funding_schedule: None,
+            funding_log: Vec::new(),
+            cumulative_volume: 0.0,
+            turnover: 0.0,
+            queue_tracker: None,
+            snapshot_watch: None,
+            fill_callback: None,
+            event_log: None,
+            indicators: IndicatorSet::new(),
+            drawdown: None,
+            snapshot_cache: Cell::new(None),
+        };
+        engine.seed_position(position, avg_entry_tick);
+        log_event!(
+            info,
+            initial_cash = config.initial_cash,
+            tick_size = config.tick_size,
+            maker_fee_bps = config.maker_fee_bps,
+            taker_fee_bps = config.taker_fee_bps,
+            "engine created"
+        );
+
+        Ok(engine)
+    }
+
+    /// Install a validated initial position in the C engine
+    fn seed_position(&self, position: i64, avg_entry_tick: f64) {
+        if position != 0 {
+            self.invalidate_snapshot();
+            let result = unsafe { engine_set_position(self.handle, position, avg_entry_tick) };
+            debug_assert_eq!(result, 0, "initial position was validated");
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.invalidate_snapshot();
+        unsafe { engine_reset(self.handle) }
+        // Validated when this config was installed
+        if let Ok((position, avg_entry_tick)) = self.config.initial_position_ffi() {
+            self.seed_position(position, avg_entry_tick);
+        }
+        self.clear_run_state();
+        self.notify_snapshot_change();
+    }
+
+    /// Clear everything a run accumulates on the Rust side, keeping opt-in
+    /// components enabled; shared by `reset` and `reset_with_config`
+    fn clear_run_state(&mut self) {
+        self.next_order_id = 1;
+        self.fills.clear();
+        self.pending_orders.clear();
+        self.gtd_orders.clear();
+        self.ioc_orders.clear();
+        self.rejected_orders.clear();
+        self.band_breaches = 0;
+        self.funding_log.clear();
+        self.cumulative_volume = 0.0;
+        self.turnover = 0.0;
+        if let Some(schedule) = &mut self.funding_schedule {
+            schedule.rewind();
+        }
+        if let Some(log) = &mut self.equity_log {
+            log.clear();
+        }
+        self.equity_log_skipped = 0;
+        if let Some(tracker) = &mut self.queue_tracker {
+            tracker.clear();
+        }
+        if let Some(log) = &mut self.event_log {
+            log.clear();
+        }
+        self.indicators.reset();
+        if let Some(tracker) = &mut self.drawdown {
+            tracker.reset();
+        }
+    }
+
+    /// Reset the engine to initial state under a new configuration
+    ///
+    /// Position and average entry price are held in tick space, so changing
+    /// `tick_size` while a position is open would silently reinterpret them at
+    /// the new scale. Rather than rescaling, this refuses with
+    /// `EngineError::TickSizeChangeWithPosition`: flatten (or `reset`) first.
+    /// Changing any other parameter, or `tick_size` while flat, is always allowed.
+    pub fn reset_with_config(&mut self, config: EngineConfig) -> Result<(), EngineError> {
+        config.validate()?;
+        let (position, avg_entry_tick) = config.initial_position_ffi()?;
+
+        if config.tick_size != self.config.tick_size {
+            let snap = unsafe { engine_get_snapshot(self.handle) };
+            if snap.position != 0 {
+                return Err(EngineError::TickSizeChangeWithPosition {
+                    old: self.config.tick_size,
+                    new: config.tick_size,
+                });
+            }
+        }
+
+        self.invalidate_snapshot();
+        unsafe { engine_reset_with_config(self.handle, &config.to_ffi()) };
+        self.seed_position(position, avg_entry_tick);
+        self.config = config;
+        self.sync_fill_callback_tick_size();
+        self.clear_run_state();
+        self.notify_snapshot_change();
+        Ok(())
+    }
+
+    /// Capture the full engine state for later [`Engine::restore`]
+    ///
+    /// Intended for parameter sweeps: warm the engine up once, checkpoint,
+    /// then restore before each variation instead of re-ingesting the warmup.
+    pub fn checkpoint(&self) -> Result<EngineCheckpoint, EngineError> {
+        let handle = unsafe { engine_clone(self.handle) };
+
+        if handle.is_null() {
+            return Err(EngineError::CreateFailed);
+        }
+
+        Ok(EngineCheckpoint {
+            handle,
+            config: self.config,
+            next_order_id: self.next_order_id,
+            fills: self.fills.clone(),
+            pending_orders: self.pending_orders.clone(),
+            gtd_orders: self.gtd_orders.clone(),
+            ioc_orders: self.ioc_orders.clone(),
+            rejected_orders: self.rejected_orders.clone(),
+            equity_log: self.equity_log.clone(),
+            equity_log_cap: self.equity_log_cap,
+            equity_log_sampling: self.equity_log_sampling,
+            equity_log_skipped: self.equity_log_skipped,
+            band_breaches: self.band_breaches,
+            funding_schedule: self.funding_schedule.clone(),
+            funding_log: self.funding_log.clone(),
+            cumulative_volume: self.cumulative_volume,
+            turnover: self.turnover,
+            queue_tracker: self.queue_tracker.clone(),
+            event_log: self.event_log.clone(),
+            indicators: self.indicators.clone(),
+            drawdown: self.drawdown,
+        })
+    }
+
+    /// Return to the state captured by `checkpoint`, including its config
+    ///
+    /// A checkpoint can be restored any number of times.
+    pub fn restore(&mut self, checkpoint: &EngineCheckpoint) {
+        self.invalidate_snapshot();
+        unsafe { engine_copy_state(self.handle, checkpoint.handle) };
+        self.config = checkpoint.config;
+        self.sync_fill_callback_tick_size();
+        self.next_order_id = checkpoint.next_order_id;
+        self.fills.clone_from(&checkpoint.fills);
+        self.pending_orders.clone_from(&checkpoint.pending_orders);
+        self.gtd_orders.clone_from(&checkpoint.gtd_orders);
+        self.ioc_orders.clone_from(&checkpoint.ioc_orders);
+        self.rejected_orders.clone_from(&checkpoint.rejected_orders);
+        self.equity_log.clone_from(&checkpoint.equity_log);
+        self.equity_log_cap = checkpoint.equity_log_cap;
+        self.equity_log_sampling = checkpoint.equity_log_sampling;
+        self.equity_log_skipped = checkpoint.equity_log_skipped;
+        self.band_breaches = checkpoint.band_breaches;
+        self.funding_schedule.clone_from(&checkpoint.funding_schedule);
+        self.funding_log.clone_from(&checkpoint.funding_log);
+        self.cumulative_volume = checkpoint.cumulative_volume;
+        self.turnover = checkpoint.turnover;
+        self.queue_tracker.clone_from(&checkpoint.queue_tracker);
+        self.event_log.clone_from(&checkpoint.event_log);
+        self.indicators.clone_from(&checkpoint.indicators);
+        self.drawdown = checkpoint.drawdown;
+        self.notify_snapshot_change();
+    }
+
+    /// Start recording `(ts_ms, equity)` after every tick
+    ///
+    /// Off by default since it costs a snapshot per tick. The log is cleared
+    /// by `reset` but stays enabled.
+    pub fn enable_equity_log(&mut self) {
+        self.equity_log.get_or_insert_with(Vec::new);
+        self.equity_log_cap = None;
+        self.equity_log_sampling = EquitySampling::EveryTick;
+    }
+
+    /// Like [`Engine::enable_equity_log`], but append only as often as
+    /// `sampling` allows
+    ///
+    /// For tick data, logging every trade is rarely useful; one point per
+    /// bar or per N ms is enough to plot and costs a fraction of the memory.
+    /// Combines with [`Engine::enable_bounded_equity_log`]. Intervals of
+    /// zero log every tick. Only the log is sampled: drawdown tracking still
+    /// sees every tick.
+    pub fn enable_sampled_equity_log(&mut self, sampling: EquitySampling) {
+        self.equity_log.get_or_insert_with(Vec::new);
+        self.equity_log_sampling = sampling;
+        self.equity_log_skipped = 0;
+    }
+
+    /// Like [`Engine::enable_equity_log`], but hold at most `max_points`
+    ///
+    /// Whenever the log outgrows the cap it is compacted in place to half of
+    /// it with [`metrics::compact_curve`], keeping the first and last points
+    /// and each bucket's minimum and maximum. Memory stays bounded and the
+    /// overall extremes survive every compaction, at the cost of coarser
+    /// time resolution for older points. Caps below 8 are raised to 8, the
+    /// smallest that still keeps extremes when halved.
+    pub fn enable_bounded_equity_log(&mut self, max_points: usize) {
+        let cap = max_points.max(8);
+        let log = self.equity_log.get_or_insert_with(Vec::new);
+        metrics::compact_curve(log, cap);
+        self.equity_log_cap = Some(cap);
+    }
+
+    /// Start recording every accepted call as an [`EngineCommand`]
+    ///
+    /// Off by default since it costs a push per tick. See [`event_log`] for
+    /// what is recorded. The log is cleared by `reset` but stays enabled.
+    pub fn enable_event_log(&mut self) {
+        self.event_log.get_or_insert_with(Vec::new);
+    }
+
+    /// Recorded commands, oldest first (empty unless enabled)
+    pub fn event_log(&self) -> &[EngineCommand] {
+        self.event_log.as_deref().unwrap_or(&[])
+    }
+
+    /// Register an indicator to be updated by every candle stepped through
+    /// [`Engine::step_candle`] (and so by [`market_event::replay`]),
+    /// returning its index in [`Engine::indicators`]
+    ///
+    /// Registered indicators are cleared by `reset` and captured by
+    /// `checkpoint`, so runs that reset in between each warm up from scratch.
+    pub fn add_indicator(&mut self, indicator: impl Into<Indicator>) -> usize {
+        self.indicators.add(indicator)
+    }
+
+    /// Registered indicators, as of the last stepped candle
+    pub fn indicators(&self) -> &IndicatorSet {
+        &self.indicators
+    }
+
+    /// Apply recorded commands in order
+    ///
+    /// On a fresh engine with the recording engine's config (and funding
+    /// schedule, if any) this reproduces the recorded state exactly. Stops
+    /// at the first command that fails, which means the log did not come
+    /// from an engine in this one's state.
+    pub fn replay_log(&mut self, commands: &[EngineCommand]) -> Result<(), EngineError> {
+        for command in commands {
+            match *command {
+                EngineCommand::Tick { ts_ms, price_tick, qty_scaled, side } => {
+                    let side = match side {
+                        Side::Buy => side_t::SIDE_BUY,
+                        Side::Sell => side_t::SIDE_SELL,
+                    };
+                    self.step_tick_raw(&tick_event_t { ts_ms, price_tick, qty: qty_scaled, side })?;
+                }
+                EngineCommand::PlaceOrder {
+                    order_id,
+                    order_type,
+                    side,
+                    qty,
+                    price,
+                    client_tag,
+                    time_in_force,
+                    reduce_only,
+                } => {
+                    let placed = self.submit_order(
+                        order_type.as_str(),
+                        side.as_str(),
+                        qty,
+                        price,
+                        client_tag,
+                        time_in_force,
+                        reduce_only,
+                    )?;
+                    debug_assert_eq!(placed, order_id, "replayed order ids follow the recording");
+                }
+                EngineCommand::CancelOrder { order_id } => self.cancel_order(order_id)?,
+                EngineCommand::AdvanceClock { ts_ms } => self.advance_clock(ts_ms)?,
+                EngineCommand::ApplyFunding { rate } => {
+                    self.apply_funding(rate)?;
+                }
+                EngineCommand::MarkTo { price_tick } => self.mark_to_tick(price_tick)?,
+                EngineCommand::ClosePosition { price_tick } => self.close_position_at_tick(price_tick)?,
+                EngineCommand::Finalize => {
+                    self.finalize();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Start estimating queue positions of limit orders placed from now on
+    ///
+    /// Off by default since it costs a map update per tick. See [`queue`] for
+    /// the model. Cleared by `reset` but stays enabled.
+    pub fn enable_queue_tracking(&mut self) {
+        self.queue_tracker.get_or_insert_with(QueueTracker::new);
+    }
+
+    /// Estimated volume ahead of a resting limit order at its price level,
+    /// scaled by 1e6 like tick quantities
+    ///
+    /// `None` unless tracking is enabled and the order is an open limit order
+    /// placed since.
+    pub fn queue_position(&self, order_id: u64) -> Option<i64> {
+        self.queue_tracker.as_ref()?.queue_position(order_id)
+    }
+
+    /// Start tracking drawdown from the equity after every tick
+    ///
+    /// Off by default since it costs a snapshot per tick. Unlike the equity
+    /// log this keeps no history, only the peak and the worst drawdown. The
+    /// tracker is cleared by `reset` but stays enabled.
+    pub fn enable_drawdown_tracking(&mut self) {
+        self.drawdown.get_or_insert_with(DrawdownTracker::new);
+    }
+
+    /// Running drawdown, if tracking is enabled
+    pub fn drawdown(&self) -> Option<&DrawdownTracker> {
+        self.drawdown.as_ref()
+    }
+
+    /// Recorded equity curve, oldest first (empty unless enabled)
+    pub fn equity_curve(&self) -> &[(i64, f64)] {
+        self.equity_log.as_deref().unwrap_or(&[])
+    }
+
+    /// Fills executed so far, oldest first
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    /// Take the fill log, leaving it empty
+    pub fn take_fills(&mut self) -> Vec<Fill> {
+        std::mem::take(&mut self.fills)
+    }
+
+    /// Move pending fills from the C engine into the fill log
+    fn drain_fills(&mut self) {
+        let mut buf = [std::mem::MaybeUninit::<fill_t>::uninit(); FILL_DRAIN_CHUNK];
+
+        loop {
+            let n = unsafe {
+                engine_drain_fills(self.handle, buf.as_mut_ptr() as *mut fill_t, FILL_DRAIN_CHUNK as i32)
+            };
+
+            for raw in &buf[..n as usize] {
+                // SAFETY: the engine initialized the first n entries
+                let raw = unsafe { raw.assume_init() };
+                let fill = Fill::from_raw(&raw, self.config.tick_size);
+                if let Some(tracker) = &mut self.queue_tracker {
+                    tracker.remove(fill.order_id);
+                }
+                self.cumulative_volume += fill.qty;
+                self.turnover += fill.qty * fill.price;
+                self.fills.push(fill);
+            }
+
+            if (n as usize) < FILL_DRAIN_CHUNK {
+                break;
+            }
+        }
+    }
+
+    /// Submit latency-delayed orders whose release time has come
+    ///
+    /// An order the core refuses now (the cash check uses the price at
+    /// release, not at submission) is recorded in
+    /// [`Engine::rejected_orders`] so the tick that released it still steps.
+    /// Any other failure leaves the order queued and is returned.
+    fn release_pending_orders(&mut self, ts_ms: i64) -> Result<(), EngineError> {
+        while let Some(&(release_ts, order)) = self.pending_orders.front() {
+            if release_ts > ts_ms {
+                break;
+            }
+
+            match self.place_order_raw(&order) {
+                Ok(()) => {}
+                Err(EngineError::OrderRejected(reason)) => {
+                    self.gtd_orders.retain(|&(_, id)| id != order.order_id);
+                    self.ioc_orders.retain(|&id| id != order.order_id);
+                    self.rejected_orders.push(RejectedOrder {
+                        order_id: order.order_id,
+                        client_tag: order.client_tag,
+                        ts_ms,
+                        reason,
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+            self.pending_orders.pop_front();
+        }
+
+        Ok(())
+    }
+
+    /// Hand an order to the C engine, mapping a refusal to its reason
+    fn place_order_raw(&mut self, order: &order_t) -> Result<(), EngineError> {
+        self.invalidate_snapshot();
+        let result = unsafe { engine_place_order(self.handle, order) };
+        if result >= 0 {
+            return Ok(());
+        }
+
+        if let Some(tracker) = &mut self.queue_tracker {
+            tracker.remove(order.order_id);
+        }
+
+        let raw = unsafe { engine_last_reject_reason(self.handle) };
+        let reason = match reject_reason_t::from_raw(raw) {
+            Some(reject_reason_t::REJECT_INVALID_ORDER) => RejectReason::InvalidOrder,
+            Some(reject_reason_t::REJECT_BOOK_FULL) => RejectReason::BookFull,
+            Some(reject_reason_t::REJECT_DUPLICATE_ID) => RejectReason::DuplicateId,
+            Some(reject_reason_t::REJECT_INSUFFICIENT_CASH) => RejectReason::InsufficientCash,
+            Some(reject_reason_t::REJECT_NO_PRICE) => RejectReason::NoPrice,
+            Some(reject_reason_t::REJECT_NONE) | None => return Err(EngineError::PlaceOrderFailed(result)),
+        };
+        log_event!(warn, order_id = order.order_id, reason = reason.as_str(), "order rejected");
+        Err(EngineError::OrderRejected(reason))
+    }
+
+    /// Step one already-encoded tick: release due orders, match, collect fills
+    fn step_tick_raw(&mut self, tick: &tick_event_t) -> Result<(), EngineError> {
+        if self.funding_schedule.is_some() {
+            self.apply_due_funding(tick.ts_ms)?;
+        }
+        if !self.pending_orders.is_empty() {
+            self.release_pending_orders(tick.ts_ms)?;
+        }
+        if !self.gtd_orders.is_empty() {
+            self.expire_orders(tick.ts_ms);
+        }
+
+        if self.config.price_band_bps.is_some() && self.outside_band(tick.price_tick).is_some() {
+            self.band_breaches += 1;
+        }
+        if let Some(tracker) = &mut self.queue_tracker {
+            tracker.on_trade(tick.price_tick, tick.qty);
+        }
+
+        self.invalidate_snapshot();
+        let result = unsafe { engine_step_tick(self.handle, tick) };
+        self.drain_fills();
+        self.resume_fill_callback_panic();
+
+        if result < 0 {
+            return Err(EngineError::StepFailed(result));
+        }
+        if !self.ioc_orders.is_empty() {
+            self.cancel_matched_ioc_orders();
+        }
+
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::Tick {
+                ts_ms: tick.ts_ms,
+                price_tick: tick.price_tick,
+                qty_scaled: tick.qty,
+                side: tick.side.into(),
+            });
+        }
+        self.record_equity();
+        self.notify_snapshot_change();
+        Ok(())
+    }
+
+    /// Distance from the last trade if `price_tick` is outside the price band
+    ///
+    /// Returns `Some((last_price_tick, band_bps))` on a breach; `None` when
+    /// inside the band, when no band is configured or before the first tick.
+    fn outside_band(&self, price_tick: i64) -> Option<(i64, f64)> {
+        let band_bps = self.config.price_band_bps?;
+        let last = unsafe { engine_get_last_tick_price(self.handle) };
+        if last == 0 {
+            return None;
+        }
+
+        let deviation_bps = (price_tick - last).abs() as f64 / last.abs() as f64 * 10_000.0;
+        (deviation_bps > band_bps).then_some((last, band_bps))
+    }
+
+    /// Whether a limit at `price_tick` would fill against a trade at the last
+    /// price under the configured `limit_fill_model`; false before any price
+    fn crosses_last_trade(&self, side: side_t, price_tick: i64) -> bool {
+        if unsafe { engine_has_last_price(self.handle) } == 0 {
+            return false;
+        }
+
+        let last = unsafe { engine_get_last_tick_price(self.handle) };
+        let through = self.config.limit_fill_model == LimitFillModel::OnThrough;
+        match side {
+            side_t::SIDE_BUY => last < price_tick || (!through && last == price_tick),
+            side_t::SIDE_SELL => last > price_tick || (!through && last == price_tick),
+        }
+    }
+
+    /// The error for an order under `min_order_qty` or `min_order_notional`
+    ///
+    /// Compares the scaled quantity, so a qty that truncates below the
+    /// minimum is rejected. Market orders are valued at the last trade.
+    fn below_minimum(&self, order_type: order_type_t, qty_scaled: i64, price_tick: i64) -> Option<EngineError> {
+        if let Some(min_qty) = self.config.min_order_qty {
+            if qty_scaled < (min_qty * 1000000.0).round() as i64 {
+                return Some(EngineError::BelowMinimum {
+                    field: "qty",
+                    value: qty_scaled as f64 / 1000000.0,
+                    minimum: min_qty,
+                });
+            }
+        }
+
+        if let Some(min_notional) = self.config.min_order_notional {
+            let price_tick = match order_type {
+                order_type_t::ORDER_TYPE_LIMIT => price_tick,
+                order_type_t::ORDER_TYPE_MARKET => unsafe { engine_get_last_tick_price(self.handle) },
+            };
+            let notional = qty_scaled as f64 / 1000000.0 * (price_tick as f64 * self.config.tick_size).abs();
+            if price_tick != 0 && notional < min_notional {
+                return Some(EngineError::BelowMinimum {
+                    field: "notional",
+                    value: notional,
+                    minimum: min_notional,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Number of ticks whose price moved outside `price_band_bps` of the
+    /// previous tick. They are still processed; this only flags them.
+    pub fn band_breaches(&self) -> u64 {
+        self.band_breaches
+    }
+
+    /// Pay or receive one funding period on the current position
+    ///
+    /// Transfers `-position * mark * rate` to cash, where the mark is the last
+    /// traded price: with a positive rate longs pay and shorts receive. The
+    /// payment is recorded in [`Engine::funding_events`]. Returns the cash
+    /// change.
+    pub fn apply_funding(&mut self, rate: f64) -> Result<f64, EngineError> {
+        let ts_ms = self.get_snapshot().ts_ms;
+        let amount = self.apply_funding_at(ts_ms, rate)?;
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::ApplyFunding { rate });
+        }
+        self.notify_snapshot_change();
+        Ok(amount)
+    }
+
+    /// Value the position at an external mark such as an index price,
+    /// without a trade
+    ///
+    /// The price is snapped to the tick grid with the configured rounding
+    /// and replaces the last trade price until the next tick, so unrealized
+    /// PnL, equity, funding and the price band all use it. Position, fills
+    /// and volume stats are untouched, and no equity point is logged.
+    /// Returns the updated snapshot.
+    pub fn mark_to(&mut self, price: f64) -> Result<Snapshot, EngineError> {
+        let price_tick = self.mark_price_tick(price)?;
+        self.mark_to_tick(price_tick)?;
+        Ok(self.get_snapshot())
+    }
+
+    /// Snap a mark price to the tick grid; zero and negative prices are
+    /// valid marks, only non-finite ones are refused
+    fn mark_price_tick(&self, price: f64) -> Result<i64, EngineError> {
+        if !price.is_finite() {
+            return Err(EngineError::InvalidMarkPrice(price));
+        }
+        Ok(self.config.rounding.quantize(price, self.config.tick_size))
+    }
+
+    fn mark_to_tick(&mut self, price_tick: i64) -> Result<(), EngineError> {
+        self.invalidate_snapshot();
+        let result = unsafe { engine_set_mark_price(self.handle, price_tick) };
+        if result < 0 {
+            return Err(EngineError::MarkFailed(result));
+        }
+
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::MarkTo { price_tick });
+        }
+        self.notify_snapshot_change();
+        Ok(())
+    }
+
+    fn apply_funding_at(&mut self, ts_ms: i64, rate: f64) -> Result<f64, EngineError> {
+        if !rate.is_finite() {
+            return Err(EngineError::InvalidFundingRate(rate));
+        }
+
+        let position = self.get_snapshot().position;
+        let mark_price = unsafe { engine_get_last_tick_price(self.handle) } as f64 * self.config.tick_size;
+        let amount = -position * mark_price * rate;
+
+        if amount != 0.0 {
+            self.invalidate_snapshot();
+            let result = unsafe { engine_apply_cash_flow(self.handle, amount) };
+            if result < 0 {
+                return Err(EngineError::FundingFailed(result));
+            }
+        }
+
+        self.funding_log.push(FundingEvent {
+            ts_ms,
+            rate,
+            mark_price,
+            position,
+            amount,
+        });
+        Ok(amount)
+    }
+
+    /// Apply scheduled funding payments due at or before `ts_ms`
+    fn apply_due_funding(&mut self, ts_ms: i64) -> Result<(), EngineError> {
+        while let Some((funding_ts, rate)) = self.funding_schedule.as_mut().and_then(|s| s.pop_due(ts_ms)) {
+            self.apply_funding_at(funding_ts, rate)?;
+        }
+        Ok(())
+    }
+
+    /// Apply `schedule` automatically as ticks and `advance_clock` pass its
+    /// timestamps. Replaces any previous schedule; `reset` rewinds it.
+    ///
+    /// A schedule with a non-finite rate is refused and leaves the previous
+    /// one in place.
+    pub fn set_funding_schedule(&mut self, schedule: FundingSchedule) -> Result<(), EngineError> {
+        if let Some(&(_, rate)) = schedule.payments().iter().find(|(_, rate)| !rate.is_finite()) {
+            return Err(EngineError::InvalidFundingRate(rate));
+        }
+        self.funding_schedule = Some(schedule);
+        Ok(())
+    }
+
+    /// Funding payments applied so far, oldest first
+    pub fn funding_events(&self) -> &[FundingEvent] {
+        &self.funding_log
+    }
+
+    /// Latency-delayed orders refused when they were released, oldest first
+    pub fn rejected_orders(&self) -> &[RejectedOrder] {
+        &self.rejected_orders
+    }
+
+    /// Append the current equity to the equity log, if enabled
+    fn record_equity(&mut self) {
+        if self.equity_log.is_some() || self.drawdown.is_some() {
+            let snap = self.get_snapshot();
+            if let Some(tracker) = &mut self.drawdown {
+                tracker.update(snap.equity);
+            }
+            if let Some(log) = &mut self.equity_log {
+                self.equity_log_skipped += 1;
+                let due = match (self.equity_log_sampling, log.last()) {
+                    (EquitySampling::EveryTick, _) | (_, None) => true,
+                    (EquitySampling::EveryEvents(n), Some(_)) => self.equity_log_skipped >= n,
+                    (EquitySampling::EveryMs(ms), Some(&(last_ts, _))) => snap.ts_ms - last_ts >= ms,
+                };
+                if !due {
+                    return;
+                }
+                self.equity_log_skipped = 0;
+                log.push((snap.ts_ms, snap.equity));
+                if let Some(cap) = self.equity_log_cap {
+                    if log.len() > cap {
+                        metrics::compact_curve(log, cap / 2);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Call `callback` whenever position, cash or equity change
+    ///
+    /// Checked after ticks, clock advances, funding, resets and restores;
+    /// the callback receives only the fields that differ from the last
+    /// notification. Replaces any previous callback. Without one, the check
+    /// costs a single branch.
+    pub fn on_snapshot_change(&mut self, callback: impl FnMut(&SnapshotDelta) + Send + 'static) {
+        let snap = self.get_snapshot();
+        self.snapshot_watch = Some(SnapshotWatch {
+            callback: Box::new(callback),
+            last: (snap.position, snap.cash, snap.equity),
+        });
+    }
+
+    /// Stop calling the [`Engine::on_snapshot_change`] callback
+    pub fn clear_snapshot_callback(&mut self) {
+        self.snapshot_watch = None;
+    }
+
+    /// Call `callback` with every fill as the matcher makes it, instead of
+    /// polling [`Engine::fills`]
+    ///
+    /// The C engine calls it in the middle of a step, so it sees each fill
+    /// before the fill log and snapshot do. Fills still go to the fill log.
+    /// A panic in the callback is raised again from the engine call that
+    /// made the fill, once the engine state is consistent; later fills of
+    /// that step are not reported. Replaces any previous callback and stays
+    /// registered across `reset` and `restore`; checkpoints do not carry it.
+    pub fn on_fill(&mut self, callback: impl FnMut(Fill) + Send + 'static) {
+        self.clear_fill_callback();
+        let state = Box::into_raw(Box::new(FillCallback {
+            tick_size: self.config.tick_size,
+            callback: Box::new(callback),
+            panic: None,
+        }));
+        unsafe { engine_set_fill_callback(self.handle, Some(fill_trampoline), state as *mut c_void) };
+        self.fill_callback = Some(state);
+    }
+
+    /// Stop calling the [`Engine::on_fill`] callback
+    pub fn clear_fill_callback(&mut self) {
+        if let Some(state) = self.fill_callback.take() {
+            unsafe {
+                engine_set_fill_callback(self.handle, None, ptr::null_mut());
+                // SAFETY: created by Box::into_raw in on_fill and no longer reachable from C
+                drop(Box::from_raw(state));
+            }
+        }
+    }
+
+    fn sync_fill_callback_tick_size(&mut self) {
+        if let Some(state) = self.fill_callback {
+            // SAFETY: owned by this engine; C only uses it during engine calls
+            unsafe { (*state).tick_size = self.config.tick_size };
+        }
+    }
+
+    /// Re-raise a panic caught in the fill callback during the last C call
+    fn resume_fill_callback_panic(&mut self) {
+        if let Some(state) = self.fill_callback {
+            // SAFETY: owned by this engine; C only uses it during engine calls
+            if let Some(payload) = unsafe { (*state).panic.take() } {
+                std::panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    /// Report changed fields to the snapshot callback, if one is registered
+    fn notify_snapshot_change(&mut self) {
+        if self.snapshot_watch.is_none() {
+            return;
+        }
+
+        let snap = self.get_snapshot();
+        if let Some(watch) = &mut self.snapshot_watch {
+            let (position, cash, equity) = watch.last;
+            let delta = SnapshotDelta {
+                ts_ms: snap.ts_ms,
+                position: (snap.position != position).then_some(snap.position),
+                cash: (snap.cash != cash).then_some(snap.cash),
+                equity: (snap.equity != equity).then_some(snap.equity),
+            };
+            if delta.position.is_some() || delta.cash.is_some() || delta.equity.is_some() {
+                watch.last = (snap.position, snap.cash, snap.equity);
+                (watch.callback)(&delta);
+            }
+        }
+    }
+
+    /// Cancel good-till-date orders whose validity ended before `ts_ms`
+    fn expire_orders(&mut self, ts_ms: i64) {
+        let mut expired = Vec::new();
+        self.gtd_orders.retain(|&(valid_until, order_id)| {
+            let keep = valid_until >= ts_ms;
+            if !keep {
+                expired.push(order_id);
+            }
+            keep
+        });
+
+        for order_id in expired {
+            // Already-filled orders are simply gone
+            let _ = self.cancel_resting_order(order_id);
+        }
+    }
+
+    /// Cancel immediate-or-cancel orders left over from the tick just
+    /// matched; those still waiting out `latency_ms` keep their turn
+    fn cancel_matched_ioc_orders(&mut self) {
+        let pending = &self.pending_orders;
+        let mut matched = Vec::new();
+        self.ioc_orders.retain(|&order_id| {
+            let waiting = pending.iter().any(|(_, o)| o.order_id == order_id);
+            if !waiting {
+                matched.push(order_id);
+            }
+            waiting
+        });
+
+        for order_id in matched {
+            // Filled orders are already gone
+            let _ = self.cancel_resting_order(order_id);
+        }
+    }
+
+    /// Cancel an order in the latency queue or the C book
+    fn cancel_resting_order(&mut self, order_id: u64) -> Result<(), EngineError> {
+        if let Some(tracker) = &mut self.queue_tracker {
+            tracker.remove(order_id);
+        }
+
+        if let Some(idx) = self.pending_orders.iter().position(|(_, o)| o.order_id == order_id) {
+            self.pending_orders.remove(idx);
+            return Ok(());
+        }
+
+        self.invalidate_snapshot();
+        let result = unsafe { engine_cancel_order(self.handle, order_id) };
+        if result < 0 {
+            return Err(EngineError::OrderNotFound(order_id));
+        }
+
+        Ok(())
+    }
+
+    /// Cancel an open order (including one still waiting out `latency_ms`)
+    pub fn cancel_order(&mut self, order_id: u64) -> Result<(), EngineError> {
+        self.cancel_resting_order(order_id)?;
+        self.gtd_orders.retain(|&(_, id)| id != order_id);
+        self.ioc_orders.retain(|&id| id != order_id);
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::CancelOrder { order_id });
+        }
+        Ok(())
+    }
+
+    /// End the run: cancel every order still open and return the final state
+    ///
+    /// Latency-queued orders are dropped without ever reaching the matcher,
+    /// and resting orders (good-till-date or not) are cancelled, so nothing
+    /// is left dangling when the data stops. The position is left as is,
+    /// marked at the last traded price. Calling it again cancels nothing and
+    /// returns the same snapshot.
+    pub fn finalize(&mut self) -> FinalReport {
+        let cancelled = self.open_orders();
+        for order in &cancelled {
+            // Every id came from open_orders, so the cancel cannot miss
+            let _ = self.cancel_resting_order(order.order_id);
+        }
+        self.gtd_orders.clear();
+        self.ioc_orders.clear();
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::Finalize);
+        }
+        log_event!(debug, cancelled = cancelled.len(), "engine finalized");
+
+        self.notify_snapshot_change();
+        FinalReport {
+            snapshot: self.get_snapshot(),
+            cancelled,
+        }
+    }
+
+    /// [`Engine::finalize`], then close out the position at `final_price`
+    ///
+    /// The position is closed with a market fill at the quantized price,
+    /// paying half the spread and the taker fee like any market order, so
+    /// unrealized PnL goes to zero and the result rolls into realized PnL.
+    /// The fill is recorded in the fill log under a fresh order id. When
+    /// already flat this is just `finalize`.
+    pub fn finalize_at(&mut self, final_price: f64) -> Result<FinalReport, EngineError> {
+        let price_tick = self.mark_price_tick(final_price)?;
+        let mut report = self.finalize();
+        self.close_position_at_tick(price_tick)?;
+        report.snapshot = self.get_snapshot();
+        Ok(report)
+    }
+
+    fn close_position_at_tick(&mut self, price_tick: i64) -> Result<(), EngineError> {
+        self.invalidate_snapshot();
+        let result = unsafe { engine_close_position(self.handle, self.next_order_id, price_tick) };
+        if result < 0 {
+            return Err(EngineError::ClosePositionFailed(result));
+        }
+        if result == 0 {
+            return Ok(());
+        }
+
+        self.next_order_id += 1;
+        self.drain_fills();
+        self.resume_fill_callback_panic();
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::ClosePosition { price_tick });
+        }
+        self.notify_snapshot_change();
+        Ok(())
+    }
+
+    /// Move the engine clock forward without a trade
+    ///
+    /// Releases latency-queued orders and expires good-till-date orders as of
+    /// `ts_ms`, then marks the position at the last traded price. No orders are
+    /// matched, since there is no new price to match against. Appends to the
+    /// equity log like a tick would.
+    pub fn advance_clock(&mut self, ts_ms: i64) -> Result<(), EngineError> {
+        if self.funding_schedule.is_some() {
+            self.apply_due_funding(ts_ms)?;
+        }
+        if !self.pending_orders.is_empty() {
+            self.release_pending_orders(ts_ms)?;
+        }
+        if !self.gtd_orders.is_empty() {
+            self.expire_orders(ts_ms);
+        }
+
+        self.invalidate_snapshot();
+        let result = unsafe { engine_advance_clock(self.handle, ts_ms) };
+        if result < 0 {
+            return Err(EngineError::AdvanceClockFailed(result));
+        }
+
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::AdvanceClock { ts_ms });
+        }
+        self.record_equity();
+        self.notify_snapshot_change();
+        Ok(())
+    }
+
+    pub fn step_tick(&mut self, ts_ms: i64, price_tick_i64: i64, qty: f64, side: &str) -> Result<(), EngineError> {
+        let side_enum = match side.to_uppercase().as_str() {
+            "BUY" => side_t::SIDE_BUY,
+            "SELL" => side_t::SIDE_SELL,
+            _ => return Err(EngineError::InvalidSide(side.to_string())),
+        };
+
+        let tick = tick_event_t {
+            ts_ms,
+            price_tick: price_tick_i64,
+            qty: (qty * 1000000.0) as i64, // Convert to integer representation
+            side: side_enum,
+        };
+
+        self.step_tick_raw(&tick)
+    }
+
+    /// Step one candle as synthetic ticks generated by `policy`
+    ///
+    /// The single-bar counterpart of [`market_event::replay`], for driving
+    /// the engine from a custom loop.
+    pub fn step_candle(&mut self, candle: &Candle, policy: BarToTickPolicy) -> Result<(), EngineError> {
+        self.step_candle_ticks(candle, policy, None)
+    }
+
+    /// [`Engine::step_candle`] with every synthetic tick carrying `aggressor`
+    /// instead of the side `policy` derives from the bar's direction
+    pub fn step_candle_with_side(
+        &mut self,
+        candle: &Candle,
+        policy: BarToTickPolicy,
+        aggressor: Side,
+    ) -> Result<(), EngineError> {
+        let side = match aggressor {
+            Side::Buy => side_t::SIDE_BUY,
+            Side::Sell => side_t::SIDE_SELL,
+        };
+        self.step_candle_ticks(candle, policy, Some(side))
+    }
+
+    /// Replay a candle file (format chosen by extension, quantized with this
+    /// engine's tick size), stepping each bar like [`Engine::step_candle`]
+    /// with [`BarToTickPolicy::Close`] and `aggressor` as every tick's side
+    ///
+    /// Stops at the first error; a file that cannot be opened or parsed is
+    /// an [`EngineError::Ingestion`].
+    pub fn run_file(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        aggressor: Side,
+    ) -> Result<market_event::IngestionSnapshot, EngineError> {
+        let parser = candle_parser::from_file_path(path, self.tick_size())?;
+        market_event::try_process_candles(parser, |event| match event {
+            market_event::MarketEvent::Bar(candle) => {
+                self.step_candle_with_side(&candle, BarToTickPolicy::Close, aggressor)
+            }
+            _ => Ok(()),
+        })
+    }
+
+    fn step_candle_ticks(
+        &mut self,
+        candle: &Candle,
+        policy: BarToTickPolicy,
+        aggressor: Option<side_t>,
+    ) -> Result<(), EngineError> {
+        for mut tick in policy.ticks(candle) {
+            if let Some(side) = aggressor {
+                tick.side = side;
+            }
+            self.step_tick_raw(&tick)?;
+        }
+        self.indicators.update(candle);
+        Ok(())
+    }
+
+    /// Process a batch of ticks efficiently - accepts integer sides (0=BUY, 1=SELL)
+    pub fn process_tick_batch(
+        &mut self,
+        timestamps: Vec<i64>,
+        price_ticks: Vec<i64>,
+        qtys: Vec<f64>,
+        sides: Vec<u8>,
+    ) -> Result<(), EngineError> {
+        self.process_tick_batch_with_policy(timestamps, price_ticks, qtys, sides, BatchErrorPolicy::AbortAtFirst)
+            .map(|_| ())
+    }
+
+    /// Process a batch of ticks, handling failing ticks (e.g. a side other
+    /// than 0 or 1) according to `policy`
+    ///
+    /// A length mismatch between the vectors is always an error, since no
+    /// tick can be trusted then.
+    pub fn process_tick_batch_with_policy(
+        &mut self,
+        timestamps: Vec<i64>,
+        price_ticks: Vec<i64>,
+        qtys: Vec<f64>,
+        sides: Vec<u8>,
+        policy: BatchErrorPolicy,
+    ) -> Result<BatchResult, EngineError> {
+        // Validate all vectors have same length
+        let n = timestamps.len();
+        if price_ticks.len() != n || qtys.len() != n || sides.len() != n {
+            return Err(EngineError::LengthMismatch {
+                timestamps: n,
+                price_ticks: price_ticks.len(),
+                qtys: qtys.len(),
+                sides: sides.len(),
+            });
+        }
+
+        let mut result = BatchResult::default();
+
+        // Process all ticks in the batch
+        for i in 0..n {
+            let stepped = match sides[i] {
+                0 => Ok(side_t::SIDE_BUY),
+                1 => Ok(side_t::SIDE_SELL),
+                _ => Err(EngineError::InvalidSideValue(sides[i])),
+            }
+            .and_then(|side| {
+                self.step_tick_raw(&tick_event_t {
+                    ts_ms: timestamps[i],
+                    price_tick: price_ticks[i],
+                    qty: (qtys[i] * 1000000.0) as i64, // Convert to integer representation
+                    side,
+                })
+            });
+
+            if stepped.is_err() && policy != BatchErrorPolicy::AbortAtFirst {
+                result.first_error_index.get_or_insert(i);
+            }
+            match (stepped, policy) {
+                (Ok(()), _) => result.processed += 1,
+                (Err(_), BatchErrorPolicy::SkipAndContinue) => result.skipped += 1,
+                (Err(_), BatchErrorPolicy::StopAndReport) => {
+                    result.failed_index = Some(i);
+                    break;
+                }
+                (Err(EngineError::StepFailed(code)), BatchErrorPolicy::AbortAtFirst) => {
+                    return Err(EngineError::BatchStepFailed { index: i, code });
+                }
+                (Err(e), BatchErrorPolicy::AbortAtFirst) => return Err(e),
+            }
+        }
+
+        log_event!(
+            debug,
+            ticks = n,
+            processed = result.processed,
+            skipped = result.skipped,
+            failed_index = ?result.failed_index,
+            "tick batch processed"
+        );
+        Ok(result)
+    }
+
+    pub fn place_order(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+    ) -> Result<u64, EngineError> {
+        self.place_order_with_tag(order_type, side, qty, price, 0)
+    }
+
+    /// Place an order carrying a caller-defined tag
+    ///
+    /// The engine never interprets `client_tag`; it is echoed back in
+    /// [`Fill::client_tag`] and [`OpenOrder::client_tag`] so fills can be
+    /// correlated with the strategy's own intent (entry, take-profit, ...).
+    pub fn place_order_with_tag(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+        client_tag: u64,
+    ) -> Result<u64, EngineError> {
+        self.submit_order(order_type, side, qty, price, client_tag, TimeInForce::Gtc, false)
+    }
+
+    /// Place an order that is cancelled automatically once the engine clock
+    /// (a tick or `advance_clock`) moves past `valid_until_ms`
+    pub fn place_order_gtd(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+        valid_until_ms: i64,
+    ) -> Result<u64, EngineError> {
+        self.submit_order(order_type, side, qty, price, 0, TimeInForce::Gtd(valid_until_ms), false)
+    }
+
+    /// Place an order with the given [`TimeInForce`]
+    ///
+    /// An `Ioc` or `Fok` order is matched against the first tick after it
+    /// reaches the matcher and cancelled if that tick did not fill it. A `Fok`
+    /// limit that would not fill against a trade at the last price is
+    /// rejected with [`RejectReason::FokNotMarketable`] instead.
+    pub fn place_order_tif(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+        time_in_force: TimeInForce,
+    ) -> Result<u64, EngineError> {
+        self.submit_order(order_type, side, qty, price, 0, time_in_force, false)
+    }
+
+    /// Place an order that may only shrink the current position
+    ///
+    /// Rejected with [`EngineError::ReduceOnlyNoPosition`] while flat or when
+    /// `side` would add to the position. The quantity is capped at the
+    /// position now and capped again at fill time, so it can never flip the
+    /// position; if the position is gone by then, the order is cancelled
+    /// without filling.
+    pub fn place_order_reduce_only(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+    ) -> Result<u64, EngineError> {
+        self.submit_order(order_type, side, qty, price, 0, TimeInForce::Gtc, true)
+    }
+
+    /// Quantity (scaled by 1e6) an order on `side` can fill without growing
+    /// or flipping the position
+    fn reducible_qty(&self, side: side_t) -> i64 {
+        let position = unsafe { engine_get_snapshot(self.handle) }.position;
+        match side {
+            side_t::SIDE_BUY => (-position).max(0),
+            side_t::SIDE_SELL => position.max(0),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn submit_order(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+        client_tag: u64,
+        time_in_force: TimeInForce,
+        reduce_only: bool,
+    ) -> Result<u64, EngineError> {
+        let type_enum = match order_type.to_uppercase().as_str() {
+            "MARKET" => order_type_t::ORDER_TYPE_MARKET,
+            "LIMIT" => order_type_t::ORDER_TYPE_LIMIT,
+            _ => return Err(EngineError::InvalidOrderType(order_type.to_string())),
+        };
+
+        let side_enum = match side.to_uppercase().as_str() {
+            "BUY" => side_t::SIDE_BUY,
+            "SELL" => side_t::SIDE_SELL,
+            _ => return Err(EngineError::InvalidSide(side.to_string())),
+        };
+
+        // NaN would otherwise quantize to tick 0 and infinities saturate
+        for (field, value) in [("price", price), ("qty", qty)] {
+            if !value.is_finite() {
+                return Err(EngineError::NonFiniteOrder { field, value });
+            }
+        }
+
+        let price_tick = self
+            .config
+            .rounding
+            .quantize_order(price, self.config.tick_size, side_enum.into());
+        let mut qty_i64 = (qty * 1000000.0) as i64;
+
+        if type_enum == order_type_t::ORDER_TYPE_LIMIT {
+            if let Some((last, band_bps)) = self.outside_band(price_tick) {
+                log_event!(
+                    warn,
+                    price_tick,
+                    last_price_tick = last,
+                    reason = RejectReason::PriceOutsideBand.as_str(),
+                    "order rejected"
+                );
+                return Err(EngineError::PriceOutsideBand {
+                    price: price_tick as f64 * self.config.tick_size,
+                    last_price: last as f64 * self.config.tick_size,
+                    band_bps,
+                });
+            }
+
+            if time_in_force == TimeInForce::Fok && !self.crosses_last_trade(side_enum, price_tick) {
+                log_event!(warn, price_tick, reason = RejectReason::FokNotMarketable.as_str(), "order rejected");
+                return Err(EngineError::OrderRejected(RejectReason::FokNotMarketable));
+            }
+        }
+
+        if let Some(err) = self.below_minimum(type_enum, qty_i64, price_tick) {
+            log_event!(
+                warn,
+                qty_scaled = qty_i64,
+                price_tick,
+                reason = RejectReason::BelowMinimum.as_str(),
+                "order rejected"
+            );
+            return Err(err);
+        }
+
+        if reduce_only {
+            let reducible = self.reducible_qty(side_enum);
+            if reducible == 0 {
+                log_event!(warn, reason = RejectReason::ReduceOnlyNoPosition.as_str(), "order rejected");
+                return Err(EngineError::ReduceOnlyNoPosition);
+            }
+            qty_i64 = qty_i64.min(reducible);
+        }
+
+        let order_id = self.next_order_id;
+        let order = order_t {
+            order_id,
+            type_: type_enum,
+            side: side_enum,
+            qty: qty_i64,
+            price_tick,
+            client_tag,
+            reduce_only: reduce_only as i32,
+        };
+
+        if self.config.latency_ms > 0 {
+            // Hold the order until the engine clock reaches now + latency
+            let now = self.get_snapshot().ts_ms;
+            let release_ts = now.saturating_add(self.config.latency_ms);
+            let idx = self.pending_orders.partition_point(|&(ts, _)| ts <= release_ts);
+            self.pending_orders.insert(idx, (release_ts, order));
+        } else {
+            self.place_order_raw(&order)?;
+        }
+
+        log_event!(
+            trace,
+            order_id,
+            order_type = order_type,
+            side = side,
+            qty,
+            price_tick,
+            "order placed"
+        );
+
+        if let (Some(tracker), order_type_t::ORDER_TYPE_LIMIT) = (&mut self.queue_tracker, type_enum) {
+            tracker.on_place(order_id, price_tick);
+        }
+
+        match time_in_force {
+            TimeInForce::Gtc => {}
+            TimeInForce::Ioc | TimeInForce::Fok => self.ioc_orders.push(order_id),
+            TimeInForce::Gtd(valid_until) => self.gtd_orders.push((valid_until, order_id)),
+        }
+        if let Some(log) = &mut self.event_log {
+            log.push(EngineCommand::PlaceOrder {
+                order_id,
+                order_type: type_enum.into(),
+                side: side_enum.into(),
+                qty,
+                price,
+                client_tag,
+                time_in_force,
+                reduce_only,
+            });
+        }
+
+        self.next_order_id += 1;
+        Ok(order_id)
+    }
+
+    /// Orders not yet filled or cancelled, by order id
+    ///
+    /// Includes orders still waiting out `latency_ms` (flagged `pending`).
+    pub fn open_orders(&self) -> Vec<OpenOrder> {
+        let mut raw: Vec<order_t> = Vec::with_capacity(MAX_OPEN_ORDERS);
+        let n = unsafe { engine_get_open_orders(self.handle, raw.as_mut_ptr(), MAX_OPEN_ORDERS as i32) };
+        // SAFETY: the engine initialized the first n entries
+        unsafe { raw.set_len(n as usize) };
+
+        let live = raw.iter().map(|order| (order, false));
+        let pending = self.pending_orders.iter().map(|(_, order)| (order, true));
+
+        let mut orders: Vec<OpenOrder> = live
+            .chain(pending)
+            .map(|(order, pending)| OpenOrder {
+                order_id: order.order_id,
+                client_tag: order.client_tag,
+                order_type: order.type_.into(),
+                side: order.side.into(),
+                qty: order.qty as f64 / 1000000.0,
+                price_tick: order.price_tick,
+                pending,
+                reduce_only: order.reduce_only != 0,
+            })
+            .collect();
+        orders.sort_by_key(|order| order.order_id);
+        orders
+    }
+
+    /// The best `levels` price levels of resting limit orders on each side
+    ///
+    /// The engine sees trades, not the market's book, so the only depth it
+    /// models is this strategy's own resting orders: each level sums the
+    /// limit orders at that price. Market orders and orders still waiting
+    /// out `latency_ms` are not resting and are left out.
+    pub fn book_snapshot(&self, levels: usize) -> BookSnapshot {
+        let mut bids: BTreeMap<i64, i64> = BTreeMap::new();
+        let mut asks: BTreeMap<i64, i64> = BTreeMap::new();
+
+        let mut raw: Vec<order_t> = Vec::with_capacity(MAX_OPEN_ORDERS);
+        let n = unsafe { engine_get_open_orders(self.handle, raw.as_mut_ptr(), MAX_OPEN_ORDERS as i32) };
+        // SAFETY: the engine initialized the first n entries
+        unsafe { raw.set_len(n as usize) };
+
+        for order in raw.iter().filter(|order| order.type_ == order_type_t::ORDER_TYPE_LIMIT) {
+            let side = match order.side {
+                side_t::SIDE_BUY => &mut bids,
+                side_t::SIDE_SELL => &mut asks,
+            };
+            *side.entry(order.price_tick).or_insert(0) += order.qty;
+        }
+
+        let tick_size = self.config.tick_size;
+        let level = |(&price_tick, &qty): (&i64, &i64)| (price_tick as f64 * tick_size, qty as f64 / 1000000.0);
+        BookSnapshot {
+            bids: bids.iter().rev().take(levels).map(level).collect(),
+            asks: asks.iter().take(levels).map(level).collect(),
+        }
+    }
+
+    /// Time `n_ticks` deterministic synthetic ticks through the engine
+    ///
+    /// Runs on a fresh engine with this engine's config, so the current state
+    /// is left untouched. Tick generation is excluded from the timing; what is
+    /// measured is the per-tick FFI call plus matching and fill collection.
+    pub fn benchmark(&self, n_ticks: usize) -> Result<BenchResult, EngineError> {
+        let ticks = synthetic_ticks(n_ticks);
+        let mut scratch = Engine::with_config(self.config)?;
+
+        let start = std::time::Instant::now();
+        for tick in &ticks {
+            scratch.step_tick_raw(tick)?;
+        }
+        let elapsed = start.elapsed();
+
+        let secs = elapsed.as_secs_f64();
+        Ok(BenchResult {
+            n_ticks,
+            elapsed,
+            ticks_per_sec: if secs > 0.0 { n_ticks as f64 / secs } else { f64::INFINITY },
+            avg_ns_per_tick: if n_ticks > 0 { elapsed.as_nanos() as f64 / n_ticks as f64 } else { 0.0 },
+        })
+    }
+
+    /// Current account state
+    ///
+    /// Served from a cache while nothing has touched the core since the last
+    /// call, so repeated reads between ticks cost no FFI round trip.
+    pub fn get_snapshot(&self) -> Snapshot {
+        if let Some(snapshot) = self.snapshot_cache.get() {
+            return snapshot;
+        }
+
+        let snap = unsafe { engine_get_snapshot(self.handle) };
+        let snapshot = Snapshot {
+            ts_ms: snap.ts_ms,
+            cash: snap.cash,
+            position: snap.position as f64 / 1000000.0, // Convert back from integer
+            avg_entry_price: snap.avg_entry_price,
+            realized_pnl: snap.realized_pnl,
+            unrealized_pnl: snap.unrealized_pnl,
+            equity: snap.equity,
+            cumulative_volume: self.cumulative_volume,
+            turnover: self.turnover,
+        };
+        self.snapshot_cache.set(Some(snapshot));
+        snapshot
+    }
+
+    /// Drop the cached snapshot; call before any FFI call that mutates the core
+    #[inline]
+    fn invalidate_snapshot(&self) {
+        self.snapshot_cache.set(None);
+    }
+
+    /// Configuration the engine is running with
+    pub fn config(&self) -> EngineConfig {
+        self.config
+    }
+
+    /// Price of one tick, as configured
+    pub fn tick_size(&self) -> f64 {
+        self.config.tick_size
+    }
+
+    /// Check that prices quantized with `tick_size` can be fed to this engine
+    ///
+    /// Tick indices are only meaningful at the scale they were built with, so
+    /// anything replaying a parser's candles should call this first.
+    pub fn check_tick_size(&self, tick_size: f64) -> Result<(), EngineError> {
+        if tick_size != self.config.tick_size {
+            return Err(EngineError::TickSizeMismatch {
+                engine: self.config.tick_size,
+                parser: tick_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Current position in base units (negative = short)
+    ///
+    /// The single-field accessors below skip building anything beyond the
+    /// `Copy` snapshot; prefer them over `get_snapshot` in hot loops.
+    pub fn position(&self) -> f64 {
+        self.get_snapshot().position
+    }
+
+    /// Current cash balance
+    pub fn cash(&self) -> f64 {
+        self.get_snapshot().cash
+    }
+
+    /// Current equity as reported by the core
+    pub fn equity(&self) -> f64 {
+        self.get_snapshot().equity
+    }
+
+    /// Average entry price of the open position (0 when flat)
+    pub fn avg_entry_price(&self) -> f64 {
+        self.get_snapshot().avg_entry_price
+    }
+
+    /// Realized plus unrealized PnL if the position were marked at `price`
+    ///
+    /// `position * (price - avg_entry) + realized_pnl`, from the current
+    /// state without changing it. Useful for stress tests and scenarios.
+    pub fn pnl_at_price(&self, price: f64) -> f64 {
+        let snap = self.get_snapshot();
+        snap.realized_pnl + snap.unrealized_at(price, self.config.tick_size)
+    }
+
+    /// Equity if the position were marked at `price`: cash plus the
+    /// unrealized PnL at that price, as the core computes equity
+    pub fn equity_at_price(&self, price: f64) -> f64 {
+        let snap = self.get_snapshot();
+        snap.cash + snap.unrealized_at(price, self.config.tick_size)
+    }
+
+    /// Unrealized PnL of the current position if marked at `price`; state
+    /// is unchanged
+    pub fn unrealized_at(&self, price: f64) -> f64 {
+        self.get_snapshot().unrealized_at(price, self.config.tick_size)
+    }
+
+    /// Quantity worth `notional` at `price`, capped at current equity
+    ///
+    /// `price` is first rounded to the tick grid the order would fill on,
+    /// and the result is rounded down to the engine's 1e-6 quantity step,
+    /// so the order never costs more than asked. Returns 0 when equity is
+    /// not positive or the inputs are not positive and finite. Nothing is
+    /// placed.
+    pub fn size_for_notional(&self, notional: f64, price: f64) -> f64 {
+        let tick_size = self.config.tick_size;
+        let price = candle::price_to_tick(price, tick_size) as f64 * tick_size;
+        if !(notional > 0.0 && price > 0.0) {
+            return 0.0;
+        }
+        floor_qty(notional.min(self.equity()) / price)
+    }
+
+    /// Quantity that loses `risk_fraction` of current equity if price moves
+    /// `stop_distance` against it
+    ///
+    /// The stop distance is rounded to whole ticks (at least one) and the
+    /// result down to the 1e-6 quantity step. Returns 0 when equity is not
+    /// positive or the inputs are not positive and finite.
+    pub fn size_for_risk(&self, risk_fraction: f64, stop_distance: f64) -> f64 {
+        let tick_size = self.config.tick_size;
+        if !(risk_fraction > 0.0 && stop_distance > 0.0 && stop_distance.is_finite()) {
+            return 0.0;
+        }
+        let stop = candle::price_to_tick(stop_distance, tick_size).max(1) as f64 * tick_size;
+        floor_qty(self.equity() * risk_fraction / stop)
+    }
+}
+
+/// Round a quantity down to the engine's 1e-6 step, mapping anything not
+/// positive and finite to 0
+fn floor_qty(qty: f64) -> f64 {
+    if !(qty > 0.0 && qty.is_finite()) {
+        return 0.0;
+    }
+    // The epsilon keeps e.g. 0.3 from flooring to 0.299999
+    (qty * 1000000.0 + 1e-6).floor() / 1000000.0
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            self.clear_fill_callback();
+            unsafe { engine_free(self.handle) };
+            self.handle = ptr::null_mut();
+        }
+    }
+}
+
+// Ensure Engine is Send (safe to move between threads)
+unsafe impl Send for Engine {}
+
+/// Saved engine state, created by [`Engine::checkpoint`]
+pub struct EngineCheckpoint {
+    handle: *mut engine_handle_t,
+    config: EngineConfig,
+    next_order_id: u64,
+    fills: Vec<Fill>,
+    pending_orders: VecDeque<(i64, order_t)>,
+    gtd_orders: Vec<(i64, u64)>,
+    ioc_orders: Vec<u64>,
+    rejected_orders: Vec<RejectedOrder>,
+    equity_log: Option<Vec<(i64, f64)>>,
+    equity_log_cap: Option<usize>,
+    equity_log_sampling: EquitySampling,
+    equity_log_skipped: u64,
+    band_breaches: u64,
+    funding_schedule: Option<FundingSchedule>,
+    funding_log: Vec<FundingEvent>,
+    cumulative_volume: f64,
+    turnover: f64,
+    queue_tracker: Option<QueueTracker>,
+    event_log: Option<Vec<EngineCommand>>,
+    indicators: IndicatorSet,
+    drawdown: Option<DrawdownTracker>,
+}
+
+impl EngineCheckpoint {
+    /// Configuration the engine had when checkpointed
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+}
+
+impl Drop for EngineCheckpoint {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { engine_free(self.handle) };
+            self.handle = ptr::null_mut();
+        }
+    }
+}
+
+// The checkpoint handle is never mutated after creation
+unsafe impl Send for EngineCheckpoint {}
+unsafe impl Sync for EngineCheckpoint {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot {
+    pub ts_ms: i64,
+    pub cash: f64,
+    pub position: f64,
+    pub avg_entry_price: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub equity: f64,
+    /// Total quantity filled, buys and sells alike
+    pub cumulative_volume: f64,
+    /// Total notional filled (`qty * price`), before fees
+    pub turnover: f64,
+}
+
+impl Snapshot {
+    /// Position as the engine holds it, an integer scaled by 1e6
+    ///
+    /// Exact for any position the engine can hold, so flatness is
+    /// `position_scaled() == 0` rather than a float comparison.
+    pub fn position_scaled(&self) -> i64 {
+        (self.position * 1000000.0).round() as i64
+    }
+
+    /// Position in lots of `lot_size`, rounded to the nearest lot with
+    /// halves away from zero
+    ///
+    /// Computed in the scaled integer space, so a position of exactly N
+    /// lots always gives N. Compare `position_lots(l) * lot_scaled` with
+    /// [`position_scaled`](Self::position_scaled) to check for whole lots.
+    ///
+    /// # Panics
+    /// If `lot_size` is below 1e-6, the engine's quantity resolution.
+    pub fn position_lots(&self, lot_size: f64) -> i64 {
+        let lot = (lot_size * 1000000.0).round() as i128;
+        assert!(lot > 0, "lot size must be at least 1e-6");
+
+        let position = self.position_scaled() as i128;
+        let half = if position < 0 { -lot } else { lot };
+        ((2 * position + half) / (2 * lot)) as i64
+    }
+
+    /// Unrealized PnL this position would have at `price`
+    ///
+    /// `position * (price - entry)`, so positive for a long above its entry
+    /// and for a short below it, and 0 when flat. `tick_size` converts
+    /// `avg_entry_price`, which is in ticks, to a price. Uses no fees or
+    /// spread, like the engine's own mark.
+    pub fn unrealized_at(&self, price: f64, tick_size: f64) -> f64 {
+        if self.position_scaled() == 0 {
+            return 0.0;
+        }
+        self.position * (price - self.avg_entry_price * tick_size)
+    }
+}
+
+/// Fields that changed since the previous [`Engine::on_snapshot_change`]
+/// notification; unchanged fields are `None`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapshotDelta {
+    pub ts_ms: i64,
+    pub position: Option<f64>,
+    pub cash: Option<f64>,
+    pub equity: Option<f64>,
+}
+
+/// State behind [`Engine::on_fill`], reached from C through
+/// [`fill_trampoline`]
+struct FillCallback {
+    /// To convert fill prices; kept in step with the engine's config
+    tick_size: f64,
+    callback: Box<dyn FnMut(Fill) + Send>,
+    /// Panic raised by the callback, re-raised once back in Rust
+    panic: Option<Box<dyn std::any::Any + Send>>,
+}
+
+/// `fill_callback_t` handed to the C engine; `userdata` is a `FillCallback`
+unsafe extern "C" fn fill_trampoline(raw: *const fill_t, userdata: *mut c_void) {
+    // SAFETY: userdata is the FillCallback registered by on_fill, which
+    // outlives its registration, and raw points to the fill just recorded
+    let state = unsafe { &mut *(userdata as *mut FillCallback) };
+    if state.panic.is_some() {
+        return;
+    }
+
+    let fill = Fill::from_raw(unsafe { &*raw }, state.tick_size);
+    let callback = &mut state.callback;
+    // Unwinding into C is undefined, so the panic waits for the Rust side
+    if let Err(payload) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback(fill))) {
+        state.panic = Some(payload);
+    }
+}
+
+struct SnapshotWatch {
+    callback: Box<dyn FnMut(&SnapshotDelta) + Send>,
+    /// `(position, cash, equity)` as of the last notification
+    last: (f64, f64, f64),
+}
+
+// ========== Python Bindings ==========
+
+#[pyclass(name = "Engine")]
+struct PyEngine {
+    inner: Engine,
+}
+
+#[pymethods]
+impl PyEngine {
+    #[new]
+    #[pyo3(signature = (initial_cash=100_000.0, maker_fee=0.0001, taker_fee=0.0002, spread_bps=2.0, tick_size=0.01, latency_ms=0, initial_position=0.0, initial_entry_price=0.0, price_band_bps=None, borrow_fee_bps_per_day=0.0, min_order_qty=None, min_order_notional=None, limit_fill_model="touch", allow_rebates=false, max_fee_bps=MAX_FEE_BPS, max_rebate_bps=MAX_REBATE_BPS))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        initial_cash: f64,
+        maker_fee: f64,
+        taker_fee: f64,
+        spread_bps: f64,
+        tick_size: f64,
+        latency_ms: i64,
+        initial_position: f64,
+        initial_entry_price: f64,
+        price_band_bps: Option<f64>,
+        borrow_fee_bps_per_day: f64,
+        min_order_qty: Option<f64>,
+        min_order_notional: Option<f64>,
+        limit_fill_model: &str,
+        allow_rebates: bool,
+        max_fee_bps: f64,
+        max_rebate_bps: f64,
+    ) -> PyResult<Self> {
+        let fill_model = LimitFillModel::from_name(limit_fill_model).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "limit_fill_model must be 'touch' or 'through', got {:?}",
+                limit_fill_model
+            ))
+        })?;
+        let mut builder = Engine::builder()
+            .initial_cash(initial_cash)
+            .maker_fee_bps(maker_fee * 10000.0)
+            .taker_fee_bps(taker_fee * 10000.0)
+            .spread_bps(spread_bps)
+            .tick_size(tick_size)
+            .latency_ms(latency_ms)
+            .initial_position(initial_position, initial_entry_price)
+            .borrow_fee_bps_per_day(borrow_fee_bps_per_day)
+            .limit_fill_model(fill_model)
+            .allow_rebates(allow_rebates)
+            .max_fee_bps(max_fee_bps)
+            .max_rebate_bps(max_rebate_bps);
+        if let Some(band_bps) = price_band_bps {
+            builder = builder.price_band_bps(band_bps);
+        }
+        if let Some(min_qty) = min_order_qty {
+            builder = builder.min_order_qty(min_qty);
+        }
+        if let Some(min_notional) = min_order_notional {
+            builder = builder.min_order_notional(min_notional);
+        }
+        let engine = builder.build()?;
+
+        Ok(PyEngine { inner: engine })
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn step_tick(&mut self, ts_ms: i64, price_tick_i64: i64, qty: f64, side: &str) -> PyResult<()> {
+        self.inner.step_tick(ts_ms, price_tick_i64, qty, side)?;
+        Ok(())
+    }
+
+    /// Step one candle (prices in ticks); `policy` is "close" (one tick at
+    /// the close) or "ohlc" (four ticks along the bar)
+    #[pyo3(signature = (ts_open, ts_close, open_tick, high_tick, low_tick, close_tick, volume, policy="close"))]
+    #[allow(clippy::too_many_arguments)]
+    fn step_candle(
+        &mut self,
+        ts_open: i64,
+        ts_close: i64,
+        open_tick: i64,
+        high_tick: i64,
+        low_tick: i64,
+        close_tick: i64,
+        volume: f64,
+        policy: &str,
+    ) -> PyResult<()> {
+        let policy = BarToTickPolicy::from_name(policy).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("policy must be 'close' or 'ohlc', got {:?}", policy))
+        })?;
+        let candle = Candle {
+            ts_open,
+            ts_close,
+            open_tick,
+            high_tick,
+            low_tick,
+            close_tick,
+            volume_scaled: (volume * 1000000.0).round() as i64,
+            trade_count: candle::TRADE_COUNT_UNKNOWN,
+        };
+        self.inner.step_candle(&candle, policy)?;
+        Ok(())
+    }
+
+    /// Process a batch of ticks; `on_error` is "abort" (raise), "skip" or "stop"
+    ///
+    /// Returns a `BatchResult` with `processed`, `skipped` and
+    /// `first_error_index` (None unless "skip" or "stop" met a failing tick).
+    #[pyo3(signature = (timestamps, price_ticks, qtys, sides, on_error="abort"))]
+    fn step_batch(
+        &mut self,
+        timestamps: Vec<i64>,
+        price_ticks: Vec<i64>,
+        qtys: Vec<f64>,
+        sides: Vec<u8>,
+        on_error: &str,
+    ) -> PyResult<PyBatchResult> {
+        let policy = BatchErrorPolicy::from_name(on_error).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "on_error must be 'abort', 'skip' or 'stop', got {:?}",
+                on_error
+            ))
+        })?;
+        let result = self
+            .inner
+            .process_tick_batch_with_policy(timestamps, price_ticks, qtys, sides, policy)?;
+
+        Ok(PyBatchResult {
+            processed: result.processed,
+            skipped: result.skipped,
+            first_error_index: result.first_error_index,
+        })
+    }
+
+    /// Place an order; `time_in_force` is "GTC", "IOC" or "FOK", and
+    /// `valid_until_ms` makes a GTC order good-till-date
+    #[pyo3(signature = (order_type, side, qty, price, client_tag=0, valid_until_ms=None, reduce_only=false, time_in_force="GTC"))]
+    #[allow(clippy::too_many_arguments)]
+    fn place_order(
+        &mut self,
+        order_type: &str,
+        side: &str,
+        qty: f64,
+        price: f64,
+        client_tag: u64,
+        valid_until_ms: Option<i64>,
+        reduce_only: bool,
+        time_in_force: &str,
+    ) -> PyResult<u64> {
+        let time_in_force = match (TimeInForce::from_name(time_in_force), valid_until_ms) {
+            (Some(TimeInForce::Gtc), Some(valid_until)) => TimeInForce::Gtd(valid_until),
+            (Some(tif), None) => tif,
+            (Some(_), Some(_)) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "valid_until_ms only applies to GTC orders",
+                ))
+            }
+            (None, _) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "time_in_force must be 'GTC', 'IOC' or 'FOK', got {:?}",
+                    time_in_force
+                )))
+            }
+        };
+        Ok(self
+            .inner
+            .submit_order(order_type, side, qty, price, client_tag, time_in_force, reduce_only)?)
+    }
+
+    /// Place many orders in one call, from parallel lists
+    ///
+    /// Each order goes through the same validation as `place_order`. A
+    /// rejected order is handled by `on_error`: 'abort' raises (orders
+    /// before it stay placed), 'skip' puts None in its slot and goes on, and
+    /// 'stop' returns the ids placed before it.
+    #[pyo3(signature = (order_types, sides, qtys, prices, on_error="abort"))]
+    fn place_orders(
+        &mut self,
+        order_types: Vec<String>,
+        sides: Vec<String>,
+        qtys: Vec<f64>,
+        prices: Vec<f64>,
+        on_error: &str,
+    ) -> PyResult<Vec<Option<u64>>> {
+        let policy = BatchErrorPolicy::from_name(on_error).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "on_error must be 'abort', 'skip' or 'stop', got {:?}",
+                on_error
+            ))
+        })?;
+        let n = order_types.len();
+        if sides.len() != n || qtys.len() != n || prices.len() != n {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "length mismatch: order_types={}, sides={}, qtys={}, prices={}",
+                n,
+                sides.len(),
+                qtys.len(),
+                prices.len()
+            )));
+        }
+
+        let mut ids = Vec::with_capacity(n);
+        for i in 0..n {
+            match self.inner.place_order(&order_types[i], &sides[i], qtys[i], prices[i]) {
+                Ok(id) => ids.push(Some(id)),
+                Err(_) if policy == BatchErrorPolicy::SkipAndContinue => ids.push(None),
+                Err(_) if policy == BatchErrorPolicy::StopAndReport => break,
+                Err(e) => {
+                    return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("order {}: {}", i, e)));
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    fn cancel_order(&mut self, order_id: u64) -> PyResult<()> {
+        self.inner.cancel_order(order_id)?;
+        Ok(())
+    }
+
+    fn advance_clock(&mut self, ts_ms: i64) -> PyResult<()> {
+        self.inner.advance_clock(ts_ms)?;
+        Ok(())
+    }
+
+    /// Open orders as a list of dicts, by order id
+    fn get_open_orders(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let tick_size = self.inner.config.tick_size;
+        self.inner
+            .open_orders()
+            .iter()
+            .map(|order| open_order_dict(py, order, tick_size))
+            .collect()
+    }
+
+    /// Cancel all dangling orders at the end of a run, closing the position
+    /// at `final_price` if given; returns the final snapshot dict with the
+    /// cancelled orders under "cancelled"
+    #[pyo3(signature = (final_price=None))]
+    fn finalize(&mut self, py: Python<'_>, final_price: Option<f64>) -> PyResult<PyObject> {
+        let report = match final_price {
+            Some(price) => self.inner.finalize_at(price)?,
+            None => self.inner.finalize(),
+        };
+        let tick_size = self.inner.config.tick_size;
+
+        let dict = pyo3::types::PyDict::new_bound(py);
+        for (key, value) in snapshot_dict(&report.snapshot) {
+            dict.set_item(key, value)?;
+        }
+        let cancelled = report
+            .cancelled
+            .iter()
+            .map(|order| open_order_dict(py, order, tick_size))
+            .collect::<PyResult<Vec<_>>>()?;
+        dict.set_item("cancelled", cancelled)?;
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Fill log as a list of dicts with the effective (spread-inclusive) price
+    fn get_fills(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.inner
+            .fills()
+            .iter()
+            .map(|fill| {
+                let dict = pyo3::types::PyDict::new_bound(py);
+                dict.set_item("order_id", fill.order_id)?;
+                dict.set_item("client_tag", fill.client_tag)?;
+                dict.set_item("ts_ms", fill.ts_ms)?;
+                dict.set_item("side", fill.side.as_str())?;
+                dict.set_item("qty", fill.qty)?;
+                dict.set_item("price", fill.price)?;
+                dict.set_item("fee", fill.fee)?;
+                Ok(dict.into_any().unbind())
+            })
+            .collect()
+    }
+
+    /// Record equity after every tick, keeping at most `max_points` if given
+    ///
+    /// `every_n` logs only every nth tick and `every_ms` only once per that
+    /// much simulated time; at most one of them may be given.
+    #[pyo3(signature = (max_points=None, every_n=None, every_ms=None))]
+    fn enable_equity_log(&mut self, max_points: Option<usize>, every_n: Option<u64>, every_ms: Option<i64>) -> PyResult<()> {
+        let sampling = match (every_n, every_ms) {
+            (None, None) => EquitySampling::EveryTick,
+            (Some(n), None) => EquitySampling::EveryEvents(n),
+            (None, Some(ms)) => EquitySampling::EveryMs(ms),
+            (Some(_), Some(_)) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "pass at most one of every_n and every_ms",
+                ))
+            }
+        };
+
+        match max_points {
+            Some(max_points) => self.inner.enable_bounded_equity_log(max_points),
+            None => self.inner.enable_equity_log(),
+        }
+        self.inner.enable_sampled_equity_log(sampling);
+        Ok(())
+    }
+
+    /// Equity curve as `(ts_ms, equity)` tuples, min/max-downsampled to
+    /// `max_points` if given
+    #[pyo3(signature = (max_points=None))]
+    fn get_equity_curve(&self, max_points: Option<usize>) -> Vec<(i64, f64)> {
+        let curve = self.inner.equity_curve();
+        match max_points {
+            Some(max_points) => metrics::downsample_curve(curve, max_points),
+            None => curve.to_vec(),
+        }
+    }
+
+    /// Start tracking drawdown from the equity after every tick
+    fn enable_drawdown_tracking(&mut self) {
+        self.inner.enable_drawdown_tracking();
+    }
+
+    /// Decline of equity from its peak as a fraction, or None unless tracking
+    fn current_drawdown(&self) -> Option<f64> {
+        self.inner.drawdown().map(DrawdownTracker::current_drawdown)
+    }
+
+    /// Largest drawdown so far as a fraction, or None unless tracking
+    fn max_drawdown(&self) -> Option<f64> {
+        self.inner.drawdown().map(DrawdownTracker::max_drawdown)
+    }
+
+    /// Pay or receive one funding period at the last price; returns the cash change
+    fn apply_funding(&mut self, rate: f64) -> PyResult<f64> {
+        Ok(self.inner.apply_funding(rate)?)
+    }
+
+    /// Apply funding automatically at each `(ts_ms, rate)` as the clock passes it
+    fn set_funding_schedule(&mut self, payments: Vec<(i64, f64)>) -> PyResult<()> {
+        Ok(self.inner.set_funding_schedule(FundingSchedule::new(payments))?)
+    }
+
+    /// Funding payments applied so far as a list of dicts
+    fn get_funding_events(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.inner
+            .funding_events()
+            .iter()
+            .map(|event| {
+                let dict = pyo3::types::PyDict::new_bound(py);
+                dict.set_item("ts_ms", event.ts_ms)?;
+                dict.set_item("rate", event.rate)?;
+                dict.set_item("mark_price", event.mark_price)?;
+                dict.set_item("position", event.position)?;
+                dict.set_item("amount", event.amount)?;
+                Ok(dict.into_any().unbind())
+            })
+            .collect()
+    }
+
+    /// Latency-delayed orders refused on release as a list of dicts
+    fn get_rejected_orders(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.inner
+            .rejected_orders()
+            .iter()
+            .map(|rejected| {
+                let dict = pyo3::types::PyDict::new_bound(py);
+                dict.set_item("order_id", rejected.order_id)?;
+                dict.set_item("client_tag", rejected.client_tag)?;
+                dict.set_item("ts_ms", rejected.ts_ms)?;
+                dict.set_item("reason", rejected.reason.as_str())?;
+                Ok(dict.into_any().unbind())
+            })
+            .collect()
+    }
+
+    /// Replay a candle file through the engine, one tick per bar
+    ///
+    /// `on_progress(events, ts_ms, eta_secs)` is called every `progress_every`
+    /// candles; `eta_secs` is `None` when the file size in candles is unknown.
+    /// Returns the number of candles processed.
+    #[pyo3(signature = (path, progress_every=10_000, on_progress=None))]
+    fn replay_file(
+        &mut self,
+        py: Python<'_>,
+        path: &str,
+        progress_every: u64,
+        on_progress: Option<PyObject>,
+    ) -> PyResult<u64> {
+        let parser = candle_parser::from_file_path(path, self.inner.tick_size()).map_err(parse_error_to_py)?;
+
+        let mut callback_err: Option<PyErr> = None;
+        let reporter = |progress: &market_event::Progress| {
+            if let (Some(callback), None) = (&on_progress, &callback_err) {
+                let eta = progress.eta.map(|eta| eta.as_secs_f64());
+                if let Err(e) = callback.call1(py, (progress.events, progress.ts_ms, eta)) {
+                    callback_err = Some(e);
+                }
+            }
+        };
+        let parser = market_event::ProgressParser::new(parser, progress_every, reporter);
+        let snapshot = market_event::replay(parser, &mut self.inner)?;
+
+        match callback_err {
+            Some(e) => Err(e),
+            None => Ok(snapshot.candles_processed),
+        }
+    }
+
+    /// Load a candle file and run it through the engine in one call
+    ///
+    /// Each bar is stepped like `step_candle` with the "close" policy (one
+    /// tick at the close carrying the bar's volume, indicators updated), with
+    /// `default_side` as the aggressor. Returns a summary dict with
+    /// `events` (bars processed) and `final_equity`. Errors name the file.
+    #[pyo3(signature = (path, default_side="BUY"))]
+    fn run_file(&mut self, py: Python<'_>, path: &str, default_side: &str) -> PyResult<PyObject> {
+        let side = match default_side.to_uppercase().as_str() {
+            "BUY" => Side::Buy,
+            "SELL" => Side::Sell,
+            _ => return Err(EngineError::InvalidSide(default_side.to_string()).into()),
+        };
+        let with_path = |err: PyErr| {
+            let message = format!("{}: {}", path, err.value_bound(py));
+            if err.is_instance_of::<pyo3::exceptions::PyOSError>(py) {
+                pyo3::exceptions::PyIOError::new_err(message)
+            } else if err.is_instance_of::<pyo3::exceptions::PyValueError>(py) {
+                pyo3::exceptions::PyValueError::new_err(message)
+            } else {
+                pyo3::exceptions::PyRuntimeError::new_err(message)
+            }
+        };
+
+        let metrics = self.inner.run_file(path, side).map_err(|e| match e {
+            EngineError::Ingestion(parse_err) => with_path(parse_error_to_py(parse_err)),
+            other => with_path(other.into()),
+        })?;
+
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("events", metrics.candles_processed)?;
+        dict.set_item("final_equity", self.inner.equity())?;
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Time n synthetic ticks on a scratch engine; returns throughput stats
+    fn benchmark(&self, n: usize) -> PyResult<HashMap<String, f64>> {
+        let bench = self.inner.benchmark(n)?;
+
+        let mut result = HashMap::new();
+        result.insert("n_ticks".to_string(), bench.n_ticks as f64);
+        result.insert("elapsed_s".to_string(), bench.elapsed.as_secs_f64());
+        result.insert("ticks_per_sec".to_string(), bench.ticks_per_sec);
+        result.insert("avg_ns_per_tick".to_string(), bench.avg_ns_per_tick);
+
+        Ok(result)
+    }
+
+    /// Start estimating queue positions of limit orders placed from now on
+    fn enable_queue_tracking(&mut self) {
+        self.inner.enable_queue_tracking();
+    }
+
+    /// Estimated quantity ahead of a resting limit order, or None if untracked
+    fn queue_position(&self, order_id: u64) -> Option<f64> {
+        self.inner.queue_position(order_id).map(|ahead| ahead as f64 / 1000000.0)
+    }
+
+    /// Configuration as keyword arguments, so `Engine(**e.get_config())` builds an equivalent engine
+    fn get_config(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let config = self.inner.config();
+
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("initial_cash", config.initial_cash)?;
+        dict.set_item("maker_fee", config.maker_fee_bps / 10000.0)?;
+        dict.set_item("taker_fee", config.taker_fee_bps / 10000.0)?;
+        dict.set_item("spread_bps", config.spread_bps)?;
+        dict.set_item("tick_size", config.tick_size)?;
+        dict.set_item("latency_ms", config.latency_ms)?;
+        dict.set_item("initial_position", config.initial_position)?;
+        dict.set_item("initial_entry_price", config.initial_entry_price)?;
+        dict.set_item("price_band_bps", config.price_band_bps)?;
+        dict.set_item("borrow_fee_bps_per_day", config.borrow_fee_bps_per_day)?;
+        dict.set_item("min_order_qty", config.min_order_qty)?;
+        dict.set_item("min_order_notional", config.min_order_notional)?;
+        dict.set_item("limit_fill_model", config.limit_fill_model.name())?;
+        dict.set_item("allow_rebates", config.allow_rebates)?;
+        dict.set_item("max_fee_bps", config.max_fee_bps)?;
+        dict.set_item("max_rebate_bps", config.max_rebate_bps)?;
+        Ok(dict.into_any().unbind())
+    }
+
+    fn get_snapshot(&self) -> PyResult<HashMap<String, f64>> {
+        Ok(snapshot_dict(&self.inner.get_snapshot()))
+    }
+
+    /// Current position without building the snapshot dict
+    fn position(&self) -> f64 {
+        self.inner.position()
+    }
+
+    /// Current cash without building the snapshot dict
+    fn cash(&self) -> f64 {
+        self.inner.cash()
+    }
+
+    /// Current equity without building the snapshot dict
+    fn equity(&self) -> f64 {
+        self.inner.equity()
+    }
+
+    /// Average entry price without building the snapshot dict
+    fn avg_entry_price(&self) -> f64 {
+        self.inner.avg_entry_price()
+    }
+
+    /// Realized plus unrealized PnL if marked at `price`; state is unchanged
+    fn pnl_at_price(&self, price: f64) -> f64 {
+        self.inner.pnl_at_price(price)
+    }
+
+    /// Equity if marked at `price`; state is unchanged
+    fn equity_at_price(&self, price: f64) -> f64 {
+        self.inner.equity_at_price(price)
+    }
+
+    /// Unrealized PnL if marked at `price`; state is unchanged
+    fn unrealized_at(&self, price: f64) -> f64 {
+        self.inner.unrealized_at(price)
+    }
+
+    /// Best `levels` resting limit levels per side as {"bids": [(price, qty)], "asks": [...]},
+    /// bids descending and asks ascending
+    #[pyo3(signature = (levels=10))]
+    fn get_book(&self, levels: usize) -> HashMap<String, Vec<(f64, f64)>> {
+        let book = self.inner.book_snapshot(levels);
+        HashMap::from([("bids".to_string(), book.bids), ("asks".to_string(), book.asks)])
+    }
+
+    /// Mark the position at an external price without a trade; returns the snapshot dict
+    fn mark_to(&mut self, price: f64) -> PyResult<HashMap<String, f64>> {
+        Ok(snapshot_dict(&self.inner.mark_to(price)?))
+    }
+}
+
+/// Snapshot as the dict returned by `Engine.get_snapshot` in Python
+fn snapshot_dict(snap: &Snapshot) -> HashMap<String, f64> {
+    let mut result = HashMap::new();
+    result.insert("cash".to_string(), snap.cash);
+    result.insert("position".to_string(), snap.position);
+    result.insert("avg_entry_price".to_string(), snap.avg_entry_price);
+    result.insert("realized_pnl".to_string(), snap.realized_pnl);
+    result.insert("unrealized_pnl".to_string(), snap.unrealized_pnl);
+    result.insert("equity".to_string(), snap.equity);
+    result.insert("cumulative_volume".to_string(), snap.cumulative_volume);
+    result.insert("turnover".to_string(), snap.turnover);
+    result
+}
+
+/// Open order as a dict in `Engine.get_open_orders` form, price in currency units
+fn open_order_dict(py: Python<'_>, order: &OpenOrder, tick_size: f64) -> PyResult<PyObject> {
+    let dict = pyo3::types::PyDict::new_bound(py);
+    dict.set_item("order_id", order.order_id)?;
+    dict.set_item("client_tag", order.client_tag)?;
+    dict.set_item("order_type", order.order_type.as_str())?;
+    dict.set_item("side", order.side.as_str())?;
+    dict.set_item("qty", order.qty)?;
+    dict.set_item("price", order.price_tick as f64 * tick_size)?;
+    dict.set_item("pending", order.pending)?;
+    dict.set_item("reduce_only", order.reduce_only)?;
+    Ok(dict.into_any().unbind())
+}
+
+/// Map parse errors to Python: I/O failures to `OSError`, bad data to `ValueError`
+fn parse_error_to_py(err: ParseError) -> PyErr {
+    match err {
+        ParseError::Io(e) => pyo3::exceptions::PyIOError::new_err(e.to_string()),
+        other => pyo3::exceptions::PyValueError::new_err(other.to_string()),
+    }
+}
+
+/// Streaming candle parser for Python, chosen by file extension (.csv, .json, .jsonl, .ndjson)
+///
+/// Iterating yields one dict per candle with float prices:
+/// `ts_open, ts_close, open, high, low, close, volume, trade_count`
+/// (`None` when the file has no trade counts). With `limit`, iteration
+/// stops after that many candles.
+#[pyclass(name = "CandleParser", unsendable)]
+struct PyCandleParser {
+    inner: Box<dyn candle_parser::CandleParser>,
+}
+
+#[pymethods]
+impl PyCandleParser {
+    #[new]
+    #[pyo3(signature = (path, tick_size, limit=None))]
+    fn new(path: &str, tick_size: f64, limit: Option<usize>) -> PyResult<Self> {
+        use candle_parser::CandleParser;
+
+        let mut inner = candle_parser::from_file_path(path, tick_size).map_err(parse_error_to_py)?;
+        if let Some(max) = limit {
+            inner = Box::new(inner.limit(max));
+        }
+        Ok(PyCandleParser { inner })
+    }
+
+    #[getter]
+    fn tick_size(&self) -> f64 {
+        self.inner.tick_size()
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let candle = match self.inner.next() {
+            Some(result) => result.map_err(parse_error_to_py)?,
+            None => return Ok(None),
+        };
+        let prices = candle.to_float_prices(self.inner.tick_size());
+
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("ts_open", prices.ts_open)?;
+        dict.set_item("ts_close", prices.ts_close)?;
+        dict.set_item("open", prices.open)?;
+        dict.set_item("high", prices.high)?;
+        dict.set_item("low", prices.low)?;
+        dict.set_item("close", prices.close)?;
+        dict.set_item("volume", prices.volume)?;
+        dict.set_item("trade_count", candle.known_trade_count())?;
+        Ok(Some(dict.into_any().unbind()))
+    }
+}
+
+/// Outcome of `Engine.step_batch`
+#[pyclass(name = "BatchResult", frozen, eq)]
+#[derive(Debug, Clone, PartialEq)]
+struct PyBatchResult {
+    /// Ticks applied to the engine
+    #[pyo3(get)]
+    processed: usize,
+    /// Failing ticks skipped under on_error="skip"
+    #[pyo3(get)]
+    skipped: usize,
+    /// Index of the first failing tick under "skip" or "stop", else None
+    #[pyo3(get)]
+    first_error_index: Option<usize>,
+}
+
+#[pymethods]
+impl PyBatchResult {
+    fn __repr__(&self) -> String {
+        let first_error_index = self.first_error_index.map_or("None".to_string(), |i| i.to_string());
+        format!(
+            "BatchResult(processed={}, skipped={}, first_error_index={})",
+            self.processed, self.skipped, first_error_index
+        )
+    }
+}
+
+/// Rolling N-bar high/low channel over tick prices
+#[pyclass(name = "DonchianChannel")]
+struct PyDonchianChannel {
+    inner: indicators::DonchianChannel,
+}
+
+#[pymethods]
+impl PyDonchianChannel {
+    #[new]
+    fn new(period: usize) -> PyResult<Self> {
+        if period == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("period must be positive"));
+        }
+        Ok(PyDonchianChannel {
+            inner: indicators::DonchianChannel::new(period),
+        })
+    }
+
+    #[getter]
+    fn period(&self) -> usize {
+        self.inner.period()
+    }
+
+    /// Add a bar's high and low ticks; returns (high, low) once the window is full
+    fn update(&mut self, high_tick: i64, low_tick: i64) -> Option<(i64, i64)> {
+        self.inner.update_ticks(high_tick, low_tick)
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// Summary statistics of a candle file, computed in one streaming pass
+///
+/// Returns a dict with `count, first_ts_ms, last_ts_ms, span_ms, min_price,
+/// max_price, total_volume, total_trades, avg_range`.
+#[pyfunction]
+fn summarize_candles(py: Python<'_>, path: &str, tick_size: f64) -> PyResult<PyObject> {
+    let parser = candle_parser::from_file_path(path, tick_size).map_err(parse_error_to_py)?;
+
+    let mut parse_err = None;
+    let candles = parser.map_while(|result| result.map_err(|e| parse_err = Some(e)).ok());
+    let summary = candle::summarize(candles, tick_size);
+    if let Some(e) = parse_err {
+        return Err(parse_error_to_py(e));
+    }
+
+    let dict = pyo3::types::PyDict::new_bound(py);
+    dict.set_item("count", summary.count)?;
+    dict.set_item("first_ts_ms", summary.first_ts_ms)?;
+    dict.set_item("last_ts_ms", summary.last_ts_ms)?;
+    dict.set_item("span_ms", summary.span_ms())?;
+    dict.set_item("min_price", summary.min_price)?;
+    dict.set_item("max_price", summary.max_price)?;
+    dict.set_item("total_volume", summary.total_volume)?;
+    dict.set_item("total_trades", summary.total_trades)?;
+    dict.set_item("avg_range", summary.avg_range)?;
+    Ok(dict.into_any().unbind())
+}
+
+/// Parse in-memory candle data (`format` is "csv", "json" or "parquet")
+///
+/// Parsing runs without the GIL. Returns a dict of equal-length lists with
+/// float prices: `ts_open, ts_close, open, high, low, close, volume,
+/// trade_count` (`None` entries where the data has no trade counts).
+#[pyfunction]
+fn parse_candles_bytes(py: Python<'_>, data: &[u8], format: &str, tick_size: f64) -> PyResult<PyObject> {
+    let data = data.to_vec();
+    let candles = py
+        .allow_threads(|| {
+            candle_parser::from_bytes(data, format, tick_size)?.collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(parse_error_to_py)?;
+
+    let prices: Vec<candle::CandleFloat> = candles.iter().map(|c| c.to_float_prices(tick_size)).collect();
+    let dict = pyo3::types::PyDict::new_bound(py);
+    dict.set_item("ts_open", prices.iter().map(|p| p.ts_open).collect::<Vec<_>>())?;
+    dict.set_item("ts_close", prices.iter().map(|p| p.ts_close).collect::<Vec<_>>())?;
+    dict.set_item("open", prices.iter().map(|p| p.open).collect::<Vec<_>>())?;
+    dict.set_item("high", prices.iter().map(|p| p.high).collect::<Vec<_>>())?;
+    dict.set_item("low", prices.iter().map(|p| p.low).collect::<Vec<_>>())?;
+    dict.set_item("close", prices.iter().map(|p| p.close).collect::<Vec<_>>())?;
+    dict.set_item("volume", prices.iter().map(|p| p.volume).collect::<Vec<_>>())?;
+    dict.set_item(
+        "trade_count",
+        candles.iter().map(|c| c.known_trade_count()).collect::<Vec<_>>(),
+    )?;
+    Ok(dict.into_any().unbind())
+}
+
+#[pymodule]
+fn _ag_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyEngine>()?;
+    m.add_class::<PyCandleParser>()?;
+    m.add_class::<PyBatchResult>()?;
+    m.add_class::<PyDonchianChannel>()?;
+    m.add_function(wrap_pyfunction!(summarize_candles, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_candles_bytes, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> EngineConfig {
+        EngineConfig {
+            initial_cash: 10_000.0,
+            maker_fee_bps: 0.0,
+            taker_fee_bps: 0.0,
+            spread_bps: 0.0,
+            tick_size: 0.01,
+            latency_ms: 0,
+            initial_position: 0.0,
+            initial_entry_price: 0.0,
+            rounding: RoundingMode::Nearest,
+            price_band_bps: None,
+            borrow_fee_bps_per_day: 0.0,
+            min_order_qty: None,
+            min_order_notional: None,
+            limit_fill_model: LimitFillModel::OnTouch,
+            allow_rebates: false,
+            max_fee_bps: MAX_FEE_BPS,
+            max_rebate_bps: MAX_REBATE_BPS,
+        }
+    }
+
+    #[test]
+    fn test_config_validation_boundaries() {
+        let param_of = |config: EngineConfig| match Engine::with_config(config) {
+            Err(EngineError::InvalidConfig { param, .. }) => Some(param),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => None,
+        };
+
+        assert_eq!(param_of(EngineConfig { maker_fee_bps: MAX_FEE_BPS, ..test_config() }), None);
+        assert_eq!(param_of(EngineConfig { maker_fee_bps: 1_000.5, ..test_config() }), Some("maker_fee_bps"));
+        assert_eq!(param_of(EngineConfig { taker_fee_bps: f64::NAN, ..test_config() }), Some("taker_fee_bps"));
+        assert_eq!(param_of(EngineConfig { taker_fee_bps: -0.5, ..test_config() }), Some("taker_fee_bps"));
+
+        let rebates = EngineConfig { allow_rebates: true, ..test_config() };
+        assert_eq!(param_of(EngineConfig { maker_fee_bps: -0.5, ..rebates }), None);
+        assert_eq!(param_of(EngineConfig { maker_fee_bps: -MAX_REBATE_BPS, ..rebates }), None);
+        assert_eq!(param_of(EngineConfig { maker_fee_bps: -100.5, ..rebates }), Some("maker_fee_bps"));
+
+        assert_eq!(param_of(EngineConfig { spread_bps: 0.0, ..test_config() }), None);
+        assert_eq!(param_of(EngineConfig { spread_bps: -0.1, ..test_config() }), Some("spread_bps"));
+        assert_eq!(param_of(EngineConfig { initial_cash: 0.0, ..test_config() }), None);
+        assert_eq!(param_of(EngineConfig { initial_cash: -1.0, ..test_config() }), Some("initial_cash"));
+        assert_eq!(param_of(EngineConfig { initial_cash: f64::INFINITY, ..test_config() }), Some("initial_cash"));
+
+        // The band is configurable, and its own limits are checked
+        let tight = EngineConfig { max_fee_bps: 10.0, max_rebate_bps: 1.0, allow_rebates: true, ..test_config() };
+        assert_eq!(param_of(EngineConfig { taker_fee_bps: 10.0, ..tight }), None);
+        assert_eq!(param_of(EngineConfig { taker_fee_bps: 10.5, ..tight }), Some("taker_fee_bps"));
+        assert_eq!(param_of(EngineConfig { maker_fee_bps: -1.5, ..tight }), Some("maker_fee_bps"));
+        assert_eq!(param_of(EngineConfig { max_fee_bps: f64::NAN, ..test_config() }), Some("max_fee_bps"));
+        assert_eq!(param_of(EngineConfig { max_rebate_bps: -1.0, ..test_config() }), Some("max_rebate_bps"));
+        assert!(Engine::builder().max_fee_bps(5_000.0).taker_fee_bps(2_000.0).build().is_ok());
+
+        for tick_size in [0.0, -0.01, f64::NAN, f64::INFINITY] {
+            assert_eq!(param_of(EngineConfig { tick_size, ..test_config() }), Some("tick_size"), "{}", tick_size);
+        }
+
+        let err = Engine::builder().taker_fee_bps(-2.0).build().err().unwrap();
+        assert!(err.to_string().contains("taker_fee_bps"), "{}", err);
+        assert!(Engine::builder().taker_fee_bps(-2.0).allow_rebates(true).build().is_ok());
+
+        // A rejected config leaves the engine as it was
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        assert!(engine.reset_with_config(EngineConfig { spread_bps: -1.0, ..test_config() }).is_err());
+        assert_eq!(engine.config(), test_config());
+    }
+
+    #[test]
+    fn test_reset_with_config_flat() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        let new_config = EngineConfig {
+            initial_cash: 5_000.0,
+            tick_size: 0.5,
+            ..test_config()
+        };
+        engine.reset_with_config(new_config).unwrap();
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.cash, 5_000.0);
+        assert_eq!(snap.position, 0.0);
+    }
+
+    #[test]
+    fn test_reset_with_config_tick_size_change_with_position() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.get_snapshot().position, 1.0);
+
+        let new_config = EngineConfig { tick_size: 0.5, ..test_config() };
+        let result = engine.reset_with_config(new_config);
+        assert!(matches!(result, Err(EngineError::TickSizeChangeWithPosition { .. })));
+
+        // State is untouched by the refused reset
+        assert_eq!(engine.get_snapshot().position, 1.0);
+
+        // Same tick_size is fine even with a position
+        let same_tick = EngineConfig { initial_cash: 20_000.0, ..test_config() };
+        engine.reset_with_config(same_tick).unwrap();
+        assert_eq!(engine.get_snapshot().cash, 20_000.0);
+    }
+
+    #[test]
+    fn test_market_round_trip_costs_spread_plus_fees() {
+        let config = EngineConfig {
+            taker_fee_bps: 2.0,
+            spread_bps: 2.0,
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        // Mid at 100.00: half-spread of 1bp is one tick on each side
+        engine.mark_to(100.0).unwrap();
+        let buy_id = engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        let sell_id = engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+        engine.step_tick(1001, 10_000, 1.0, "BUY").unwrap();
+
+        let fills = engine.fills();
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].order_id, buy_id);
+        assert_eq!(fills[0].side, Side::Buy);
+        assert_eq!(fills[0].price_tick, 10_001);
+        assert_eq!(fills[1].order_id, sell_id);
+        assert_eq!(fills[1].price_tick, 9_999);
+
+        let spread_cost = 100.0 * 2.0 / 10_000.0;
+        let fees = fills[0].fee + fills[1].fee;
+        assert!((fees - (100.01 + 99.99) * 2.0 / 10_000.0).abs() < 1e-9);
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.position, 0.0);
+        let loss = config.initial_cash - snap.cash;
+        assert!((loss - (spread_cost + fees)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_latency_delays_order_eligibility() {
+        let mut engine = Engine::builder()
+            .initial_cash(10_000.0)
+            .maker_fee_bps(0.0)
+            .taker_fee_bps(0.0)
+            .spread_bps(0.0)
+            .tick_size(0.01)
+            .latency_ms(100)
+            .build()
+            .unwrap();
+
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+
+        // Favorable price arrives before the order reaches the engine
+        engine.step_tick(1050, 9_900, 1.0, "SELL").unwrap();
+        assert!(engine.fills().is_empty());
+        assert_eq!(engine.get_snapshot().position, 0.0);
+
+        // Order becomes eligible at 1000 + 100 and fills at the worse price
+        engine.step_tick(1100, 10_100, 1.0, "SELL").unwrap();
+        assert_eq!(engine.fills().len(), 1);
+        assert_eq!(engine.fills()[0].price_tick, 10_100);
+        assert_eq!(engine.get_snapshot().position, 1.0);
+    }
+
+    #[test]
+    fn test_delayed_order_rejected_on_release_still_steps_tick() {
+        let mut engine = Engine::builder()
+            .initial_cash(10_000.0)
+            .maker_fee_bps(0.0)
+            .taker_fee_bps(0.0)
+            .spread_bps(0.0)
+            .tick_size(0.01)
+            .latency_ms(100)
+            .build()
+            .unwrap();
+
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        let order_id = engine.place_order_with_tag("MARKET", "BUY", 95.0, 0.0, 7).unwrap();
+
+        // A rally during the latency window puts 95 units out of reach
+        engine.step_tick(1050, 11_000, 1.0, "BUY").unwrap();
+        engine.step_tick(1100, 11_000, 1.0, "SELL").unwrap();
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.ts_ms, 1100);
+        assert_eq!(snap.position, 0.0);
+        assert!(engine.fills().is_empty());
+        assert!(engine.open_orders().is_empty());
+        assert_eq!(
+            engine.rejected_orders(),
+            &[RejectedOrder {
+                order_id,
+                client_tag: 7,
+                ts_ms: 1100,
+                reason: RejectReason::InsufficientCash,
+            }]
+        );
+
+        engine.reset();
+        assert!(engine.rejected_orders().is_empty());
+    }
+
+    #[test]
+    fn test_initial_position_marks_to_first_tick() {
+        let mut engine = Engine::with_config(EngineConfig {
+            initial_position: 2.0,
+            initial_entry_price: 100.0,
+            ..test_config()
+        })
+        .unwrap();
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.position, 2.0);
+        assert_eq!(snap.unrealized_pnl, 0.0);
+        assert_eq!(snap.cash, 10_000.0);
+
+        engine.step_tick(1000, 10_150, 1.0, "SELL").unwrap();
+        let snap = engine.get_snapshot();
+        assert!((snap.unrealized_pnl - 3.0).abs() < 1e-9);
+
+        // Reset restores the configured position
+        engine.place_order("MARKET", "SELL", 2.0, 0.0).unwrap();
+        engine.step_tick(1001, 10_150, 1.0, "BUY").unwrap();
+        assert_eq!(engine.get_snapshot().position, 0.0);
+        engine.reset();
+        assert_eq!(engine.get_snapshot().position, 2.0);
+    }
+
+    #[test]
+    fn test_initial_position_validation() {
+        let short = Engine::builder().initial_position(-1.5, 50.0).build().unwrap();
+        assert_eq!(short.get_snapshot().position, -1.5);
+
+        for (qty, price) in [(1.0, 0.0), (1.0, f64::NAN), (f64::INFINITY, 100.0), (1e-9, 100.0)] {
+            let result = Engine::builder().initial_position(qty, price).build();
+            assert!(matches!(result, Err(EngineError::InvalidInitialPosition(_))), "{} @ {}", qty, price);
+        }
+    }
+
+    #[test]
+    fn test_initial_long_costing_more_than_cash_rejected() {
+        let result = Engine::builder().initial_cash(0.0).initial_position(1e9, 1e9).build();
+        assert!(matches!(result, Err(EngineError::InvalidInitialPosition(_))));
+        let result = Engine::builder().initial_cash(999.0).initial_position(10.0, 100.0).build();
+        assert!(matches!(result, Err(EngineError::InvalidInitialPosition(_))));
+
+        // Up to the cash is fine, and shorts are not checked
+        Engine::builder().initial_cash(1_000.0).initial_position(10.0, 100.0).build().unwrap();
+        Engine::builder().initial_cash(0.0).initial_position(-1e9, 1e9).build().unwrap();
+    }
+
+    #[test]
+    fn test_builder_seeded_long_gains_on_rally() {
+        let mut engine = Engine::builder()
+            .maker_fee_bps(0.0)
+            .taker_fee_bps(0.0)
+            .spread_bps(0.0)
+            .tick_size(0.01)
+            .initial_position(1.0, 100.0)
+            .build()
+            .unwrap();
+
+        let snap = engine.get_snapshot();
+        assert_eq!((snap.position, snap.avg_entry_price), (1.0, 10_000.0));
+
+        engine.step_tick(1000, 11_000, 1.0, "BUY").unwrap();
+        let snap = engine.get_snapshot();
+        assert!(snap.unrealized_pnl > 0.0);
+        assert!((snap.unrealized_pnl - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_position_sizing_helpers() {
+        let engine = Engine::with_config(test_config()).unwrap();
+        assert_eq!(engine.equity(), 10_000.0);
+
+        assert_eq!(engine.size_for_notional(2_500.0, 100.0), 25.0);
+        assert_eq!(engine.size_for_notional(3_000.0, 7.0), 428.571428);
+        // Price snaps to the 0.01 grid before dividing
+        assert_eq!(engine.size_for_notional(1_000.0, 99.996), 10.0);
+        // Capped at what equity covers
+        assert_eq!(engine.size_for_notional(50_000.0, 100.0), 100.0);
+
+        // 1% of 10k at risk over a 2.00 stop
+        assert_eq!(engine.size_for_risk(0.01, 2.0), 50.0);
+        assert_eq!(engine.size_for_risk(0.01, 0.0), 0.0);
+        assert_eq!(engine.size_for_notional(1_000.0, f64::NAN), 0.0);
+
+        let broke = Engine::with_config(EngineConfig { initial_cash: 0.0, ..test_config() }).unwrap();
+        assert_eq!(broke.size_for_notional(1_000.0, 100.0), 0.0);
+        assert_eq!(broke.size_for_risk(0.02, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_checkpoint_restore() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        let checkpoint = engine.checkpoint().unwrap();
+        let warm = engine.get_snapshot();
+
+        for exit_price in [10_100, 9_900] {
+            engine.restore(&checkpoint);
+            engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+            engine.step_tick(2000, exit_price, 1.0, "BUY").unwrap();
+
+            let snap = engine.get_snapshot();
+            assert_eq!(snap.position, 0.0);
+            assert!((snap.realized_pnl - (exit_price - 10_000) as f64 * 0.01).abs() < 1e-9);
+            assert_eq!(engine.fills().len(), 2);
+        }
+
+        engine.restore(&checkpoint);
+        let restored = engine.get_snapshot();
+        assert_eq!(restored.position, warm.position);
+        assert_eq!(restored.cash, warm.cash);
+        assert_eq!(restored.ts_ms, warm.ts_ms);
+        assert_eq!(engine.fills().len(), 1);
+
+        // Order ids continue from the checkpoint, not from the abandoned branch
+        assert_eq!(engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_passive_rounding_for_limit_orders() {
+        let config = EngineConfig {
+            tick_size: 0.5,
+            rounding: RoundingMode::Passive,
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        // 100.25 is half-way between ticks 200 and 201
+        engine.place_order("LIMIT", "BUY", 1.0, 100.25).unwrap();
+        engine.step_tick(1000, 201, 1.0, "SELL").unwrap();
+        assert!(engine.fills().is_empty());
+
+        engine.step_tick(1001, 200, 1.0, "SELL").unwrap();
+        assert_eq!(engine.fills()[0].price_tick, 200);
+
+        engine.reset();
+        engine.place_order("LIMIT", "SELL", 1.0, 100.25).unwrap();
+        engine.step_tick(1000, 200, 1.0, "BUY").unwrap();
+        assert!(engine.fills().is_empty());
+        engine.step_tick(1001, 201, 1.0, "BUY").unwrap();
+        assert_eq!(engine.fills()[0].price_tick, 201);
+    }
+
+    #[test]
+    fn test_benchmark_leaves_engine_untouched() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.step_tick(5_000, 12_345, 1.0, "BUY").unwrap();
+
+        let bench = engine.benchmark(1_000).unwrap();
+        assert_eq!(bench.n_ticks, 1_000);
+        assert!(bench.ticks_per_sec > 0.0);
+        assert!(bench.avg_ns_per_tick > 0.0);
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.ts_ms, 5_000);
+        assert_eq!(snap.cash, 10_000.0);
+
+        // Same synthetic input every run
+        let a = synthetic_ticks(100);
+        let b = synthetic_ticks(100);
+        assert!(a.iter().zip(&b).all(|(x, y)| x.price_tick == y.price_tick && x.ts_ms == y.ts_ms));
+    }
+
+    #[test]
+    fn test_client_tag_survives_fill() {
+        const ENTRY: u64 = 1;
+        const TAKE_PROFIT: u64 = 2;
+
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        engine.mark_to(100.0).unwrap();
+        let entry_id = engine.place_order_with_tag("MARKET", "BUY", 1.0, 0.0, ENTRY).unwrap();
+        let tp_id = engine.place_order_with_tag("LIMIT", "SELL", 1.0, 101.0, TAKE_PROFIT).unwrap();
+
+        let open = engine.open_orders();
+        assert_eq!(open.len(), 2);
+        assert_eq!((open[0].order_id, open[0].client_tag), (entry_id, ENTRY));
+        assert_eq!((open[1].order_id, open[1].client_tag), (tp_id, TAKE_PROFIT));
+        assert_eq!(open[1].order_type, OrderType::Limit);
+        assert_eq!(open[1].price_tick, 10_100);
+
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.open_orders().len(), 1);
+        engine.step_tick(1001, 10_100, 1.0, "BUY").unwrap();
+        assert!(engine.open_orders().is_empty());
+
+        let tags: Vec<u64> = engine.fills().iter().map(|f| f.client_tag).collect();
+        assert_eq!(tags, vec![ENTRY, TAKE_PROFIT]);
+
+        // Untagged orders report 0
+        engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+        engine.step_tick(1002, 10_000, 1.0, "BUY").unwrap();
+        assert_eq!(engine.fills()[2].client_tag, 0);
+    }
+
+    #[test]
+    fn test_open_orders_include_latency_pending() {
+        let mut engine = Engine::with_config(EngineConfig { latency_ms: 100, ..test_config() }).unwrap();
+
+        engine.place_order_with_tag("LIMIT", "BUY", 1.0, 99.0, 7).unwrap();
+        let open = engine.open_orders();
+        assert_eq!(open.len(), 1);
+        assert!(open[0].pending);
+        assert_eq!(open[0].client_tag, 7);
+
+        engine.step_tick(100, 10_000, 1.0, "SELL").unwrap();
+        assert!(!engine.open_orders()[0].pending);
+    }
+
+    #[test]
+    fn test_bounded_equity_log_keeps_extremes() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.enable_bounded_equity_log(50);
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        for tick in synthetic_ticks(20_000) {
+            engine.step_tick_raw(&tick).unwrap();
+            let equity = engine.equity();
+            min = min.min(equity);
+            max = max.max(equity);
+            assert!(engine.equity_curve().len() <= 50);
+        }
+
+        let curve = engine.equity_curve();
+        assert!(curve.iter().any(|&(_, e)| e == min));
+        assert!(curve.iter().any(|&(_, e)| e == max));
+        assert!(curve.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(curve.last().unwrap().0, engine.get_snapshot().ts_ms);
+    }
+
+    #[test]
+    fn test_sampled_equity_log() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.enable_sampled_equity_log(EquitySampling::EveryMs(100));
+        for i in 0..1000 {
+            engine.step_tick(i, 10_000 + i % 7, 1.0, "SELL").unwrap();
+        }
+        let curve = engine.equity_curve();
+        assert_eq!(curve.len(), 10);
+        assert!(curve.iter().enumerate().all(|(i, &(ts, _))| ts == i as i64 * 100));
+
+        engine.reset();
+        engine.enable_sampled_equity_log(EquitySampling::EveryEvents(250));
+        for i in 0..1000 {
+            engine.step_tick(i, 10_000, 1.0, "SELL").unwrap();
+        }
+        let logged: Vec<i64> = engine.equity_curve().iter().map(|&(ts, _)| ts).collect();
+        assert_eq!(logged, vec![0, 250, 500, 750]);
+    }
+
+    #[test]
+    fn test_position_lots_at_half_lot() {
+        let snapshot = |position: f64| Snapshot {
+            position,
+            ..Engine::with_config(test_config()).unwrap().get_snapshot()
+        };
+
+        assert_eq!(snapshot(0.3).position_scaled(), 300_000);
+        assert_eq!(snapshot(-0.000001).position_scaled(), -1);
+        assert_eq!(snapshot(0.0).position_scaled(), 0);
+
+        for (position, lots) in [
+            (300.0, 3),
+            (249.999999, 2),
+            (250.0, 3),
+            (250.000001, 3),
+            (-249.999999, -2),
+            (-250.0, -3),
+            (49.999999, 0),
+            (0.0, 0),
+        ] {
+            assert_eq!(snapshot(position).position_lots(100.0), lots, "{}", position);
+        }
+        // Fractional lots whose sum drifts as a float still count exactly
+        assert_eq!(snapshot(0.1 + 0.2).position_lots(0.1), 3);
+        assert_eq!(snapshot(0.15).position_lots(0.1), 2);
+    }
+
+    #[test]
+    fn test_equity_log() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert!(engine.equity_curve().is_empty());
+
+        engine.enable_equity_log();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(1001, 10_000, 1.0, "SELL").unwrap();
+        engine.step_tick(1002, 10_200, 1.0, "SELL").unwrap();
+
+        let curve = engine.equity_curve();
+        assert_eq!(curve.len(), 2);
+        assert_eq!(curve[0].0, 1001);
+        assert_eq!(curve[1].0, 1002);
+        assert!((curve[1].1 - curve[0].1 - 2.0).abs() < 1e-9);
+
+        engine.reset();
+        assert!(engine.equity_curve().is_empty());
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.equity_curve().len(), 1);
+    }
+
+    #[test]
+    fn test_gtd_order_expires_on_later_tick() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        let gtd = engine.place_order_tif("LIMIT", "BUY", 1.0, 99.0, TimeInForce::Gtd(2_000)).unwrap();
+
+        engine.step_tick(2_000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.open_orders().len(), 1);
+        engine.step_tick(2_001, 9_900, 1.0, "SELL").unwrap();
+        assert!(engine.open_orders().is_empty());
+        assert!(engine.fills().is_empty());
+        assert!(engine.cancel_order(gtd).is_err());
+    }
+
+    #[test]
+    fn test_ioc_order_leaves_no_remainder() {
+        let mut engine = Engine::with_config(EngineConfig { latency_ms: 100, ..test_config() }).unwrap();
+        engine.enable_event_log();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order_tif("LIMIT", "BUY", 1.0, 99.0, TimeInForce::Ioc).unwrap();
+        let fok = engine.place_order_tif("MARKET", "BUY", 1.0, 0.0, TimeInForce::Fok).unwrap();
+
+        // Still waiting out the latency, so this tick is not their turn
+        engine.step_tick(1050, 9_800, 1.0, "SELL").unwrap();
+        assert_eq!(engine.open_orders().len(), 2);
+
+        // Matched against the first tick after release: the market order
+        // fills, the limit above the trade does not and is cancelled
+        engine.step_tick(1100, 10_000, 1.0, "SELL").unwrap();
+        assert!(engine.open_orders().is_empty());
+        assert_eq!(engine.fills().len(), 1);
+        assert_eq!(engine.fills()[0].order_id, fok);
+        engine.step_tick(1200, 9_800, 1.0, "SELL").unwrap();
+        assert_eq!(engine.fills().len(), 1);
+
+        let mut replayed = Engine::with_config(engine.config).unwrap();
+        replayed.replay_log(engine.event_log()).unwrap();
+        assert_eq!(replayed.fills(), engine.fills());
+        assert_eq!(TimeInForce::from_name("ioc"), Some(TimeInForce::Ioc));
+        assert_eq!(TimeInForce::from_name("GTD"), None);
+    }
+
+    #[test]
+    fn test_fok_limit_rejected_unless_it_crosses_last_trade() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        assert_eq!(
+            rejection(engine.place_order_tif("LIMIT", "BUY", 1.0, 100.0, TimeInForce::Fok)),
+            Some(RejectReason::FokNotMarketable)
+        );
+
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(
+            rejection(engine.place_order_tif("LIMIT", "BUY", 1.0, 90.0, TimeInForce::Fok)),
+            Some(RejectReason::FokNotMarketable)
+        );
+        assert_eq!(
+            rejection(engine.place_order_tif("LIMIT", "SELL", 1.0, 110.0, TimeInForce::Fok)),
+            Some(RejectReason::FokNotMarketable)
+        );
+        assert!(engine.open_orders().is_empty());
+
+        // At the last trade it fills on touch, and IOC is never pre-checked
+        let fok = engine.place_order_tif("LIMIT", "BUY", 1.0, 100.0, TimeInForce::Fok).unwrap();
+        engine.place_order_tif("LIMIT", "BUY", 1.0, 90.0, TimeInForce::Ioc).unwrap();
+        engine.step_tick(1001, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.fills().len(), 1);
+        assert_eq!(engine.fills()[0].order_id, fok);
+        assert!(engine.open_orders().is_empty());
+
+        // Trade-through needs the limit strictly past the last trade
+        let config = EngineConfig { limit_fill_model: LimitFillModel::OnThrough, ..test_config() };
+        let mut engine = Engine::with_config(config).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(
+            rejection(engine.place_order_tif("LIMIT", "SELL", 1.0, 100.0, TimeInForce::Fok)),
+            Some(RejectReason::FokNotMarketable)
+        );
+        engine.place_order_tif("LIMIT", "SELL", 1.0, 99.99, TimeInForce::Fok).unwrap();
+    }
+
+    #[test]
+    fn test_gtd_order_expires_on_clock_advance() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+
+        let gtd = engine.place_order_gtd("LIMIT", "BUY", 1.0, 99.0, 5_000).unwrap();
+        let gtc = engine.place_order("LIMIT", "BUY", 1.0, 98.0).unwrap();
+
+        // Still valid at exactly valid_until_ms
+        engine.advance_clock(5_000).unwrap();
+        assert_eq!(engine.open_orders().len(), 2);
+
+        engine.advance_clock(5_001).unwrap();
+        let open: Vec<u64> = engine.open_orders().iter().map(|o| o.order_id).collect();
+        assert_eq!(open, vec![gtc]);
+        assert!(engine.cancel_order(gtd).is_err());
+
+        // Clock moved, price did not: marked at the last trade, nothing filled
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.ts_ms, 5_001);
+        assert!(engine.fills().is_empty());
+
+        // A tick through the old limit no longer fills it
+        engine.step_tick(6_000, 9_800, 1.0, "SELL").unwrap();
+        assert_eq!(engine.fills().len(), 1);
+        assert_eq!(engine.fills()[0].order_id, gtc);
+    }
+
+    #[test]
+    fn test_finalize_cancels_dangling_orders() {
+        let mut engine = Engine::with_config(EngineConfig { latency_ms: 100, ..test_config() }).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1100, 10_000, 1.0, "SELL").unwrap();
+
+        let gtd = engine.place_order_gtd("LIMIT", "BUY", 1.0, 99.0, 5_000).unwrap();
+        let resting = engine.place_order("LIMIT", "SELL", 1.0, 105.0).unwrap();
+        engine.step_tick(1200, 10_100, 1.0, "BUY").unwrap();
+        let queued = engine.place_order("LIMIT", "BUY", 1.0, 101.0).unwrap();
+        let before = engine.get_snapshot();
+
+        let report = engine.finalize();
+        let cancelled: Vec<(u64, bool)> = report.cancelled.iter().map(|o| (o.order_id, o.pending)).collect();
+        assert_eq!(cancelled, vec![(gtd, false), (resting, false), (queued, true)]);
+        assert!(engine.open_orders().is_empty());
+
+        // The position stays open, marked at the last trade; nothing filled
+        assert_eq!(engine.fills().len(), 1);
+        assert_eq!(report.snapshot, before);
+        assert_eq!(report.snapshot.position, 2.0);
+
+        let again = engine.finalize();
+        assert!(again.cancelled.is_empty());
+        assert_eq!(again.snapshot, report.snapshot);
+
+        // The dropped latency order never reaches the matcher
+        engine.step_tick(1400, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.fills().len(), 1);
+    }
+
+    #[test]
+    fn test_event_log_replay_reproduces_state() {
+        let config = EngineConfig { latency_ms: 50, ..test_config() };
+        let mut engine = Engine::with_config(config).unwrap();
+        engine.enable_event_log();
+
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        let cancelled = engine.place_order("LIMIT", "BUY", 1.0, 95.0).unwrap();
+        engine.place_order_gtd("LIMIT", "SELL", 1.0, 103.0, 3_000).unwrap();
+        assert!(engine.place_order("LIMIT", "HOLD", 1.0, 99.0).is_err());
+        engine.advance_clock(1100).unwrap();
+        engine.cancel_order(cancelled).unwrap();
+        let candle = Candle {
+            ts_open: 1100,
+            ts_close: 2000,
+            open_tick: 10_000,
+            high_tick: 10_350,
+            low_tick: 9_950,
+            close_tick: 10_300,
+            volume_scaled: 4_000_000,
+            trade_count: 4,
+        };
+        engine.step_candle(&candle, BarToTickPolicy::Ohlc).unwrap();
+        engine.apply_funding(0.001).unwrap();
+        engine.place_order("LIMIT", "BUY", 1.0, 101.0).unwrap();
+        engine.finalize();
+
+        // The rejected order left no trace
+        assert_eq!(engine.event_log().len(), 13);
+
+        let mut replayed = Engine::with_config(config).unwrap();
+        replayed.replay_log(engine.event_log()).unwrap();
+        assert_eq!(replayed.get_snapshot(), engine.get_snapshot());
+        assert_eq!(replayed.fills(), engine.fills());
+        assert_eq!(replayed.funding_events(), engine.funding_events());
+        assert!(replayed.open_orders().is_empty());
+
+        engine.reset();
+        assert!(engine.event_log().is_empty());
+    }
+
+    #[test]
+    fn test_indicators_reset_with_engine() {
+        let bars: Vec<Candle> = (0..6i64)
+            .map(|i| Candle {
+                ts_open: i * 60_000,
+                ts_close: (i + 1) * 60_000 - 1,
+                open_tick: 10_000,
+                high_tick: 10_000 + 40 * (3 - i).abs(),
+                low_tick: 9_990 - 10 * i,
+                close_tick: 10_000,
+                volume_scaled: 1_000_000,
+                trade_count: 1,
+            })
+            .collect();
+        let run = |engine: &mut Engine| -> Vec<Option<(i64, i64)>> {
+            bars.iter()
+                .map(|bar| {
+                    engine.step_candle(bar, BarToTickPolicy::Close).unwrap();
+                    engine.indicators().donchian(0).unwrap().value()
+                })
+                .collect()
+        };
+
+        let mut fresh = Engine::with_config(test_config()).unwrap();
+        fresh.add_indicator(indicators::DonchianChannel::new(3));
+        let expected = run(&mut fresh);
+        assert_eq!(expected[2], Some((10_120, 9_970)));
+
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let index = engine.add_indicator(indicators::DonchianChannel::new(3));
+        assert_eq!(index, 0);
+        run(&mut engine);
+        engine.reset();
+        assert_eq!(engine.indicators().len(), 1);
+        assert_eq!(engine.indicators().donchian(0).unwrap().value(), None);
+        assert_eq!(run(&mut engine), expected);
+    }
+
+    #[test]
+    fn test_drawdown_tracking_matches_equity_curve() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        assert!(engine.drawdown().is_none());
+        engine.enable_drawdown_tracking();
+        engine.enable_equity_log();
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 10.0, 0.0).unwrap();
+        for (i, price_tick) in [10_000, 10_400, 9_700, 10_200, 9_500, 10_600].into_iter().enumerate() {
+            engine.step_tick(i as i64, price_tick, 1.0, "SELL").unwrap();
+        }
+
+        let tracker = engine.drawdown().unwrap();
+        assert!(tracker.max_drawdown() > 0.0);
+        assert_eq!(tracker.max_drawdown(), metrics::max_drawdown(engine.equity_curve()));
+        assert_eq!(tracker.current_drawdown(), 0.0);
+
+        engine.reset();
+        assert_eq!(engine.drawdown(), Some(&DrawdownTracker::new()));
+    }
+
+    #[test]
+    fn test_mark_to_tracks_unrealized_linearly() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 3.0, 0.0).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        let before = engine.get_snapshot();
+        let entry = before.avg_entry_price * engine.tick_size();
+
+        for price in [95.0, 100.0, 100.5, 110.25] {
+            let snap = engine.mark_to(price).unwrap();
+            assert!((snap.unrealized_pnl - 3.0 * (price - entry)).abs() < 1e-9, "{}", price);
+            assert_eq!(snap.equity, snap.cash + snap.unrealized_pnl);
+            assert_eq!(snap.unrealized_pnl, engine.get_snapshot().unrealized_pnl);
+            assert_eq!(
+                (snap.position, snap.cash, snap.cumulative_volume, snap.ts_ms),
+                (before.position, before.cash, before.cumulative_volume, before.ts_ms)
+            );
+        }
+        assert_eq!(engine.fills().len(), 1);
+        assert!(matches!(engine.mark_to(f64::NAN), Err(EngineError::InvalidMarkPrice(_))));
+
+        // The next trade takes over the mark
+        engine.step_tick(1001, 10_000, 1.0, "SELL").unwrap();
+        assert!((engine.get_snapshot().unrealized_pnl - before.unrealized_pnl).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mark_to_negative_price() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.mark_to(10.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1000, 1_000, 1.0, "SELL").unwrap();
+
+        // A long at 10.00 marked at -37.63, as in April 2020
+        let snap = engine.mark_to(-37.63).unwrap();
+        assert!((snap.unrealized_pnl - 2.0 * (-37.63 - 10.0)).abs() < 1e-9);
+        assert_eq!(snap.equity, snap.cash + snap.unrealized_pnl);
+
+        let snap = engine.mark_to(0.0).unwrap();
+        assert!((snap.unrealized_pnl + 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_finalize_at_realizes_position() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.enable_event_log();
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("LIMIT", "SELL", 2.0, 120.0).unwrap();
+
+        let report = engine.finalize_at(105.0).unwrap();
+        assert_eq!(report.cancelled.len(), 1);
+        assert_eq!(report.snapshot.position, 0.0);
+        assert_eq!(report.snapshot.unrealized_pnl, 0.0);
+        assert!((report.snapshot.realized_pnl - 10.0).abs() < 1e-9);
+        assert!((report.snapshot.equity - (test_config().initial_cash + 10.0)).abs() < 1e-9);
+        let close = engine.fills().last().unwrap();
+        assert_eq!((close.side, close.qty, close.price), (Side::Sell, 2.0, 105.0));
+
+        // Flat: nothing more to close
+        let again = engine.finalize_at(105.0).unwrap();
+        assert_eq!(engine.fills().len(), 2);
+        assert_eq!(again.snapshot.realized_pnl, report.snapshot.realized_pnl);
+        assert!(matches!(engine.finalize_at(f64::NAN), Err(EngineError::InvalidMarkPrice(_))));
+
+        let mut replayed = Engine::with_config(test_config()).unwrap();
+        replayed.replay_log(engine.event_log()).unwrap();
+        assert_eq!(replayed.get_snapshot(), engine.get_snapshot());
+    }
+
+    #[test]
+    fn test_finalize_at_negative_price() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.mark_to(10.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1000, 1_000, 1.0, "SELL").unwrap();
+
+        let report = engine.finalize_at(-5.0).unwrap();
+        assert_eq!(report.snapshot.position, 0.0);
+        assert_eq!(report.snapshot.unrealized_pnl, 0.0);
+        // Bought at 10.00, closed at -5.00
+        assert!((report.snapshot.realized_pnl + 30.0).abs() < 1e-9);
+        let close = engine.fills().last().unwrap();
+        assert_eq!((close.side, close.qty, close.price), (Side::Sell, 2.0, -5.0));
+    }
+
+    #[test]
+    fn test_run_file_updates_indicators() {
+        let path = std::env::temp_dir().join(format!("ag_core_run_file_{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "timestamp,open,high,low,close,volume\n\
+             1609459200000,100.0,101.0,99.0,100.5,10\n\
+             1609459260000,100.5,102.0,100.0,101.5,12\n\
+             1609459320000,101.5,101.75,98.5,99.0,8\n",
+        )
+        .unwrap();
+
+        let mut engine = Engine::with_config(EngineConfig { tick_size: 0.25, ..test_config() }).unwrap();
+        let channel = engine.add_indicator(indicators::DonchianChannel::new(2));
+        let metrics = engine.run_file(&path, Side::Sell).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(metrics.candles_processed, 3);
+        assert_eq!(engine.indicators().donchian(channel).unwrap().value(), Some((408, 394)));
+        assert_eq!(engine.get_snapshot().ts_ms, 1609459380000);
+
+        let missing = engine.run_file(std::env::temp_dir().join("ag_core_no_such_file.csv"), Side::Buy);
+        assert!(matches!(missing, Err(EngineError::Ingestion(ParseError::Io(_)))));
+    }
+
+    #[test]
+    fn test_snapshot_unrealized_at_long_and_short() {
+        let mut engine = Engine::with_config(EngineConfig { tick_size: 0.5, ..test_config() }).unwrap();
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1000, 200, 1.0, "SELL").unwrap();
+        let long = engine.get_snapshot();
+        assert_eq!(long.avg_entry_price, 200.0);
+
+        for (price, expected) in [(100.0, 0.0), (110.0, 20.0), (95.5, -9.0)] {
+            assert!((long.unrealized_at(price, 0.5) - expected).abs() < 1e-9, "{}", price);
+        }
+        assert!((engine.unrealized_at(110.0) - 20.0).abs() < 1e-9);
+
+        engine.place_order("MARKET", "SELL", 5.0, 0.0).unwrap();
+        engine.step_tick(1001, 200, 1.0, "BUY").unwrap();
+        let short = engine.get_snapshot();
+        assert_eq!(short.position, -3.0);
+        for (price, expected) in [(100.0, 0.0), (110.0, -30.0), (95.5, 13.5)] {
+            assert!((short.unrealized_at(price, 0.5) - expected).abs() < 1e-9, "{}", price);
+        }
+
+        engine.place_order("MARKET", "BUY", 3.0, 0.0).unwrap();
+        engine.step_tick(1002, 200, 1.0, "SELL").unwrap();
+        assert_eq!(engine.get_snapshot().unrealized_at(150.0, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_fill_callback_fires_on_fill() {
+        use std::sync::{Arc, Mutex};
+
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        engine.on_fill(move |fill| sink.lock().unwrap().push(fill));
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.place_order("LIMIT", "SELL", 2.0, 101.0).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(seen.lock().unwrap().as_slice(), engine.fills());
+        assert_eq!(seen.lock().unwrap().len(), 1);
+
+        // Survives a reset; the next fill is reported too
+        engine.reset();
+        engine.mark_to(101.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(2000, 10_100, 1.0, "SELL").unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 2);
+        assert_eq!(seen.lock().unwrap()[1].price, 101.0);
+
+        // A panicking callback resurfaces from the call that filled
+        engine.on_fill(|_| panic!("callback failed"));
+        engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            engine.step_tick(3000, 10_100, 1.0, "BUY")
+        }));
+        assert!(result.is_err());
+        assert_eq!(engine.get_snapshot().position, 0.0);
+
+        engine.clear_fill_callback();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(4000, 10_100, 1.0, "SELL").unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_book_snapshot_levels() {
+        let mut engine = Engine::with_config(EngineConfig { latency_ms: 100, ..test_config() }).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("LIMIT", "BUY", 1.0, 99.0).unwrap();
+        engine.place_order("LIMIT", "BUY", 2.0, 98.5).unwrap();
+        engine.place_order("LIMIT", "BUY", 0.5, 99.0).unwrap();
+        engine.place_order("LIMIT", "BUY", 1.0, 97.0).unwrap();
+        engine.place_order("LIMIT", "SELL", 1.5, 101.0).unwrap();
+        engine.place_order("LIMIT", "SELL", 1.0, 100.5).unwrap();
+        engine.place_order("MARKET", "SELL", 3.0, 0.0).unwrap();
+        assert!(engine.book_snapshot(5).bids.is_empty(), "pending orders are not resting");
+
+        engine.advance_clock(1100).unwrap();
+        let book = engine.book_snapshot(2);
+        assert_eq!(book.bids.len(), 2);
+        assert!((book.bids[0].0 - 99.0).abs() < 1e-9 && (book.bids[0].1 - 1.5).abs() < 1e-9);
+        assert!((book.bids[1].0 - 98.5).abs() < 1e-9 && (book.bids[1].1 - 2.0).abs() < 1e-9);
+        assert!((book.asks[0].0 - 100.5).abs() < 1e-9 && (book.asks[0].1 - 1.0).abs() < 1e-9);
+        assert!((book.asks[1].0 - 101.0).abs() < 1e-9 && (book.asks[1].1 - 1.5).abs() < 1e-9);
+        assert_eq!(engine.book_snapshot(10).bids.len(), 3);
+        assert_eq!(engine.book_snapshot(0), BookSnapshot::default());
+    }
+
+    #[test]
+    fn test_advance_clock_releases_latency_queue() {
+        let mut engine = Engine::with_config(EngineConfig { latency_ms: 100, ..test_config() }).unwrap();
+        engine.step_tick(1000, 10_000, 1.0, "SELL").unwrap();
+        let id = engine.place_order("LIMIT", "BUY", 1.0, 99.0).unwrap();
+
+        engine.advance_clock(1100).unwrap();
+        assert!(!engine.open_orders()[0].pending);
+
+        engine.cancel_order(id).unwrap();
+        assert!(engine.open_orders().is_empty());
+        assert!(matches!(engine.cancel_order(id), Err(EngineError::OrderNotFound(_))));
+    }
+
+    #[test]
+    fn test_field_accessors_match_snapshot() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        engine.step_tick(2, 10_100, 1.0, "SELL").unwrap();
+
+        let snap = engine.get_snapshot();
+        assert_eq!(engine.position(), snap.position);
+        assert_eq!(engine.cash(), snap.cash);
+        assert_eq!(engine.equity(), snap.equity);
+        assert_eq!(engine.avg_entry_price(), snap.avg_entry_price);
+        assert!((engine.position() - 2.0).abs() < 1e-9);
+    }
+
+    /// Accessor vs. snapshot-dict cost; run with `cargo test -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_position_accessor_vs_snapshot_dict() {
+        use std::time::Instant;
+
+        let engine = Engine::with_config(test_config()).unwrap();
+        let n = 1_000_000;
+
+        let start = Instant::now();
+        let mut total = 0.0;
+        for _ in 0..n {
+            total += snapshot_dict(&engine.get_snapshot())["position"];
+        }
+        let dict_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..n {
+            total += engine.position();
+        }
+        let accessor_elapsed = start.elapsed();
+
+        assert_eq!(total, 0.0);
+        println!(
+            "snapshot dict: {:.1} ns/call, position(): {:.1} ns/call ({:.1}x)",
+            dict_elapsed.as_nanos() as f64 / n as f64,
+            accessor_elapsed.as_nanos() as f64 / n as f64,
+            dict_elapsed.as_secs_f64() / accessor_elapsed.as_secs_f64()
+        );
+    }
+
+    /// Assert the (possibly cached) snapshot matches what the core reports now
+    fn assert_snapshot_fresh(engine: &Engine) {
+        let cached = engine.get_snapshot();
+        let raw = unsafe { engine_get_snapshot(engine.handle) };
+        assert_eq!(cached.ts_ms, raw.ts_ms);
+        assert_eq!(cached.cash, raw.cash);
+        assert_eq!(cached.position, raw.position as f64 / 1_000_000.0);
+        assert_eq!(cached.avg_entry_price, raw.avg_entry_price);
+        assert_eq!(cached.realized_pnl, raw.realized_pnl);
+        assert_eq!(cached.unrealized_pnl, raw.unrealized_pnl);
+        assert_eq!(cached.equity, raw.equity);
+    }
+
+    #[test]
+    fn test_snapshot_change_fires_on_fill_not_on_no_op_tick() {
+        use std::sync::{Arc, Mutex};
+
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&deltas);
+        engine.on_snapshot_change(move |delta| sink.lock().unwrap().push(*delta));
+
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        assert!(deltas.lock().unwrap().is_empty());
+
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(2, 10_000, 5.0, "SELL").unwrap();
+        assert_eq!(
+            deltas.lock().unwrap().as_slice(),
+            &[SnapshotDelta {
+                ts_ms: 2,
+                position: Some(2.0),
+                cash: Some(9_800.0),
+                equity: Some(9_800.0),
+            }]
+        );
+
+        // Same price, no fill: nothing to report
+        engine.step_tick(3, 10_000, 1.0, "BUY").unwrap();
+        assert_eq!(deltas.lock().unwrap().len(), 1);
+
+        engine.step_tick(4, 10_100, 1.0, "BUY").unwrap();
+        let last = *deltas.lock().unwrap().last().unwrap();
+        assert_eq!((last.position, last.cash), (None, None));
+        assert!((last.equity.unwrap() - 9_802.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_snapshot_cache_hits_until_mutation() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+
+        let first = engine.get_snapshot();
+        assert_eq!(engine.snapshot_cache.get(), Some(first));
+        assert_eq!(engine.get_snapshot(), first);
+        assert_eq!(engine.position(), first.position);
+    }
+
+    #[test]
+    fn test_snapshot_cache_invalidated_by_every_mutation() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.get_snapshot();
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        assert_snapshot_fresh(&engine);
+
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        assert_snapshot_fresh(&engine);
+        assert!((engine.position() - 1.0).abs() < 1e-9);
+
+        engine.process_tick_batch(vec![2], vec![10_050], vec![1.0], vec![1]).unwrap();
+        assert_snapshot_fresh(&engine);
+
+        let id = engine.place_order("LIMIT", "BUY", 1.0, 90.0).unwrap();
+        engine.get_snapshot();
+        engine.cancel_order(id).unwrap();
+        assert_snapshot_fresh(&engine);
+
+        engine.advance_clock(10).unwrap();
+        assert_snapshot_fresh(&engine);
+        assert_eq!(engine.get_snapshot().ts_ms, 10);
+
+        let checkpoint = engine.checkpoint().unwrap();
+        engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+        engine.step_tick(11, 10_100, 1.0, "BUY").unwrap();
+        assert!(engine.position().abs() < 1e-9);
+        engine.restore(&checkpoint);
+        assert_snapshot_fresh(&engine);
+        assert!((engine.position() - 1.0).abs() < 1e-9);
+
+        engine.reset();
+        assert_snapshot_fresh(&engine);
+        assert_eq!(engine.position(), 0.0);
+
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        engine.get_snapshot();
+        engine.reset_with_config(EngineConfig { initial_cash: 5_000.0, ..test_config() }).unwrap();
+        assert_snapshot_fresh(&engine);
+        assert_eq!(engine.cash(), 5_000.0);
+    }
+
+    #[test]
+    fn test_snapshot_cache_invalidated_by_latency_release_and_expiry() {
+        let mut engine = Engine::with_config(EngineConfig { latency_ms: 5, ..test_config() }).unwrap();
+        engine.step_tick(0, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.place_order_gtd("LIMIT", "BUY", 1.0, 90.0, 20).unwrap();
+        assert_eq!(engine.position(), 0.0);
+
+        // Releases both queued orders and fills the market order
+        engine.step_tick(5, 10_000, 1.0, "SELL").unwrap();
+        assert_snapshot_fresh(&engine);
+        assert!((engine.position() - 1.0).abs() < 1e-9);
+
+        engine.advance_clock(30).unwrap();
+        assert_snapshot_fresh(&engine);
+        assert!(engine.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_price_band_rejects_far_limit() {
+        let config = EngineConfig {
+            price_band_bps: Some(1_000.0),
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        // No reference price before the first tick
+        engine.place_order("LIMIT", "BUY", 1.0, 50.0).unwrap();
+        engine.cancel_order(1).unwrap();
+
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        let result = engine.place_order("LIMIT", "BUY", 1.0, 50.0);
+        assert!(matches!(
+            result,
+            Err(EngineError::PriceOutsideBand { band_bps, .. }) if band_bps == 1_000.0
+        ));
+        assert!(engine.open_orders().is_empty());
+
+        // Inside the band, and market orders are never checked
+        engine.place_order("LIMIT", "BUY", 1.0, 95.0).unwrap();
+        engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+
+        assert_eq!(engine.band_breaches(), 0);
+        engine.step_tick(2, 5_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.band_breaches(), 1);
+    }
+
+    #[test]
+    fn test_trading_at_negative_prices() {
+        let config = EngineConfig {
+            taker_fee_bps: 10.0,
+            spread_bps: 100.0,
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        // Mid -40.00: buys lift the ask above mid, sells hit the bid below it
+        engine.mark_to(-40.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(1, -4_000, 1.0, "SELL").unwrap();
+        let buy = engine.fills()[0].clone();
+        assert_eq!(buy.price_tick, -3_980);
+        assert!((buy.fee - 0.0398).abs() < 1e-12);
+        assert!((engine.cash() - (10_000.0 + 39.80 - 0.0398)).abs() < 1e-9);
+
+        engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+        engine.step_tick(2, -3_000, 1.0, "BUY").unwrap();
+        let sell = engine.fills()[1].clone();
+        assert_eq!(sell.price_tick, -3_015);
+        assert!(sell.fee > 0.0);
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.position, 0.0);
+        assert!((snap.realized_pnl - (-30.15 + 39.80)).abs() < 1e-9);
+
+        // Limit orders and the price band work across zero
+        let config = EngineConfig {
+            price_band_bps: Some(1_000.0),
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+        engine.step_tick(1, -1_000, 1.0, "SELL").unwrap();
+        assert!(engine.place_order("LIMIT", "BUY", 1.0, -12.0).is_err());
+        engine.place_order("LIMIT", "BUY", 1.0, -10.5).unwrap();
+        engine.step_tick(2, -1_060, 1.0, "SELL").unwrap();
+        assert_eq!(engine.position(), 1.0);
+
+        let short = Engine::builder().initial_position(-1.0, -5.0).build().unwrap();
+        assert_eq!(short.avg_entry_price(), -500.0);
+    }
+
+    #[test]
+    fn test_pnl_and_equity_at_hypothetical_price() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        assert_eq!(engine.pnl_at_price(1e9), 0.0);
+
+        // Long 2 at 100, then sell 1 at 110: realized 10
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 5.0, "SELL").unwrap();
+        engine.place_order("MARKET", "SELL", 1.0, 0.0).unwrap();
+        engine.step_tick(2, 11_000, 5.0, "BUY").unwrap();
+        let before = engine.get_snapshot();
+        assert!((before.realized_pnl - 10.0).abs() < 1e-9);
+
+        // At the entry price nothing is unrealized
+        assert!((engine.pnl_at_price(100.0) - 10.0).abs() < 1e-9);
+        assert!((engine.equity_at_price(100.0) - before.cash).abs() < 1e-9);
+
+        // At the last trade it agrees with the core's own marking
+        assert!((engine.equity_at_price(110.0) - before.equity).abs() < 1e-9);
+        assert!((engine.pnl_at_price(110.0) - (before.realized_pnl + before.unrealized_pnl)).abs() < 1e-9);
+
+        // Extremes stay linear in price
+        assert!((engine.pnl_at_price(0.0) - (10.0 - 100.0)).abs() < 1e-9);
+        assert!((engine.pnl_at_price(-50.0) - (10.0 - 150.0)).abs() < 1e-9);
+        assert!((engine.pnl_at_price(1e9) - (10.0 + 1e9 - 100.0)).abs() < 1e-3);
+
+        assert_eq!(engine.get_snapshot(), before);
+    }
+
+    /// Five ticks with an invalid side at index 2
+    fn batch_with_bad_tick() -> (Vec<i64>, Vec<i64>, Vec<f64>, Vec<u8>) {
+        (
+            vec![1, 2, 3, 4, 5],
+            vec![10_000, 10_010, 10_020, 10_030, 10_040],
+            vec![1.0; 5],
+            vec![0, 1, 7, 0, 1],
+        )
+    }
+
+    #[test]
+    fn test_batch_policy_abort_at_first() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let (ts, prices, qtys, sides) = batch_with_bad_tick();
+
+        let result = engine.process_tick_batch_with_policy(ts, prices, qtys, sides, BatchErrorPolicy::AbortAtFirst);
+        assert!(matches!(result, Err(EngineError::InvalidSideValue(7))));
+        assert_eq!(engine.get_snapshot().ts_ms, 2);
+    }
+
+    #[test]
+    fn test_batch_policy_skip_and_continue() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let (ts, prices, qtys, sides) = batch_with_bad_tick();
+
+        let result = engine
+            .process_tick_batch_with_policy(ts, prices, qtys, sides, BatchErrorPolicy::SkipAndContinue)
+            .unwrap();
+        assert_eq!(
+            result,
+            BatchResult { processed: 4, skipped: 1, failed_index: None, first_error_index: Some(2) }
+        );
+        assert_eq!(engine.get_snapshot().ts_ms, 5);
+    }
+
+    #[test]
+    fn test_batch_policy_stop_and_report() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let (ts, prices, qtys, sides) = batch_with_bad_tick();
+
+        let result = engine
+            .process_tick_batch_with_policy(ts, prices, qtys, sides, BatchErrorPolicy::StopAndReport)
+            .unwrap();
+        assert_eq!(
+            result,
+            BatchResult { processed: 2, skipped: 0, failed_index: Some(2), first_error_index: Some(2) }
+        );
+        assert_eq!(engine.get_snapshot().ts_ms, 2);
+
+        // Length mismatches are errors under every policy
+        let result = engine.process_tick_batch_with_policy(vec![6], vec![], vec![], vec![], BatchErrorPolicy::StopAndReport);
+        assert!(matches!(result, Err(EngineError::LengthMismatch { .. })));
+        assert_eq!(BatchErrorPolicy::from_name("Skip"), Some(BatchErrorPolicy::SkipAndContinue));
+    }
+
+    #[test]
+    fn test_queue_position_drains_with_trades_at_level() {
+        let config = EngineConfig {
+            latency_ms: 100,
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+        engine.enable_queue_tracking();
+
+        // 3 units trade at 100.00 before the order joins that level
+        engine.step_tick(1, 10_000, 3.0, "SELL").unwrap();
+        engine.step_tick(2, 10_050, 1.0, "BUY").unwrap();
+        let id = engine.place_order("LIMIT", "BUY", 1.0, 100.0).unwrap();
+        let market = engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        assert_eq!(engine.queue_position(id), Some(3_000_000));
+        assert_eq!(engine.queue_position(market), None);
+
+        // Only trades at the order's price consume the queue; the order is
+        // still in flight, so nothing fills yet
+        let mut ahead = Vec::new();
+        for (ts, price_tick) in [(3, 10_010), (4, 10_000), (5, 10_000), (6, 10_000)] {
+            engine.step_tick(ts, price_tick, 1.25, "SELL").unwrap();
+            ahead.push(engine.queue_position(id).unwrap());
+        }
+        assert_eq!(ahead, vec![3_000_000, 1_750_000, 500_000, 0]);
+        assert!(engine.fills().is_empty());
+
+        // Filled orders stop being tracked
+        engine.step_tick(200, 10_000, 1.0, "SELL").unwrap();
+        assert!(engine.fills().iter().any(|f| f.order_id == id));
+        assert_eq!(engine.queue_position(id), None);
+
+        let untracked = {
+            let mut engine = Engine::with_config(test_config()).unwrap();
+            let id = engine.place_order("LIMIT", "BUY", 1.0, 99.0).unwrap();
+            engine.queue_position(id)
+        };
+        assert_eq!(untracked, None);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_rejection_emits_warn_event_with_reason() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Level, Metadata, Subscriber};
+
+        type Recorded = Arc<Mutex<Vec<(Level, Option<String>)>>>;
+
+        /// Records `(level, reason field)` of every event
+        struct Recorder(Recorded);
+
+        struct ReasonVisitor(Option<String>);
+
+        impl Visit for ReasonVisitor {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                if field.name() == "reason" {
+                    self.0 = Some(value.to_string());
+                }
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut visitor = ReasonVisitor(None);
+                event.record(&mut visitor);
+                self.0.lock().unwrap().push((*event.metadata().level(), visitor.0));
+            }
+            fn enter(&self, _span: &Id) {}
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Recorder(Arc::clone(&events)), || {
+            let mut engine = Engine::with_config(test_config()).unwrap();
+            engine.place_order("LIMIT", "BUY", 1.0, 99.0).unwrap();
+            assert!(engine.place_order("LIMIT", "BUY", 1_000.0, 99.0).is_err());
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[0], (Level::INFO, None));
+        assert!(events.contains(&(Level::TRACE, None)));
+        assert_eq!(
+            events.iter().filter(|(level, _)| *level == Level::WARN).collect::<Vec<_>>(),
+            vec![&(Level::WARN, Some("insufficient cash".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_cumulative_volume_and_turnover() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.5, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order("MARKET", "SELL", 2.0, 0.0).unwrap();
+        engine.step_tick(2, 11_000, 1.0, "BUY").unwrap();
+        let taken = engine.take_fills();
+        engine.place_order("MARKET", "BUY", 0.25, 0.0).unwrap();
+        engine.step_tick(3, 10_500, 1.0, "SELL").unwrap();
+
+        let fills: Vec<Fill> = taken.into_iter().chain(engine.fills().iter().cloned()).collect();
+        assert_eq!(fills.len(), 3);
+
+        let snap = engine.get_snapshot();
+        assert_eq!(snap.cumulative_volume, fills.iter().map(|f| f.qty).sum::<f64>());
+        assert_eq!(snap.cumulative_volume, 3.75);
+        assert!((snap.turnover - (1.5 * 100.0 + 2.0 * 110.0 + 0.25 * 105.0)).abs() < 1e-9);
+
+        engine.reset();
+        assert_eq!(engine.get_snapshot().cumulative_volume, 0.0);
+        assert_eq!(engine.get_snapshot().turnover, 0.0);
+    }
+
+    #[test]
+    fn test_config_reads_back_unchanged() {
+        let mut engine = Engine::builder()
+            .initial_cash(25_000.0)
+            .maker_fee_bps(0.75)
+            .taker_fee_bps(3.5)
+            .spread_bps(1.25)
+            .tick_size(0.05)
+            .latency_ms(20)
+            .borrow_fee_bps_per_day(4.0)
+            .build()
+            .unwrap();
+
+        let config = engine.config();
+        assert_eq!(config.initial_cash, 25_000.0);
+        assert_eq!(config.maker_fee_bps, 0.75);
+        assert_eq!(config.taker_fee_bps, 3.5);
+        assert_eq!(config.spread_bps, 1.25);
+        assert_eq!(config.tick_size, 0.05);
+        assert_eq!(config.latency_ms, 20);
+        assert_eq!(config.price_band_bps, None);
+        assert_eq!(config.borrow_fee_bps_per_day, 4.0);
+
+        engine.step_tick(1, 2_000, 1.0, "BUY").unwrap();
+        engine.reset();
+        assert_eq!(engine.config(), config);
+    }
+
+    fn rejection(result: Result<u64, EngineError>) -> Option<RejectReason> {
+        result.err().and_then(|e| e.reject_reason())
+    }
+
+    #[test]
+    fn test_reject_insufficient_cash() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        assert_eq!(
+            rejection(engine.place_order("LIMIT", "BUY", 101.0, 100.0)),
+            Some(RejectReason::InsufficientCash)
+        );
+        let id = engine.place_order("LIMIT", "BUY", 100.0, 100.0).unwrap();
+        engine.cancel_order(id).unwrap();
+
+        // Market buys are costed at the last trade; shorts never need cash
+        engine.step_tick(1, 20_000, 1.0, "SELL").unwrap();
+        assert_eq!(
+            rejection(engine.place_order("MARKET", "BUY", 51.0, 0.0)),
+            Some(RejectReason::InsufficientCash)
+        );
+        engine.place_order("MARKET", "SELL", 500.0, 0.0).unwrap();
+        assert!(engine.open_orders().iter().all(|o| o.side == Side::Sell));
+    }
+
+    #[test]
+    fn test_reject_market_buy_before_first_price() {
+        let mut engine = Engine::with_config(EngineConfig { initial_cash: 100.0, ..test_config() }).unwrap();
+
+        assert_eq!(
+            rejection(engine.place_order("MARKET", "BUY", 1e6, 0.0)),
+            Some(RejectReason::NoPrice)
+        );
+        assert!(engine.open_orders().is_empty());
+
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.cash(), 100.0);
+        assert_eq!(engine.position(), 0.0);
+        assert_eq!(
+            rejection(engine.place_order("MARKET", "BUY", 1e6, 0.0)),
+            Some(RejectReason::InsufficientCash)
+        );
+    }
+
+    #[test]
+    fn test_reject_buys_beyond_committed_cash() {
+        let mut engine = Engine::with_config(EngineConfig { initial_cash: 100.0, ..test_config() }).unwrap();
+
+        let first = engine.place_order("LIMIT", "BUY", 0.9, 100.0).unwrap();
+        assert_eq!(
+            rejection(engine.place_order("LIMIT", "BUY", 0.9, 100.0)),
+            Some(RejectReason::InsufficientCash)
+        );
+
+        // Cancelling frees the committed cash
+        engine.cancel_order(first).unwrap();
+        engine.place_order("LIMIT", "BUY", 0.9, 100.0).unwrap();
+        assert_eq!(engine.open_orders().len(), 1);
+    }
+
+    #[test]
+    fn test_reject_invalid_order_and_price_band() {
+        let config = EngineConfig {
+            price_band_bps: Some(100.0),
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        let err = engine.place_order("LIMIT", "SELL", 0.0, 100.0).unwrap_err();
+        assert!(matches!(err, EngineError::OrderRejected(RejectReason::InvalidOrder)));
+        assert_eq!(err.to_string(), "Order rejected: invalid order");
+
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(
+            rejection(engine.place_order("LIMIT", "SELL", 1.0, 110.0)),
+            Some(RejectReason::PriceOutsideBand)
+        );
+        assert_eq!(EngineError::StepFailed(-1).reject_reason(), None);
+    }
+
+    #[test]
+    fn test_reject_non_finite_order_price_and_qty() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+
+        let err = engine.place_order("LIMIT", "BUY", 1.0, f64::NAN).unwrap_err();
+        assert!(matches!(err, EngineError::NonFiniteOrder { field: "price", .. }));
+        assert_eq!(err.reject_reason(), Some(RejectReason::InvalidOrder));
+        assert!(matches!(
+            engine.place_order("MARKET", "SELL", f64::INFINITY, 0.0),
+            Err(EngineError::NonFiniteOrder { field: "qty", .. })
+        ));
+        assert!(matches!(
+            engine.place_order("LIMIT", "SELL", 1.0, f64::NEG_INFINITY),
+            Err(EngineError::NonFiniteOrder { field: "price", .. })
+        ));
+        assert!(engine.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_reject_below_minimum_qty_and_notional() {
+        let config = EngineConfig {
+            min_order_qty: Some(0.5),
+            min_order_notional: Some(100.0),
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        let err = engine.place_order("LIMIT", "BUY", 0.4, 1_000.0).unwrap_err();
+        assert!(matches!(err, EngineError::BelowMinimum { field: "qty", .. }));
+        assert_eq!(err.reject_reason(), Some(RejectReason::BelowMinimum));
+        assert!(matches!(
+            engine.place_order("LIMIT", "BUY", 0.5, 150.0),
+            Err(EngineError::BelowMinimum { field: "notional", .. })
+        ));
+
+        // Market orders skip the notional check until a price is known
+        engine.place_order("MARKET", "SELL", 0.5, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "BUY").unwrap();
+        assert!(matches!(
+            engine.place_order("MARKET", "BUY", 0.5, 0.0),
+            Err(EngineError::BelowMinimum { field: "notional", .. })
+        ));
+        assert!(engine.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_minimum_order_size_boundary() {
+        let config = EngineConfig {
+            min_order_qty: Some(0.5),
+            min_order_notional: Some(100.0),
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        // Exactly at both minimums is accepted
+        engine.place_order("LIMIT", "BUY", 0.5, 200.0).unwrap();
+        engine.place_order("LIMIT", "BUY", 1.0, 100.0).unwrap();
+        assert!(engine.place_order("LIMIT", "BUY", 0.499999, 1_000.0).is_err());
+        assert!(engine.place_order("LIMIT", "BUY", 1.0, 99.99).is_err());
+        assert_eq!(engine.open_orders().len(), 2);
+    }
+
+    #[test]
+    fn test_limit_fill_model_at_exact_price() {
+        for (model, fills_at_level) in [(LimitFillModel::OnTouch, true), (LimitFillModel::OnThrough, false)] {
+            let config = EngineConfig {
+                limit_fill_model: model,
+                ..test_config()
+            };
+            let mut engine = Engine::with_config(config).unwrap();
+
+            engine.place_order("LIMIT", "BUY", 1.0, 99.0).unwrap();
+            engine.place_order("LIMIT", "SELL", 1.0, 101.0).unwrap();
+            engine.step_tick(1, 9_900, 1.0, "SELL").unwrap();
+            engine.step_tick(2, 10_100, 1.0, "BUY").unwrap();
+            assert_eq!(engine.fills().len(), if fills_at_level { 2 } else { 0 }, "{:?}", model);
+
+            // A trade one tick through fills either way
+            engine.step_tick(3, 9_899, 1.0, "SELL").unwrap();
+            engine.step_tick(4, 10_101, 1.0, "BUY").unwrap();
+            assert_eq!(engine.fills().len(), 2, "{:?}", model);
+            assert!(engine.fills().iter().all(|f| f.price_tick == 9_900 || f.price_tick == 10_100));
+        }
+    }
+
+    #[test]
+    fn test_reduce_only_clamps_to_position() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.get_snapshot().position, 2.0);
+
+        // Capped at the position when placed...
+        let id = engine.place_order_reduce_only("MARKET", "SELL", 5.0, 0.0).unwrap();
+        let open = engine.open_orders();
+        assert_eq!(open[0].order_id, id);
+        assert_eq!(open[0].qty, 2.0);
+        assert!(open[0].reduce_only);
+        engine.step_tick(2, 10_000, 1.0, "BUY").unwrap();
+        assert_eq!(engine.get_snapshot().position, 0.0);
+
+        // ...and again at fill time, so two resting exits cannot flip it
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(3, 10_000, 1.0, "SELL").unwrap();
+        engine.place_order_reduce_only("LIMIT", "SELL", 1.5, 101.0).unwrap();
+        engine.place_order_reduce_only("LIMIT", "SELL", 1.5, 101.0).unwrap();
+        engine.step_tick(4, 10_100, 1.0, "BUY").unwrap();
+        assert_eq!(engine.get_snapshot().position, 0.0);
+        assert!(engine.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_reduce_only_rejected_without_position() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        let result = engine.place_order_reduce_only("MARKET", "SELL", 1.0, 0.0);
+        assert!(matches!(result, Err(EngineError::ReduceOnlyNoPosition)));
+        assert_eq!(rejection(result), Some(RejectReason::ReduceOnlyNoPosition));
+
+        // Same side as the position would grow it
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        assert!(matches!(
+            engine.place_order_reduce_only("LIMIT", "BUY", 1.0, 99.0),
+            Err(EngineError::ReduceOnlyNoPosition)
+        ));
+        assert!(engine.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_reject_book_full_and_duplicate_id() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+
+        for _ in 0..MAX_OPEN_ORDERS {
+            engine.place_order("LIMIT", "SELL", 1.0, 200.0).unwrap();
+        }
+        assert_eq!(
+            rejection(engine.place_order("LIMIT", "SELL", 1.0, 200.0)),
+            Some(RejectReason::BookFull)
+        );
+
+        // The safe API never reuses ids, so submit a clash directly
+        let clash = order_t {
+            order_id: 2,
+            type_: order_type_t::ORDER_TYPE_LIMIT,
+            side: side_t::SIDE_SELL,
+            qty: 1_000_000,
+            price_tick: 20_000,
+            client_tag: 0,
+            reduce_only: 0,
+        };
+        assert!(matches!(
+            engine.place_order_raw(&clash),
+            Err(EngineError::OrderRejected(RejectReason::DuplicateId))
+        ));
+        assert_eq!(unsafe { engine_last_reject_reason(engine.handle) }, reject_reason_t::REJECT_DUPLICATE_ID as i32);
+    }
+
+    #[test]
+    fn test_borrow_cost_accrues_on_short() {
+        const DAY_MS: i64 = 86_400_000;
+        let config = EngineConfig {
+            borrow_fee_bps_per_day: 10.0,
+            ..test_config()
+        };
+        let mut engine = Engine::with_config(config).unwrap();
+
+        engine.place_order("MARKET", "SELL", 2.0, 0.0).unwrap();
+        engine.step_tick(DAY_MS, 10_000, 1.0, "BUY").unwrap();
+        let cash_after_entry = engine.cash();
+
+        // Short 2 @ 100.00 = 200 notional, 10 bps/day = 0.2 per day
+        for day in 2..=4 {
+            engine.step_tick(day * DAY_MS, 10_000, 1.0, "BUY").unwrap();
+        }
+        assert!((cash_after_entry - engine.cash() - 0.6).abs() < 1e-9);
+
+        // Half a day via the clock alone
+        engine.advance_clock(4 * DAY_MS + DAY_MS / 2).unwrap();
+        assert!((cash_after_entry - engine.cash() - 0.7).abs() < 1e-9);
+
+        // Longs pay nothing
+        engine.place_order("MARKET", "BUY", 4.0, 0.0).unwrap();
+        engine.step_tick(5 * DAY_MS, 10_000, 1.0, "SELL").unwrap();
+        let cash_long = engine.cash();
+        engine.step_tick(9 * DAY_MS, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.cash(), cash_long);
+    }
+
+    #[test]
+    fn test_scheduled_funding_charges_long() {
+        const HOUR_MS: i64 = 3_600_000;
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.set_funding_schedule(FundingSchedule::periodic(8 * HOUR_MS, 8 * HOUR_MS, 3, 0.0001)).unwrap();
+
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(HOUR_MS, 10_000, 1.0, "SELL").unwrap();
+        let cash_after_entry = engine.cash();
+
+        // Long 2 @ 100.00 pays 2 * 100 * 0.0001 = 0.02 per funding time
+        for (i, expected_paid) in [(1, 0.02), (2, 0.04), (3, 0.06)] {
+            engine.step_tick(i * 8 * HOUR_MS + 1, 10_000, 1.0, "SELL").unwrap();
+            assert!((cash_after_entry - engine.cash() - expected_paid).abs() < 1e-9);
+        }
+
+        let events = engine.funding_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].ts_ms, 8 * HOUR_MS);
+        assert!((events[2].amount + 0.02).abs() < 1e-9);
+
+        // Past the end of the schedule; manual funding still works
+        engine.step_tick(40 * HOUR_MS, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.funding_events().len(), 3);
+        let received = engine.apply_funding(-0.0005).unwrap();
+        assert!((received - 0.1).abs() < 1e-9);
+
+        engine.reset();
+        assert!(engine.funding_events().is_empty());
+    }
+
+    #[test]
+    fn test_non_finite_funding_rate_rejected() {
+        let mut engine = Engine::with_config(test_config()).unwrap();
+        engine.mark_to(100.0).unwrap();
+        engine.place_order("MARKET", "BUY", 2.0, 0.0).unwrap();
+        engine.step_tick(1, 10_000, 1.0, "SELL").unwrap();
+        let cash = engine.cash();
+
+        for rate in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(engine.apply_funding(rate), Err(EngineError::InvalidFundingRate(_))));
+        }
+        assert_eq!(engine.cash(), cash);
+        assert!(engine.funding_events().is_empty());
+
+        // A bad entry refuses the whole schedule and keeps the previous one
+        engine.set_funding_schedule(FundingSchedule::new(vec![(10, 0.0001)])).unwrap();
+        let bad = FundingSchedule::new(vec![(20, 0.0001), (30, f64::NAN)]);
+        assert!(matches!(engine.set_funding_schedule(bad), Err(EngineError::InvalidFundingRate(_))));
+        engine.step_tick(40, 10_000, 1.0, "SELL").unwrap();
+        assert_eq!(engine.funding_events().len(), 1);
+        assert_eq!(engine.funding_events()[0].ts_ms, 10);
+    }
+}
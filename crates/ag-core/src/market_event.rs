@@ -34,6 +34,36 @@ pub struct AggTrade {
     pub side: u8, // 0 = BUY, 1 = SELL
 }
 
+/// Control signal injected into an event stream by adapters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlSignal {
+    /// The trading session closed at the given timestamp
+    SessionEnd(i64),
+
+    /// No more events will follow
+    EndOfData,
+
+    /// Clock advance with no market data, e.g. from an idle live feed
+    Heartbeat(i64),
+
+    /// The bar that follows (opening at the given timestamp) is a trading
+    /// halt; see `ZeroVolumePolicy::MarkHalt`
+    Halt(i64),
+}
+
+impl ControlSignal {
+    /// Timestamp of the signal; `EndOfData` sorts after every other event
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        match self {
+            ControlSignal::SessionEnd(ts_ms)
+            | ControlSignal::Heartbeat(ts_ms)
+            | ControlSignal::Halt(ts_ms) => *ts_ms,
+            ControlSignal::EndOfData => i64::MAX,
+        }
+    }
+}
+
 /// Unified market event wrapper
 #[derive(Debug, Clone, Copy)]
 pub enum MarketEvent {
@@ -42,6 +72,9 @@ pub enum MarketEvent {
 
     /// Bar (OHLC) event
     Bar(Candle),
+
+    /// Non-market control signal (session close, end of data, clock tick)
+    Control(ControlSignal),
 }
 
 impl MarketEvent {
@@ -51,6 +84,7 @@ impl MarketEvent {
         match self {
             MarketEvent::Trade(trade) => trade.ts_ms,
             MarketEvent::Bar(candle) => candle.ts_open,
+            MarketEvent::Control(signal) => signal.timestamp(),
         }
     }
 
@@ -65,21 +99,51 @@ impl MarketEvent {
     pub fn is_bar(&self) -> bool {
         matches!(self, MarketEvent::Bar(_))
     }
+
+    /// Check if this is a control signal
+    #[inline]
+    pub fn is_control(&self) -> bool {
+        matches!(self, MarketEvent::Control(_))
+    }
 }
 
 // ============================================================================
 // Event Loop Adapter
 // ============================================================================
 
-use crate::candle_parser::{CandleParser, ParseError};
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::candle_file::MmapCandles;
+use crate::candle_parser::{CandleParser, CsvCandleIter, JsonCandleIter, ParseError};
+use crate::trade_parser::{CsvTradeIter, JsonTradeIter, TradeParser};
+use crate::{Engine, EngineError, Snapshot};
+use ag_core_sys::{side_t, tick_event_t};
+use std::io::Read;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Metrics for candle ingestion
+///
+/// One instance can be shared by several adapters or feeders (see
+/// [`CandleEventAdapter::with_metrics`]), e.g. one per symbol: the counters
+/// then hold totals across all of them, `last_ts_ms` is whichever event was
+/// recorded last, and the rate blends the feeders' individual rates rather
+/// than summing them.
 #[derive(Debug, Default)]
 pub struct IngestionMetrics {
     pub candles_processed: AtomicU64,
     pub candles_rejected: AtomicU64,
     pub parse_errors: AtomicU64,
+    /// Candles accepted after `Candle::repair` (only in repair mode)
+    pub candles_repaired: AtomicU64,
+    /// Candles flagged as trading halts (only with `ZeroVolumePolicy::MarkHalt`)
+    pub candles_halted: AtomicU64,
+    /// Candles flagged by an attached `candle::AnomalyDetector`
+    pub candles_anomalous: AtomicU64,
+    /// Timestamp of the most recently processed event (0 until the first one)
+    pub last_ts_ms: AtomicI64,
+    /// EWMA of processed events per wall-clock second, as `f64` bits
+    pub events_per_sec: AtomicU64,
 }
 
 impl IngestionMetrics {
@@ -87,12 +151,89 @@ impl IngestionMetrics {
         Self::default()
     }
 
+    /// Timestamp of the most recently processed event, readable while ingestion runs
+    #[inline]
+    pub fn last_ts_ms(&self) -> i64 {
+        self.last_ts_ms.load(Ordering::Relaxed)
+    }
+
+    /// Smoothed processing rate in events per second (0 until measured),
+    /// readable while ingestion runs
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        f64::from_bits(self.events_per_sec.load(Ordering::Relaxed))
+    }
+
+    /// Fold one measured rate into the EWMA
+    ///
+    /// A plain load and store: readers see either the old or the new value,
+    /// and feeders sharing the metrics may drop each other's samples, which
+    /// a smoothed rate tolerates.
+    fn record_rate(&self, sample: f64) {
+        let previous = self.rate();
+        let rate = if previous == 0.0 {
+            sample
+        } else {
+            RATE_ALPHA * sample + (1.0 - RATE_ALPHA) * previous
+        };
+        self.events_per_sec.store(rate.to_bits(), Ordering::Relaxed);
+    }
+
     pub fn snapshot(&self) -> IngestionSnapshot {
         IngestionSnapshot {
             candles_processed: self.candles_processed.load(Ordering::Relaxed),
             candles_rejected: self.candles_rejected.load(Ordering::Relaxed),
             parse_errors: self.parse_errors.load(Ordering::Relaxed),
+            candles_repaired: self.candles_repaired.load(Ordering::Relaxed),
+            candles_halted: self.candles_halted.load(Ordering::Relaxed),
+            candles_anomalous: self.candles_anomalous.load(Ordering::Relaxed),
+            last_ts_ms: self.last_ts_ms.load(Ordering::Relaxed),
+            events_per_sec: self.rate(),
+        }
+    }
+}
+
+/// Weight of the newest sample in [`IngestionMetrics::rate`]
+const RATE_ALPHA: f64 = 0.3;
+
+/// Wall-clock time each rate sample covers
+const RATE_WINDOW: Duration = Duration::from_millis(100);
+
+/// Events between clock reads, so timing stays off the per-event path
+const RATE_CHECK_EVERY: u64 = 64;
+
+/// Producer-side state for sampling the event rate
+#[derive(Debug)]
+struct RateSampler {
+    window_start: Option<Instant>,
+    events: u64,
+}
+
+impl RateSampler {
+    fn new() -> Self {
+        Self { window_start: None, events: 0 }
+    }
+
+    /// Count one event, closing the window once it is long enough
+    #[inline]
+    fn on_event(&mut self, metrics: &IngestionMetrics) {
+        let start = *self.window_start.get_or_insert_with(Instant::now);
+        self.events += 1;
+        if self.events.is_multiple_of(RATE_CHECK_EVERY) && start.elapsed() >= RATE_WINDOW {
+            self.flush(metrics);
+        }
+    }
+
+    /// Record the rate over the open window, however short
+    fn flush(&mut self, metrics: &IngestionMetrics) {
+        let Some(start) = self.window_start.take() else {
+            return;
+        };
+        let elapsed = start.elapsed().as_secs_f64();
+        if self.events > 0 && elapsed > 0.0 {
+            metrics.record_rate(self.events as f64 / elapsed);
         }
+        self.events = 0;
     }
 }
 
@@ -101,19 +242,43 @@ pub struct IngestionSnapshot {
     pub candles_processed: u64,
     pub candles_rejected: u64,
     pub parse_errors: u64,
+    pub candles_repaired: u64,
+    pub candles_halted: u64,
+    pub candles_anomalous: u64,
+    pub last_ts_ms: i64,
+    /// Smoothed events per second when the snapshot was taken
+    pub events_per_sec: f64,
 }
 
 /// Adapter that converts CandleParser into MarketEvent stream
+///
+/// Candles the parser flags as halts are preceded by
+/// `ControlSignal::Halt(ts_open)`, so strategies can skip trading on them.
 pub struct CandleEventAdapter<P: CandleParser> {
     parser: P,
-    metrics: IngestionMetrics,
+    metrics: Arc<IngestionMetrics>,
+    rate: RateSampler,
+    held: Option<MarketEvent>,
+    /// Parser's repaired and halted counts already added to the metrics
+    repaired_seen: u64,
+    halted_seen: u64,
 }
 
 impl<P: CandleParser> CandleEventAdapter<P> {
     pub fn new(parser: P) -> Self {
+        Self::with_metrics(parser, Arc::new(IngestionMetrics::new()))
+    }
+
+    /// Adapter recording into existing metrics, possibly shared with other
+    /// adapters, which then see combined counts
+    pub fn with_metrics(parser: P, metrics: Arc<IngestionMetrics>) -> Self {
         Self {
             parser,
-            metrics: IngestionMetrics::new(),
+            metrics,
+            rate: RateSampler::new(),
+            held: None,
+            repaired_seen: 0,
+            halted_seen: 0,
         }
     }
 
@@ -121,6 +286,11 @@ impl<P: CandleParser> CandleEventAdapter<P> {
         &self.metrics
     }
 
+    /// Shared handle to the metrics, readable from other threads while the adapter runs
+    pub fn metrics_handle(&self) -> Arc<IngestionMetrics> {
+        Arc::clone(&self.metrics)
+    }
+
     pub fn tick_size(&self) -> f64 {
         self.parser.tick_size()
     }
@@ -130,36 +300,203 @@ impl<P: CandleParser> Iterator for CandleEventAdapter<P> {
     type Item = Result<MarketEvent, ParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.parser.next() {
+        if let Some(event) = self.held.take() {
+            return Some(Ok(event));
+        }
+
+        let next = self.parser.next();
+        // Add what this parser gained, so shared metrics sum across adapters
+        let repaired = self.parser.repaired_count();
+        self.metrics.candles_repaired.fetch_add(repaired - self.repaired_seen, Ordering::Relaxed);
+        self.repaired_seen = repaired;
+        let halted = self.parser.halted_count();
+        self.metrics.candles_halted.fetch_add(halted - self.halted_seen, Ordering::Relaxed);
+        self.halted_seen = halted;
+
+        match next {
             Some(Ok(candle)) => {
                 self.metrics.candles_processed.fetch_add(1, Ordering::Relaxed);
+                self.metrics.last_ts_ms.store(candle.ts_open, Ordering::Relaxed);
+                self.rate.on_event(&self.metrics);
+
+                if self.parser.last_halted() {
+                    self.held = Some(MarketEvent::Bar(candle));
+                    return Some(Ok(MarketEvent::Control(ControlSignal::Halt(candle.ts_open))));
+                }
+
                 Some(Ok(MarketEvent::Bar(candle)))
             }
             Some(Err(e)) => {
                 self.metrics.parse_errors.fetch_add(1, Ordering::Relaxed);
                 Some(Err(e))
             }
-            None => None,
+            None => {
+                self.rate.flush(&self.metrics);
+                None
+            }
         }
     }
 }
 
+/// Adapter that converts TradeParser into MarketEvent stream
+pub struct TradeEventAdapter<P: TradeParser> {
+    parser: P,
+}
+
+impl<P: TradeParser> TradeEventAdapter<P> {
+    pub fn new(parser: P) -> Self {
+        Self { parser }
+    }
+}
+
+impl<P: TradeParser> Iterator for TradeEventAdapter<P> {
+    type Item = Result<MarketEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next().map(|trade| trade.map(MarketEvent::Trade))
+    }
+}
+
+/// Streaming parser producing unified market events
+pub trait EventParser: Iterator<Item = Result<MarketEvent, ParseError>> {
+    /// Get the tick size used for quantization
+    fn tick_size(&self) -> f64;
+}
+
+impl<P: CandleParser> EventParser for CandleEventAdapter<P> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+}
+
+impl<P: TradeParser> EventParser for TradeEventAdapter<P> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+}
+
+/// Candles already held in one contiguous buffer, such as a mapped binary
+/// file or an in-memory batch
+///
+/// [`CandleEventAdapter`] copies every candle into a [`MarketEvent::Bar`].
+/// Consumers that only read candles can borrow them from such a buffer
+/// instead.
+pub trait CandleBuffer {
+    /// All candles, in order
+    fn candles(&self) -> &[Candle];
+
+    /// Borrow each candle in place, without copying
+    ///
+    /// The references borrow `self` and cannot outlive it. For
+    /// [`MmapCandles`] they point straight into the mapping, which is
+    /// unmapped when the `MmapCandles` is dropped.
+    fn events_ref(&self) -> impl Iterator<Item = &Candle> {
+        self.candles().iter()
+    }
+}
+
+impl CandleBuffer for MmapCandles {
+    fn candles(&self) -> &[Candle] {
+        self.as_slice()
+    }
+}
+
+impl CandleBuffer for [Candle] {
+    fn candles(&self) -> &[Candle] {
+        self
+    }
+}
+
+impl CandleBuffer for Vec<Candle> {
+    fn candles(&self) -> &[Candle] {
+        self
+    }
+}
+
+// ============================================================================
+// Parser factory
+// ============================================================================
+
+/// Build an event parser for the given data mode
+///
+/// Dispatches on both the mode and the source type:
+/// - `OHLC(Csv)` / `OHLC(Json)` -> `CsvCandleIter` / `JsonCandleIter`
+/// - `AggTrades(Csv)` / `AggTrades(Json)` -> `CsvTradeIter` / `JsonTradeIter`
+///
+/// Any other combination (raw ticks, Parquet, WebSocket) returns
+/// `ParseError::Unsupported`.
+pub fn make_parser<R: Read + 'static>(
+    mode: DataMode,
+    reader: R,
+    tick_size: f64,
+) -> Result<Box<dyn EventParser>, ParseError> {
+    match mode {
+        DataMode::OHLC(SourceType::Csv) => Ok(Box::new(CandleEventAdapter::new(
+            CsvCandleIter::new(reader, tick_size)?,
+        ))),
+        DataMode::OHLC(SourceType::Json) => Ok(Box::new(CandleEventAdapter::new(
+            JsonCandleIter::new(reader, tick_size),
+        ))),
+        DataMode::AggTrades(SourceType::Csv) => Ok(Box::new(TradeEventAdapter::new(
+            CsvTradeIter::new(reader, tick_size)?,
+        ))),
+        DataMode::AggTrades(SourceType::Json) => Ok(Box::new(TradeEventAdapter::new(
+            JsonTradeIter::new(reader, tick_size),
+        ))),
+        other => Err(ParseError::Unsupported(format!("{:?}", other))),
+    }
+}
+
 // ============================================================================
 // Channel-based Event Feed (for async event loops)
 // ============================================================================
 
 use std::sync::mpsc::{Receiver, channel};
 use std::thread;
+use thiserror::Error;
 
 /// Spawns a background thread that feeds candles into a channel
 pub fn spawn_candle_feeder<P: CandleParser + Send + 'static>(
+    parser: P,
+    buffer_size: usize,
+) -> (Receiver<Result<MarketEvent, ParseError>>, thread::JoinHandle<IngestionSnapshot>) {
+    let (rx, _metrics, handle) = spawn_candle_feeder_with_metrics(parser, buffer_size);
+    (rx, handle)
+}
+
+/// Like [`spawn_candle_feeder`], but also returns a shared handle to the live metrics
+///
+/// The handle can be polled from any thread while the feeder runs, e.g. to show
+/// "processed up to <last_ts_ms>" without consuming the channel.
+pub fn spawn_candle_feeder_with_metrics<P: CandleParser + Send + 'static>(
+    parser: P,
+    buffer_size: usize,
+) -> (
+    Receiver<Result<MarketEvent, ParseError>>,
+    Arc<IngestionMetrics>,
+    thread::JoinHandle<IngestionSnapshot>,
+) {
+    let metrics = Arc::new(IngestionMetrics::new());
+    let (rx, handle) = spawn_candle_feeder_shared(parser, buffer_size, Arc::clone(&metrics));
+    (rx, metrics, handle)
+}
+
+/// Like [`spawn_candle_feeder`], recording into `metrics`, which may be
+/// shared with other feeders
+///
+/// Feeders for several symbols can then be watched through one handle
+/// whose counts are totals across all of them. The snapshot each thread
+/// returns is of the shared metrics as that feeder finished.
+pub fn spawn_candle_feeder_shared<P: CandleParser + Send + 'static>(
     parser: P,
     _buffer_size: usize,
+    metrics: Arc<IngestionMetrics>,
 ) -> (Receiver<Result<MarketEvent, ParseError>>, thread::JoinHandle<IngestionSnapshot>) {
     let (tx, rx) = channel();
+    let adapter = CandleEventAdapter::with_metrics(parser, metrics);
 
     let handle = thread::spawn(move || {
-        let mut adapter = CandleEventAdapter::new(parser);
+        let mut adapter = adapter;
 
         for event in &mut adapter {
             if tx.send(event).is_err() {
@@ -178,141 +515,1137 @@ pub fn spawn_candle_feeder<P: CandleParser + Send + 'static>(
 // Direct integration with engine
 // ============================================================================
 
+/// Error reported to the `on_error` policy of [`process_candles`]
+#[derive(Debug, Error)]
+pub enum ProcessingError {
+    #[error("Parse error: {0}")]
+    Parse(ParseError),
+
+    #[error("Engine error processing event: {0}")]
+    Engine(EngineError),
+}
+
+/// Error policy that logs to stderr and keeps going (the historical behavior)
+pub fn log_and_continue(err: ProcessingError) -> ControlFlow<()> {
+    eprintln!("{}", err);
+    ControlFlow::Continue(())
+}
+
+/// Error policy that silently skips errors and keeps going
+pub fn skip_errors(_err: ProcessingError) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
+
+/// Error policy that stops processing at the first error
+pub fn abort_on_error(_err: ProcessingError) -> ControlFlow<()> {
+    ControlFlow::Break(())
+}
+
 /// Process candles from parser and feed directly to engine callback
 ///
 /// This is a zero-copy streaming approach where candles are processed
 /// one at a time without buffering.
-pub fn process_candles<P, F>(
+///
+/// Parse errors and errors returned by `on_event` are handed to `on_error`,
+/// which decides whether to continue (`ControlFlow::Continue`) or stop
+/// (`ControlFlow::Break`). Stopping is not an error: the metrics gathered so
+/// far are returned, and callers that need the error can capture it in the
+/// closure.
+pub fn process_candles<P, F, E>(
     parser: P,
     mut on_event: F,
-) -> Result<IngestionSnapshot, ParseError>
+    mut on_error: E,
+) -> Result<IngestionSnapshot, EngineError>
 where
     P: CandleParser,
-    F: FnMut(MarketEvent) -> Result<(), Box<dyn std::error::Error>>,
+    F: FnMut(MarketEvent) -> Result<(), EngineError>,
+    E: FnMut(ProcessingError) -> ControlFlow<()>,
 {
     let mut adapter = CandleEventAdapter::new(parser);
 
     for event_result in &mut adapter {
-        match event_result {
-            Ok(event) => {
-                if let Err(e) = on_event(event) {
-                    eprintln!("Engine error processing event: {}", e);
-                    // Continue processing even if engine errors
-                }
-            }
-            Err(parse_err) => {
-                // Log parse error but continue
-                eprintln!("Parse error: {}", parse_err);
-            }
+        let err = match event_result {
+            Ok(event) => match on_event(event) {
+                Ok(()) => continue,
+                Err(e) => ProcessingError::Engine(e),
+            },
+            Err(parse_err) => ProcessingError::Parse(parse_err),
+        };
+
+        if on_error(err).is_break() {
+            break;
         }
     }
 
     Ok(adapter.metrics.snapshot())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::candle_parser::CsvCandleIter;
-    use std::io::Cursor;
-
-    #[test]
-    fn test_market_event_timestamp() {
-        let candle = Candle {
-            ts_open: 1609459200000,
-            ts_close: 1609459260000,
-            open_tick: 4200,
-            high_tick: 4250,
-            low_tick: 4150,
-            close_tick: 4220,
-            volume_scaled: 1_500_000_000,
-            trade_count: 42,
-        };
+/// Process candles like [`process_candles`], but stop at the first error
+///
+/// Parse errors are converted into [`EngineError::Ingestion`] and returned
+/// together with any error from the callback, so callers can propagate them
+/// with `?` instead of having them logged and skipped.
+pub fn try_process_candles<P, F>(
+    parser: P,
+    mut on_event: F,
+) -> Result<IngestionSnapshot, EngineError>
+where
+    P: CandleParser,
+    F: FnMut(MarketEvent) -> Result<(), EngineError>,
+{
+    let mut adapter = CandleEventAdapter::new(parser);
 
-        let event = MarketEvent::Bar(candle);
-        assert_eq!(event.timestamp(), 1609459200000);
-        assert!(event.is_bar());
-        assert!(!event.is_trade());
+    for event_result in &mut adapter {
+        on_event(event_result?)?;
     }
 
-    #[test]
-    fn test_candle_event_adapter() {
-        let csv_data = "\
-timestamp,open,high,low,close,volume
-1609459200000,42000,42500,41500,42200,1500
-1609459260000,42200,42800,42100,42700,2000
-";
+    Ok(adapter.metrics.snapshot())
+}
 
-        let cursor = Cursor::new(csv_data.as_bytes());
-        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
-        let mut adapter = CandleEventAdapter::new(parser);
+/// Process candles like [`try_process_candles`], stopping before the first
+/// event later than `stop_ts_ms`
+///
+/// For walk-forward runs: the candle that crossed the boundary is not
+/// handed to `on_event` or counted in the metrics, but returned as the
+/// second value (as a [`MarketEvent::Bar`]) so the caller can feed it first
+/// when resuming with a fresh parser. `None` means the input ended first.
+pub fn process_candles_until<P, F>(
+    parser: P,
+    stop_ts_ms: i64,
+    mut on_event: F,
+) -> Result<(IngestionSnapshot, Option<MarketEvent>), EngineError>
+where
+    P: CandleParser,
+    F: FnMut(MarketEvent) -> Result<(), EngineError>,
+{
+    // Hold the boundary candle back before the adapter counts it
+    let mut adapter = CandleEventAdapter::new(StopAfter {
+        parser,
+        stop_ts_ms,
+        stopped: None,
+    });
 
-        let event1 = adapter.next().unwrap().unwrap();
-        assert!(event1.is_bar());
+    for event_result in &mut adapter {
+        on_event(event_result?)?;
+    }
 
-        let event2 = adapter.next().unwrap().unwrap();
-        assert!(event2.is_bar());
+    let stopped = adapter.parser.stopped.take().map(MarketEvent::Bar);
+    Ok((adapter.metrics.snapshot(), stopped))
+}
 
-        assert!(adapter.next().is_none());
+/// Parser ending at the first candle opening after `stop_ts_ms`, which it
+/// keeps; see [`process_candles_until`]
+struct StopAfter<P: CandleParser> {
+    parser: P,
+    stop_ts_ms: i64,
+    stopped: Option<Candle>,
+}
 
-        let metrics = adapter.metrics.snapshot();
-        assert_eq!(metrics.candles_processed, 2);
-        assert_eq!(metrics.parse_errors, 0);
+impl<P: CandleParser> Iterator for StopAfter<P> {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stopped.is_some() {
+            return None;
+        }
+        match self.parser.next()? {
+            Ok(candle) if candle.ts_open > self.stop_ts_ms => {
+                self.stopped = Some(candle);
+                None
+            }
+            next => Some(next),
+        }
     }
+}
 
-    #[test]
-    fn test_process_candles() {
-        let csv_data = "\
-timestamp,open,high,low,close,volume
-1609459200000,42000,42500,41500,42200,1500
-";
+impl<P: CandleParser> CandleParser for StopAfter<P> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
 
-        let cursor = Cursor::new(csv_data.as_bytes());
-        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+    fn size_hint_total(&self) -> Option<usize> {
+        self.parser.size_hint_total()
+    }
 
-        let mut events_received = Vec::new();
+    fn repaired_count(&self) -> u64 {
+        self.parser.repaired_count()
+    }
 
-        let metrics = process_candles(parser, |event| {
-            events_received.push(event);
-            Ok(())
-        }).unwrap();
+    fn halted_count(&self) -> u64 {
+        self.parser.halted_count()
+    }
 
-        assert_eq!(events_received.len(), 1);
-        assert_eq!(metrics.candles_processed, 1);
+    fn last_halted(&self) -> bool {
+        self.parser.last_halted()
     }
+}
 
-    #[test]
-    fn test_spawn_candle_feeder() {
-        let csv_data = "\
-timestamp,open,high,low,close,volume
-1609459200000,42000,42500,41500,42200,1500
-1609459260000,42200,42800,42100,42700,2000
-1609459320000,42700,43000,42600,42900,1800
-";
+/// Progress of a long-running replay, handed to a [`ProgressReporter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Candles read so far, including ones that failed to parse
+    pub events: u64,
+    /// `ts_open` of the most recent candle (0 if none parsed yet)
+    pub ts_ms: i64,
+    /// Total candles expected, from [`CandleParser::size_hint_total`]
+    pub total: Option<usize>,
+    /// Estimated wall-clock time remaining, when `total` is known
+    pub eta: Option<std::time::Duration>,
+}
 
-        let cursor = Cursor::new(csv_data.as_bytes());
-        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+/// Receiver of periodic [`Progress`] updates
+///
+/// Implemented for any `FnMut(&Progress)` closure.
+pub trait ProgressReporter {
+    fn report(&mut self, progress: &Progress);
+}
 
-        let (rx, handle) = spawn_candle_feeder(parser, 100);
+impl<F: FnMut(&Progress)> ProgressReporter for F {
+    fn report(&mut self, progress: &Progress) {
+        self(progress)
+    }
+}
 
-        let mut received_count = 0;
-        for event_result in rx {
-            match event_result {
-                Ok(MarketEvent::Bar(_)) => {
-                    received_count += 1;
-                }
-                Ok(MarketEvent::Trade(_)) => {
-                    panic!("Unexpected trade event");
-                }
-                Err(e) => {
-                    panic!("Parse error: {}", e);
-                }
-            }
+/// Parser wrapper that reports progress every `every` candles
+///
+/// Wrap the parser passed to [`process_candles`], [`replay`] or any other
+/// consumer; between reports the only cost is a counter increment.
+///
+/// ```ignore
+/// let parser = ProgressParser::new(parser, 10_000, |p: &Progress| eprintln!("{} candles", p.events));
+/// replay(parser, &mut engine)?;
+/// ```
+pub struct ProgressParser<P: CandleParser, R: ProgressReporter> {
+    parser: P,
+    reporter: R,
+    every: u64,
+    events: u64,
+    ts_ms: i64,
+    total: Option<usize>,
+    started: std::time::Instant,
+}
+
+impl<P: CandleParser, R: ProgressReporter> ProgressParser<P, R> {
+    /// `every` is clamped to at least 1
+    pub fn new(parser: P, every: u64, reporter: R) -> Self {
+        let total = parser.size_hint_total();
+        Self {
+            parser,
+            reporter,
+            every: every.max(1),
+            events: 0,
+            ts_ms: 0,
+            total,
+            started: std::time::Instant::now(),
         }
+    }
 
-        let metrics = handle.join().expect("Thread panicked");
-        assert_eq!(received_count, 3);
-        assert_eq!(metrics.candles_processed, 3);
-        assert_eq!(metrics.parse_errors, 0);
+    /// Unwrap the reporter, e.g. to inspect state it collected
+    pub fn into_reporter(self) -> R {
+        self.reporter
+    }
+
+    fn eta(&self) -> Option<std::time::Duration> {
+        let remaining = (self.total? as u64).saturating_sub(self.events);
+        let per_event = self.started.elapsed().as_secs_f64() / self.events as f64;
+        std::time::Duration::try_from_secs_f64(per_event * remaining as f64).ok()
+    }
+}
+
+impl<P: CandleParser, R: ProgressReporter> Iterator for ProgressParser<P, R> {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.parser.next()?;
+        if let Ok(candle) = &next {
+            self.ts_ms = candle.ts_open;
+        }
+
+        self.events += 1;
+        if self.events.is_multiple_of(self.every) {
+            let progress = Progress {
+                events: self.events,
+                ts_ms: self.ts_ms,
+                total: self.total,
+                eta: self.eta(),
+            };
+            self.reporter.report(&progress);
+        }
+
+        Some(next)
+    }
+}
+
+impl<P: CandleParser, R: ProgressReporter> CandleParser for ProgressParser<P, R> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+
+    fn size_hint_total(&self) -> Option<usize> {
+        self.total
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parser.repaired_count()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parser.halted_count()
+    }
+
+    fn last_halted(&self) -> bool {
+        self.parser.last_halted()
+    }
+}
+
+/// Parser wrapper that shows every candle to a side-effect closure
+///
+/// Created by [`tee`]. Candles pass through unchanged after `sink` has seen
+/// them; parse errors pass through without reaching `sink`, so the recorded
+/// copy holds exactly the candles the downstream consumer received.
+pub struct Tee<P: CandleParser, F: FnMut(&Candle)> {
+    parser: P,
+    sink: F,
+}
+
+/// Forward `parser` while handing each candle to `sink`, e.g. to persist a
+/// cleaned copy with [`crate::candle_file::write_candles`] in the same pass
+pub fn tee<P: CandleParser, F: FnMut(&Candle)>(parser: P, sink: F) -> Tee<P, F> {
+    Tee { parser, sink }
+}
+
+impl<P: CandleParser, F: FnMut(&Candle)> Tee<P, F> {
+    /// Unwrap the sink, e.g. to flush a writer it owns
+    pub fn into_sink(self) -> F {
+        self.sink
+    }
+}
+
+impl<P: CandleParser, F: FnMut(&Candle)> Iterator for Tee<P, F> {
+    type Item = Result<Candle, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.parser.next()?;
+        if let Ok(candle) = &next {
+            (self.sink)(candle);
+        }
+        Some(next)
+    }
+}
+
+impl<P: CandleParser, F: FnMut(&Candle)> CandleParser for Tee<P, F> {
+    fn tick_size(&self) -> f64 {
+        self.parser.tick_size()
+    }
+
+    fn size_hint_total(&self) -> Option<usize> {
+        self.parser.size_hint_total()
+    }
+
+    fn repaired_count(&self) -> u64 {
+        self.parser.repaired_count()
+    }
+
+    fn halted_count(&self) -> u64 {
+        self.parser.halted_count()
+    }
+
+    fn last_halted(&self) -> bool {
+        self.parser.last_halted()
+    }
+}
+
+/// Longest single pause in [`replay_timed`], so gaps (weekends, halts) in
+/// the data don't stall a demo
+pub const MAX_REPLAY_SLEEP: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Process candles like [`try_process_candles`], paced at simulated speed
+///
+/// Before each event, sleeps for the market-time gap since the previous
+/// event divided by `speed` (`1.0` = real time, `60.0` = a minute per
+/// second), capped at [`MAX_REPLAY_SLEEP`]. `f64::INFINITY` never sleeps.
+/// Control signals are delivered without pausing.
+pub fn replay_timed<P, F>(parser: P, speed: f64, mut on_event: F) -> Result<IngestionSnapshot, EngineError>
+where
+    P: CandleParser,
+    F: FnMut(MarketEvent) -> Result<(), EngineError>,
+{
+    let mut adapter = CandleEventAdapter::new(parser);
+    let mut prev_ts: Option<i64> = None;
+
+    for event_result in &mut adapter {
+        let event = event_result?;
+
+        if !event.is_control() {
+            let ts = event.timestamp();
+            if let Some(prev) = prev_ts {
+                let gap_ms = ts.saturating_sub(prev).max(0) as f64 / speed;
+                if gap_ms > 0.0 {
+                    // Overflow (e.g. speed 0) just means "a very long time"
+                    let pause = std::time::Duration::try_from_secs_f64(gap_ms / 1_000.0)
+                        .unwrap_or(MAX_REPLAY_SLEEP);
+                    thread::sleep(pause.min(MAX_REPLAY_SLEEP));
+                }
+            }
+            prev_ts = Some(ts);
+        }
+
+        on_event(event)?;
+    }
+
+    Ok(adapter.metrics.snapshot())
+}
+
+/// How a candle is turned into synthetic ticks for the engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarToTickPolicy {
+    /// One tick at `ts_close` and `close_tick` carrying the bar's volume,
+    /// with a buy aggressor for up (or flat) bars and a sell aggressor for
+    /// down bars
+    #[default]
+    Close,
+    /// Four ticks tracing open, low, high, close for up (or flat) bars and
+    /// open, high, low, close for down bars, spread evenly from `ts_open` to
+    /// `ts_close` with the volume split between them. Each tick's aggressor
+    /// follows its move from the previous tick, so resting orders inside the
+    /// bar's range get a chance to fill.
+    Ohlc,
+}
+
+impl BarToTickPolicy {
+    /// Parse a policy name ("close" or "ohlc"), as accepted from Python
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "close" => Some(BarToTickPolicy::Close),
+            "ohlc" => Some(BarToTickPolicy::Ohlc),
+            _ => None,
+        }
+    }
+
+    /// Synthetic ticks for `candle`, in time order
+    pub(crate) fn ticks(self, candle: &Candle) -> impl Iterator<Item = tick_event_t> {
+        let up = candle.close_tick >= candle.open_tick;
+        let bar_side = if up { side_t::SIDE_BUY } else { side_t::SIDE_SELL };
+        let tick = |ts_ms, price_tick, qty, side| tick_event_t { ts_ms, price_tick, qty, side };
+
+        match self {
+            BarToTickPolicy::Close => {
+                let close = tick(candle.ts_close, candle.close_tick, candle.volume_scaled, bar_side);
+                [close, close, close, close].into_iter().take(1)
+            }
+            BarToTickPolicy::Ohlc => {
+                let path = if up {
+                    [candle.open_tick, candle.low_tick, candle.high_tick, candle.close_tick]
+                } else {
+                    [candle.open_tick, candle.high_tick, candle.low_tick, candle.close_tick]
+                };
+                let span = candle.ts_close - candle.ts_open;
+                let qty = candle.volume_scaled / 4;
+
+                let mut ticks = [tick(0, 0, 0, bar_side); 4];
+                for (i, &price_tick) in path.iter().enumerate() {
+                    let side = match i {
+                        0 => bar_side,
+                        _ if price_tick >= path[i - 1] => side_t::SIDE_BUY,
+                        _ => side_t::SIDE_SELL,
+                    };
+                    ticks[i] = tick(candle.ts_open + span * i as i64 / 3, price_tick, qty, side);
+                }
+                // The last tick absorbs the rounding remainder
+                ticks[3].qty = candle.volume_scaled - 3 * qty;
+                ticks.into_iter().take(4)
+            }
+        }
+    }
+}
+
+/// Replay candles through the engine as one tick per bar
+///
+/// Each bar is stepped with [`BarToTickPolicy::Close`]: a tick at `ts_close`
+/// and `close_tick` carrying the bar's volume, with a buy aggressor for up
+/// (or flat) bars and a sell aggressor for down bars. Fails with `EngineError::TickSizeMismatch` before reading any
+/// data if the parser quantizes with a different tick size than the engine.
+///
+/// Bars are stepped exactly in the order the parser yields them, so bars
+/// sharing a timestamp keep their input order. For several streams, combine
+/// them with [`crate::candle_parser::merge`], which orders ties by source.
+pub fn replay<P: CandleParser>(parser: P, engine: &mut Engine) -> Result<IngestionSnapshot, EngineError> {
+    engine.check_tick_size(parser.tick_size())?;
+
+    try_process_candles(parser, |event| match event {
+        MarketEvent::Bar(candle) => engine.step_candle(&candle, BarToTickPolicy::Close),
+        _ => Ok(()),
+    })
+}
+
+/// Replay candles like [`replay`], calling `on_step` with each event and the
+/// engine snapshot right after it was fed
+///
+/// The hook for strategies deciding inside the loop: the snapshot already
+/// reflects the event, including any fills it caused. Stops at the first
+/// parse or engine error, like [`replay`].
+pub fn process_candles_with_snapshot<P, F>(
+    parser: P,
+    engine: &mut Engine,
+    mut on_step: F,
+) -> Result<IngestionSnapshot, EngineError>
+where
+    P: CandleParser,
+    F: FnMut(&MarketEvent, &Snapshot),
+{
+    engine.check_tick_size(parser.tick_size())?;
+
+    try_process_candles(parser, |event| {
+        if let MarketEvent::Bar(candle) = &event {
+            engine.step_candle(candle, BarToTickPolicy::Close)?;
+        }
+        on_step(&event, &engine.get_snapshot());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::candle_parser::{ParseOptions, ZeroVolumePolicy};
+
+    #[test]
+    fn test_events_ref_borrows_from_mmap() {
+        let candles: Vec<Candle> = (0..16)
+            .map(|i| Candle {
+                ts_open: 1609459200000 + i * 60000,
+                ts_close: 1609459260000 + i * 60000,
+                open_tick: 4200 + i,
+                high_tick: 4250 + i,
+                low_tick: 4150 + i,
+                close_tick: 4220 + i,
+                volume_scaled: 1_000_000,
+                trade_count: 1,
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!("ag_core_events_ref_{}.bin", std::process::id()));
+        crate::candle_file::write_candles(std::fs::File::create(&path).unwrap(), &candles).unwrap();
+
+        let mapped = MmapCandles::open(&path, 1.0).unwrap();
+        let mapping = mapped.as_slice().as_ptr_range();
+        let borrowed: Vec<&Candle> = mapped.events_ref().collect();
+        assert_eq!(borrowed.len(), 16);
+        for (i, candle) in borrowed.iter().enumerate() {
+            // Each reference is the mapped record itself, not a copy
+            assert!(std::ptr::eq(*candle, &mapped.as_slice()[i]));
+            assert!(mapping.contains(&(*candle as *const Candle)));
+            assert_eq!(**candle, candles[i]);
+        }
+
+        let in_memory: Vec<*const Candle> = candles.events_ref().map(|c| c as *const Candle).collect();
+        assert_eq!(in_memory[3], &candles[3] as *const Candle);
+
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_market_event_timestamp() {
+        let candle = Candle {
+            ts_open: 1609459200000,
+            ts_close: 1609459260000,
+            open_tick: 4200,
+            high_tick: 4250,
+            low_tick: 4150,
+            close_tick: 4220,
+            volume_scaled: 1_500_000_000,
+            trade_count: 42,
+        };
+
+        let event = MarketEvent::Bar(candle);
+        assert_eq!(event.timestamp(), 1609459200000);
+        assert!(event.is_bar());
+        assert!(!event.is_trade());
+        assert!(!event.is_control());
+    }
+
+    #[test]
+    fn test_control_event_timestamp() {
+        let heartbeat = MarketEvent::Control(ControlSignal::Heartbeat(1609459200000));
+        assert_eq!(heartbeat.timestamp(), 1609459200000);
+        assert!(heartbeat.is_control());
+        assert!(!heartbeat.is_bar());
+
+        let session_end = MarketEvent::Control(ControlSignal::SessionEnd(1609459260000));
+        assert_eq!(session_end.timestamp(), 1609459260000);
+
+        let end = MarketEvent::Control(ControlSignal::EndOfData);
+        assert!(end.timestamp() > session_end.timestamp());
+    }
+
+    #[test]
+    fn test_candle_event_adapter() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+        let mut adapter = CandleEventAdapter::new(parser);
+
+        let event1 = adapter.next().unwrap().unwrap();
+        assert!(event1.is_bar());
+
+        let event2 = adapter.next().unwrap().unwrap();
+        assert!(event2.is_bar());
+
+        assert!(adapter.next().is_none());
+
+        let metrics = adapter.metrics.snapshot();
+        assert_eq!(metrics.candles_processed, 2);
+        assert_eq!(metrics.parse_errors, 0);
+    }
+
+    #[test]
+    fn test_adapter_reports_event_rate() {
+        let mut csv_data = String::from("timestamp,open,high,low,close,volume\n");
+        for i in 0..20_000i64 {
+            csv_data.push_str(&format!("{},42000,42500,41500,42200,1500\n", 1609459200000 + i * 60000));
+        }
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.into_bytes()), 1.0).unwrap();
+        let mut adapter = CandleEventAdapter::new(parser);
+        let metrics = adapter.metrics_handle();
+        assert_eq!(metrics.rate(), 0.0);
+
+        let start = Instant::now();
+        let count = adapter.by_ref().count();
+        let actual = count as f64 / start.elapsed().as_secs_f64();
+
+        let rate = metrics.rate();
+        assert!(rate > 0.0);
+        assert!(rate > actual / 10.0 && rate < actual * 10.0, "rate {} vs actual {}", rate, actual);
+        assert_eq!(metrics.snapshot().events_per_sec, rate);
+    }
+
+    #[test]
+    fn test_process_candles() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let mut events_received = Vec::new();
+
+        let metrics = process_candles(parser, |event| {
+            events_received.push(event);
+            Ok(())
+        }, abort_on_error).unwrap();
+
+        assert_eq!(events_received.len(), 1);
+        assert_eq!(metrics.candles_processed, 1);
+    }
+
+    #[test]
+    fn test_process_candles_until_keeps_boundary_candle() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+1609459320000,42700,43000,42600,42900,1800
+1609459380000,42900,43100,42800,43000,1700
+1609459440000,43000,43200,42900,43100,1600
+";
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+
+        let mut seen = Vec::new();
+        let (metrics, stopped) = process_candles_until(parser, 1609459350000, |event| {
+            seen.push(event.timestamp());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(seen, vec![1609459200000, 1609459260000, 1609459320000]);
+        assert_eq!(metrics.candles_processed, 3);
+        assert_eq!(metrics.last_ts_ms, 1609459320000);
+        match stopped {
+            Some(MarketEvent::Bar(candle)) => {
+                assert_eq!(candle.ts_open, 1609459380000);
+                assert_eq!(candle.close_tick, 43000);
+            }
+            other => panic!("expected the fourth candle, got {:?}", other),
+        }
+
+        // A boundary past the data runs to the end
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        let (metrics, stopped) = process_candles_until(parser, i64::MAX, |_| Ok(())).unwrap();
+        assert_eq!(metrics.candles_processed, 5);
+        assert!(stopped.is_none());
+    }
+
+    #[test]
+    fn test_spawn_candle_feeder() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+1609459320000,42700,43000,42600,42900,1800
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let (rx, handle) = spawn_candle_feeder(parser, 100);
+
+        let mut received_count = 0;
+        for event_result in rx {
+            match event_result {
+                Ok(MarketEvent::Bar(_)) => {
+                    received_count += 1;
+                }
+                Ok(MarketEvent::Trade(_)) => {
+                    panic!("Unexpected trade event");
+                }
+                Ok(MarketEvent::Control(signal)) => {
+                    panic!("Unexpected control signal: {:?}", signal);
+                }
+                Err(e) => {
+                    panic!("Parse error: {}", e);
+                }
+            }
+        }
+
+        let metrics = handle.join().expect("Thread panicked");
+        assert_eq!(received_count, 3);
+        assert_eq!(metrics.candles_processed, 3);
+        assert_eq!(metrics.parse_errors, 0);
+    }
+
+    #[test]
+    fn test_feeders_share_metrics() {
+        let btc = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+1609459320000,42700,43000,42600,42900,1800
+";
+        let eth = "\
+timestamp,open,high,low,close,volume
+1609459200000,730,735,725,732,900
+1609459260000,732,740,731,741,800
+";
+
+        let metrics = Arc::new(IngestionMetrics::new());
+        let repair = ParseOptions { repair_invalid: true, ..ParseOptions::default() };
+        let btc_parser = CsvCandleIter::new(Cursor::new(btc.as_bytes()), 1.0).unwrap();
+        let eth_parser = CsvCandleIter::new_with_options(Cursor::new(eth.as_bytes()), 1.0, repair).unwrap();
+        let (btc_rx, btc_handle) = spawn_candle_feeder_shared(btc_parser, 100, Arc::clone(&metrics));
+        let (eth_rx, eth_handle) = spawn_candle_feeder_shared(eth_parser, 100, Arc::clone(&metrics));
+
+        assert_eq!(btc_rx.iter().count() + eth_rx.iter().count(), 5);
+        btc_handle.join().expect("Thread panicked");
+        eth_handle.join().expect("Thread panicked");
+
+        let combined = metrics.snapshot();
+        assert_eq!(combined.candles_processed, 3 + 2);
+        assert_eq!(combined.candles_repaired, 1);
+        assert_eq!(combined.parse_errors, 0);
+    }
+
+    #[test]
+    fn test_feeder_publishes_last_timestamp() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+1609459320000,42700,43000,42600,42900,1800
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let (rx, metrics, handle) = spawn_candle_feeder_with_metrics(parser, 100);
+
+        for event_result in rx {
+            let event = event_result.unwrap();
+            // The feeder publishes the timestamp before sending the event
+            assert!(metrics.last_ts_ms() >= event.timestamp());
+        }
+
+        let snapshot = handle.join().expect("Thread panicked");
+        assert_eq!(metrics.last_ts_ms(), 1609459320000);
+        assert_eq!(snapshot.last_ts_ms, 1609459320000);
+    }
+
+    #[test]
+    fn test_try_process_candles_propagates_parse_error() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42500,42000,41500,42200,1500
+1609459320000,42700,43000,42600,42900,1800
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let mut events_received = 0;
+        let result = try_process_candles(parser, |_| {
+            events_received += 1;
+            Ok(())
+        });
+
+        assert!(matches!(result, Err(EngineError::Ingestion(ParseError::InvalidCandle(_)))));
+        assert_eq!(events_received, 1);
+    }
+
+    #[test]
+    fn test_try_process_candles_propagates_callback_error() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let result = try_process_candles(parser, |_| Err(EngineError::StepFailed(-1)));
+
+        assert!(matches!(result, Err(EngineError::StepFailed(-1))));
+    }
+
+    #[test]
+    fn test_process_candles_abort_on_first_error() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42500,42000,41500,42200,1500
+1609459320000,42700,43000,42600,42900,1800
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let mut events_received = 0;
+        let mut errors_seen = 0;
+        let metrics = process_candles(parser, |_| {
+            events_received += 1;
+            Ok(())
+        }, |_| {
+            errors_seen += 1;
+            ControlFlow::Break(())
+        }).unwrap();
+
+        assert_eq!(events_received, 1);
+        assert_eq!(errors_seen, 1);
+        assert_eq!(metrics.candles_processed, 1);
+        assert_eq!(metrics.parse_errors, 1);
+    }
+
+    #[test]
+    fn test_process_candles_collect_all_errors() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42500,42000,41500,42200,1500
+1609459320000,42700,43000,42600,42900,1800
+1609459380000,42900,43100,42800,43000,1700
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap();
+
+        let mut errors = Vec::new();
+        let metrics = process_candles(parser, |event| {
+            if event.timestamp() == 1609459320000 {
+                return Err(EngineError::StepFailed(-1));
+            }
+            Ok(())
+        }, |err| {
+            errors.push(err);
+            ControlFlow::Continue(())
+        }).unwrap();
+
+        assert_eq!(metrics.candles_processed, 3);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], ProcessingError::Parse(ParseError::InvalidCandle(_))));
+        assert!(matches!(errors[1], ProcessingError::Engine(EngineError::StepFailed(-1))));
+    }
+
+    #[test]
+    fn test_make_parser_dispatch() {
+        let candles_csv = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+";
+        let mut parser = make_parser(
+            DataMode::OHLC(SourceType::Csv),
+            Cursor::new(candles_csv.as_bytes()),
+            1.0,
+        ).unwrap();
+        assert_eq!(parser.tick_size(), 1.0);
+        assert!(parser.next().unwrap().unwrap().is_bar());
+        assert!(parser.next().is_none());
+
+        let trades_json = r#"{"T":1704067200000,"p":"42150.5","q":"0.025","m":true}"#;
+        let mut parser = make_parser(
+            DataMode::AggTrades(SourceType::Json),
+            Cursor::new(trades_json.as_bytes()),
+            0.5,
+        ).unwrap();
+        let event = parser.next().unwrap().unwrap();
+        assert!(event.is_trade());
+        assert_eq!(event.timestamp(), 1704067200000);
+    }
+
+    #[test]
+    fn test_make_parser_unsupported_mode() {
+        let result = make_parser(DataMode::Ticks(SourceType::Csv), Cursor::new(Vec::new()), 1.0);
+        assert!(matches!(result, Err(ParseError::Unsupported(_))));
+
+        let result = make_parser(DataMode::OHLC(SourceType::Parquet), Cursor::new(Vec::new()), 1.0);
+        assert!(matches!(result, Err(ParseError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_adapter_counts_repairs() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42190,42100,42700,2000
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0).unwrap().repair_invalid(true);
+        let adapter = CandleEventAdapter::new(parser);
+        let metrics = adapter.metrics_handle();
+
+        assert_eq!(adapter.filter(|e| e.is_ok()).count(), 2);
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.candles_processed, 2);
+        assert_eq!(snapshot.candles_repaired, 1);
+    }
+
+    #[test]
+    fn test_adapter_flags_halted_bars() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42200,42200,42200,0
+1609459320000,42200,42800,42100,42700,2000
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let parser = CsvCandleIter::new(cursor, 1.0)
+            .unwrap()
+            .treat_zero_volume(ZeroVolumePolicy::MarkHalt);
+        let adapter = CandleEventAdapter::new(parser);
+        let metrics = adapter.metrics_handle();
+
+        let events: Vec<MarketEvent> = adapter.map(|e| e.unwrap()).collect();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[1], MarketEvent::Control(ControlSignal::Halt(1609459260000))));
+        assert!(matches!(events[2], MarketEvent::Bar(c) if c.volume_scaled == 0));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.candles_processed, 3);
+        assert_eq!(snapshot.candles_halted, 1);
+    }
+
+    #[test]
+    fn test_replay_rejects_tick_size_mismatch() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,100.0,101.0,99.5,100.5,10
+";
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5).unwrap();
+        let mut engine = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.01).unwrap();
+
+        let result = replay(parser, &mut engine);
+        assert!(matches!(
+            result,
+            Err(EngineError::TickSizeMismatch { engine, parser }) if engine == 0.01 && parser == 0.5
+        ));
+        assert_eq!(engine.get_snapshot().ts_ms, 0);
+    }
+
+    #[test]
+    fn test_replay_steps_one_tick_per_bar() {
+        let csv_data = "\
+timestamp,ts_close,open,high,low,close,volume
+1609459200000,1609459260000,100.0,101.0,99.5,100.5,10
+1609459260000,1609459320000,100.5,100.5,99.0,99.0,12
+";
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5).unwrap();
+        let mut engine = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.5).unwrap();
+        assert_eq!(engine.tick_size(), 0.5);
+
+        let snapshot = replay(parser, &mut engine).unwrap();
+        assert_eq!(snapshot.candles_processed, 2);
+        assert_eq!(engine.get_snapshot().ts_ms, 1609459320000);
+    }
+
+    #[test]
+    fn test_process_candles_with_snapshot() {
+        let csv_data = "\
+timestamp,ts_close,open,high,low,close,volume
+1609459200000,1609459260000,100.0,101.0,99.5,100.5,10
+1609459260000,1609459320000,100.5,100.5,99.0,99.0,12
+1609459320000,1609459380000,99.0,100.0,98.5,99.5,8
+";
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 0.5).unwrap();
+        let mut engine = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.5).unwrap();
+        engine.mark_to(100.5).unwrap();
+        engine.place_order("MARKET", "BUY", 1.0, 0.0).unwrap();
+
+        let mut steps = Vec::new();
+        let metrics = process_candles_with_snapshot(parser, &mut engine, |event, snap| {
+            steps.push((event.timestamp(), snap.ts_ms, snap.position));
+        })
+        .unwrap();
+
+        assert_eq!(metrics.candles_processed, 3);
+        assert_eq!(steps.len(), 3);
+        // Each snapshot is taken after its bar was stepped
+        assert_eq!(steps[0], (1609459200000, 1609459260000, 1.0));
+        assert_eq!(steps[2].1, 1609459380000);
+    }
+
+    #[test]
+    fn test_step_candle_matches_manual_ticks() {
+        let candle = Candle {
+            ts_open: 1_000,
+            ts_close: 61_000,
+            open_tick: 10_000,
+            high_tick: 10_300,
+            low_tick: 9_900,
+            close_tick: 10_200,
+            volume_scaled: 4_000_001,
+            trade_count: 10,
+        };
+
+        // Close: one buy tick at the close
+        let mut engine = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.01).unwrap();
+        let mut manual = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.01).unwrap();
+        engine.place_order("LIMIT", "BUY", 1.0, 99.5).unwrap();
+        manual.place_order("LIMIT", "BUY", 1.0, 99.5).unwrap();
+        engine.step_candle(&candle, BarToTickPolicy::Close).unwrap();
+        manual.step_tick(61_000, 10_200, 4.000001, "BUY").unwrap();
+        assert_eq!(engine.get_snapshot(), manual.get_snapshot());
+        assert_eq!(engine.get_snapshot().position, 0.0);
+
+        // OHLC: open, low, high, close, so the dip fills the resting buy
+        let mut engine = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.01).unwrap();
+        let mut manual = Engine::new(10_000.0, 0.0, 0.0, 0.0, 0.01).unwrap();
+        engine.place_order("LIMIT", "BUY", 1.0, 99.5).unwrap();
+        manual.place_order("LIMIT", "BUY", 1.0, 99.5).unwrap();
+        engine.step_candle(&candle, BarToTickPolicy::Ohlc).unwrap();
+        manual.step_tick(1_000, 10_000, 1.0, "BUY").unwrap();
+        manual.step_tick(21_000, 9_900, 1.0, "SELL").unwrap();
+        manual.step_tick(41_000, 10_300, 1.0, "BUY").unwrap();
+        manual.step_tick(61_000, 10_200, 1.000001, "SELL").unwrap();
+        assert_eq!(engine.get_snapshot(), manual.get_snapshot());
+        assert_eq!(engine.get_snapshot().position, 1.0);
+        assert_eq!(engine.fills(), manual.fills());
+    }
+
+    #[test]
+    fn test_replay_timed_preserves_order() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+1609459320000,42700,42900,42600,42800,1800
+1609545600000,42800,43000,42700,42900,1700
+";
+
+        // A minute of market time per 0.1ms; the day-long gap is ~14ms
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        let mut seen = Vec::new();
+        let start = std::time::Instant::now();
+        let snapshot = replay_timed(parser, 600_000.0, |event| {
+            seen.push(event.timestamp());
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert_eq!(snapshot.candles_processed, 4);
+        assert_eq!(seen, vec![1609459200000, 1609459260000, 1609459320000, 1609545600000]);
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        let snapshot = replay_timed(parser, f64::INFINITY, |_| Ok(())).unwrap();
+        assert_eq!(snapshot.candles_processed, 4);
+    }
+
+    #[test]
+    fn test_progress_reports_every_n_candles() {
+        let mut csv_data = String::from("timestamp,open,high,low,close,volume\n");
+        for i in 0..10 {
+            csv_data.push_str(&format!("{},100,101,99,100,10\n", 1609459200000i64 + i * 60_000));
+        }
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.clone().into_bytes()), 1.0).unwrap();
+        let mut reports = Vec::new();
+        let parser = ProgressParser::new(parser, 3, |p: &Progress| reports.push(*p));
+        let mut engine = Engine::new(10_000.0, 0.0, 0.0, 0.0, 1.0).unwrap();
+        let snapshot = replay(parser, &mut engine).unwrap();
+
+        assert_eq!(snapshot.candles_processed, 10);
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports.iter().map(|p| p.events).collect::<Vec<_>>(), vec![3, 6, 9]);
+        assert_eq!(reports[2].ts_ms, 1609459200000 + 8 * 60_000);
+        assert_eq!(reports[0].total, None);
+        assert_eq!(reports[0].eta, None);
+
+        // A zero interval reports on every candle
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.into_bytes()), 1.0).unwrap();
+        let mut count = 0;
+        let parser = ProgressParser::new(parser, 0, |_: &Progress| count += 1);
+        try_process_candles(parser, |_| Ok(())).unwrap();
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_tee_records_what_downstream_sees() {
+        let csv_data = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,abc
+1609459320000,42700,42900,42600,42800,1800
+";
+
+        let parser = CsvCandleIter::new(Cursor::new(csv_data.as_bytes()), 1.0).unwrap();
+        let mut recorded = Vec::new();
+        let mut downstream = Vec::new();
+        let mut errors = 0;
+        let metrics = process_candles(
+            tee(parser, |candle| recorded.push(*candle)),
+            |event| {
+                if let MarketEvent::Bar(candle) = event {
+                    downstream.push(candle);
+                }
+                Ok(())
+            },
+            |_| {
+                errors += 1;
+                ControlFlow::Continue(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(errors, 1);
+        assert_eq!(metrics.candles_processed, 2);
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded, downstream);
+
+        let mut file = Vec::new();
+        crate::candle_file::write_candles(&mut file, &recorded).unwrap();
+        assert_eq!(file.len(), 2 * std::mem::size_of::<Candle>());
     }
 }
@@ -0,0 +1,225 @@
+//! Post-processing helpers for equity curves
+
+/// Reduce an equity curve to at most `max_points` for plotting
+///
+/// The first and last points are always kept. The interior is split into
+/// equal-count buckets and each bucket contributes its minimum and maximum
+/// equity (in time order), so spikes and drawdowns survive downsampling.
+/// Curves that already fit are returned unchanged.
+pub fn downsample_curve(curve: &[(i64, f64)], max_points: usize) -> Vec<(i64, f64)> {
+    if curve.len() <= max_points {
+        return curve.to_vec();
+    }
+
+    downsample_indices(curve, max_points).into_iter().map(|i| curve[i]).collect()
+}
+
+/// [`downsample_curve`] in place: shrink `curve` to at most `max_points`
+/// without allocating a second curve
+pub fn compact_curve(curve: &mut Vec<(i64, f64)>, max_points: usize) {
+    if curve.len() <= max_points {
+        return;
+    }
+
+    // Kept indices increase and never fall behind their output slot, so
+    // copying forward cannot overwrite a point still to be read
+    let kept = downsample_indices(curve, max_points);
+    for (slot, &i) in kept.iter().enumerate() {
+        curve[slot] = curve[i];
+    }
+    curve.truncate(kept.len());
+}
+
+/// Increasing indices of the points [`downsample_curve`] keeps, for a curve
+/// longer than `max_points`
+fn downsample_indices(curve: &[(i64, f64)], max_points: usize) -> Vec<usize> {
+    let last = curve.len() - 1;
+    if max_points <= 2 {
+        return [0, last][2 - max_points..].to_vec();
+    }
+
+    let interior = &curve[1..last];
+    let buckets = (max_points - 2) / 2;
+    let mut out = Vec::with_capacity(max_points);
+    out.push(0);
+
+    for b in 0..buckets {
+        let start = b * interior.len() / buckets;
+        let end = (b + 1) * interior.len() / buckets;
+        let bucket = &interior[start..end];
+        if bucket.is_empty() {
+            continue;
+        }
+
+        let mut min_idx = 0;
+        let mut max_idx = 0;
+        for (i, &(_, equity)) in bucket.iter().enumerate() {
+            if equity < bucket[min_idx].1 {
+                min_idx = i;
+            }
+            if equity > bucket[max_idx].1 {
+                max_idx = i;
+            }
+        }
+
+        let (first, second) = if min_idx <= max_idx { (min_idx, max_idx) } else { (max_idx, min_idx) };
+        out.push(1 + start + first);
+        if second != first {
+            out.push(1 + start + second);
+        }
+    }
+
+    out.push(last);
+    out
+}
+
+/// Largest peak-to-trough decline of an equity curve, as a fraction of the
+/// peak (0.25 = 25%)
+///
+/// Zero for an empty or never-declining curve. Declines from a peak at or
+/// below zero equity are not measured.
+pub fn max_drawdown(curve: &[(i64, f64)]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut max = 0.0f64;
+    for &(_, equity) in curve {
+        peak = peak.max(equity);
+        if peak > 0.0 {
+            max = max.max((peak - equity) / peak);
+        }
+    }
+    max
+}
+
+/// Running drawdown of an equity series, updated one value at a time
+///
+/// Holds only the peak and the worst drawdown so far, so strategies can
+/// size positions by drawdown live instead of storing the curve. Matches
+/// [`max_drawdown`] over the same values. Enable on an engine with
+/// [`crate::Engine::enable_drawdown_tracking`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DrawdownTracker {
+    peak: Option<f64>,
+    current: f64,
+    max: f64,
+}
+
+impl DrawdownTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the latest equity
+    pub fn update(&mut self, equity: f64) {
+        let peak = self.peak.map_or(equity, |peak| peak.max(equity));
+        self.peak = Some(peak);
+        self.current = if peak > 0.0 { (peak - equity) / peak } else { 0.0 };
+        self.max = self.max.max(self.current);
+    }
+
+    /// Decline of the latest equity from the peak, as a fraction of the peak
+    pub fn current_drawdown(&self) -> f64 {
+        self.current
+    }
+
+    /// Largest drawdown seen so far, as a fraction of its peak
+    pub fn max_drawdown(&self) -> f64 {
+        self.max
+    }
+
+    /// Highest equity seen, or `None` before the first update
+    pub fn peak(&self) -> Option<f64> {
+        self.peak
+    }
+
+    /// Forget all values, as if newly created
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine_curve(n: usize) -> Vec<(i64, f64)> {
+        (0..n).map(|i| (i as i64 * 1000, 10_000.0 + (i as f64 / 50.0).sin() * 100.0)).collect()
+    }
+
+    #[test]
+    fn test_downsample_preserves_endpoints_and_count() {
+        let curve = sine_curve(10_000);
+
+        for max_points in [0, 1, 2, 3, 10, 101, 1000] {
+            let sampled = downsample_curve(&curve, max_points);
+            assert!(sampled.len() <= max_points, "{} > {}", sampled.len(), max_points);
+
+            if max_points >= 2 {
+                assert_eq!(sampled[0], curve[0]);
+                assert_eq!(*sampled.last().unwrap(), *curve.last().unwrap());
+            }
+            assert!(sampled.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+
+        assert_eq!(downsample_curve(&curve[..5], 10), curve[..5].to_vec());
+    }
+
+    #[test]
+    fn test_compact_matches_downsample() {
+        let curve = sine_curve(5_000);
+
+        for max_points in [0, 1, 2, 3, 12, 999, 5_000] {
+            let mut compacted = curve.clone();
+            compact_curve(&mut compacted, max_points);
+            assert_eq!(compacted, downsample_curve(&curve, max_points));
+        }
+    }
+
+    #[test]
+    fn test_downsample_keeps_spikes() {
+        let mut curve: Vec<(i64, f64)> = (0..1000).map(|i| (i, 100.0)).collect();
+        curve[137].1 = 250.0;
+        curve[612].1 = 10.0;
+
+        let sampled = downsample_curve(&curve, 20);
+        assert!(sampled.contains(&(137, 250.0)));
+        assert!(sampled.contains(&(612, 10.0)));
+    }
+
+    #[test]
+    fn test_drawdown_tracker_matches_batch() {
+        let curve: Vec<(i64, f64)> = (0..2_000)
+            .map(|i| (i, 10_000.0 + (i as f64 / 37.0).sin() * 900.0 - i as f64 * 0.4))
+            .collect();
+
+        let mut tracker = DrawdownTracker::new();
+        for (n, &(_, equity)) in curve.iter().enumerate() {
+            tracker.update(equity);
+            assert_eq!(tracker.max_drawdown(), max_drawdown(&curve[..=n]));
+        }
+        assert!(tracker.max_drawdown() > 0.1);
+
+        let last = curve.last().unwrap().1;
+        let peak = tracker.peak().unwrap();
+        assert_eq!(tracker.current_drawdown(), (peak - last) / peak);
+
+        tracker.reset();
+        assert_eq!(tracker, DrawdownTracker::new());
+        assert_eq!(max_drawdown(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_downsample_retains_bucket_extremes() {
+        let curve = sine_curve(1002);
+        let sampled = downsample_curve(&curve, 12);
+
+        // 1000 interior points in 5 buckets of 200
+        for bucket in curve[1..1001].chunks(200) {
+            let min = bucket.iter().cloned().fold(f64::INFINITY, |m, (_, e)| m.min(e));
+            let max = bucket.iter().cloned().fold(f64::NEG_INFINITY, |m, (_, e)| m.max(e));
+            let in_bucket = |ts: i64| ts >= bucket[0].0 && ts <= bucket[bucket.len() - 1].0;
+
+            assert!(sampled.iter().any(|&(ts, e)| in_bucket(ts) && e == min));
+            assert!(sampled.iter().any(|&(ts, e)| in_bucket(ts) && e == max));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Queue-position estimates for resting limit orders
+//!
+//! The engine only sees trades, not the book, so depth at a level is
+//! unknown. As a proxy, the volume already traded at a price stands in for
+//! the size resting there: a limit order joins behind that much volume, and
+//! every later trade at its price is assumed to consume the queue ahead of
+//! it. The estimate is informational; the matcher fills by
+//! [`crate::LimitFillModel`] alone.
+
+use std::collections::HashMap;
+
+/// Volume traded per price level and volume ahead of each tracked order,
+/// all in quantity units scaled by 1e6
+#[derive(Debug, Clone, Default)]
+pub struct QueueTracker {
+    level_volume: HashMap<i64, i64>,
+    /// `order_id -> (price_tick, volume ahead)`
+    orders: HashMap<u64, (i64, i64)>,
+}
+
+impl QueueTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a trade, consuming the queue ahead of orders at its price
+    pub fn on_trade(&mut self, price_tick: i64, qty_scaled: i64) {
+        *self.level_volume.entry(price_tick).or_insert(0) += qty_scaled;
+
+        for (level, ahead) in self.orders.values_mut() {
+            if *level == price_tick {
+                *ahead = (*ahead - qty_scaled).max(0);
+            }
+        }
+    }
+
+    /// Start tracking an order joining the back of its level
+    pub fn on_place(&mut self, order_id: u64, price_tick: i64) {
+        let ahead = self.level_volume.get(&price_tick).copied().unwrap_or(0);
+        self.orders.insert(order_id, (price_tick, ahead));
+    }
+
+    /// Stop tracking a filled or cancelled order
+    pub fn remove(&mut self, order_id: u64) {
+        self.orders.remove(&order_id);
+    }
+
+    /// Estimated volume ahead of `order_id`, if it is tracked
+    pub fn queue_position(&self, order_id: u64) -> Option<i64> {
+        self.orders.get(&order_id).map(|&(_, ahead)| ahead)
+    }
+
+    /// Forget all levels and orders
+    pub fn clear(&mut self) {
+        self.level_volume.clear();
+        self.orders.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trades_at_level_consume_queue() {
+        let mut tracker = QueueTracker::new();
+        tracker.on_trade(100, 3_000_000);
+        tracker.on_trade(101, 9_000_000);
+
+        tracker.on_place(1, 100);
+        tracker.on_place(2, 99);
+        assert_eq!(tracker.queue_position(1), Some(3_000_000));
+        assert_eq!(tracker.queue_position(2), Some(0));
+
+        tracker.on_trade(101, 5_000_000);
+        assert_eq!(tracker.queue_position(1), Some(3_000_000));
+        tracker.on_trade(100, 2_000_000);
+        assert_eq!(tracker.queue_position(1), Some(1_000_000));
+        tracker.on_trade(100, 2_000_000);
+        assert_eq!(tracker.queue_position(1), Some(0));
+
+        // A later order at the same level queues behind everything traded there
+        tracker.on_place(3, 100);
+        assert_eq!(tracker.queue_position(3), Some(7_000_000));
+
+        tracker.remove(1);
+        assert_eq!(tracker.queue_position(1), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Daily trading-session boundaries computed purely from timestamps
+//!
+//! A session is a window `[open_ms_of_day, close_ms_of_day)` of local time,
+//! where local time is UTC shifted by a fixed `tz_offset_ms`. Windows that
+//! wrap midnight (`open > close`, e.g. futures 18:00-17:00) are supported and
+//! `open == close` means a 24h session starting at `open`. There is no
+//! calendar: weekends and holidays simply produce no events, and DST must be
+//! handled by the caller choosing the offset.
+
+use crate::candle::Candle;
+use crate::candle_parser::ParseError;
+use crate::market_event::{ControlSignal, EventParser, MarketEvent};
+
+pub const MS_PER_DAY: i64 = 86_400_000;
+
+/// Daily session window in a fixed UTC offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionSpec {
+    pub tz_offset_ms: i64,
+    pub open_ms_of_day: i64,
+    pub close_ms_of_day: i64,
+}
+
+impl SessionSpec {
+    pub fn new(tz_offset_ms: i64, open_ms_of_day: i64, close_ms_of_day: i64) -> Self {
+        Self {
+            tz_offset_ms,
+            open_ms_of_day,
+            close_ms_of_day,
+        }
+    }
+
+    /// Session containing `ts_ms`, identified by the local day number it opened on
+    ///
+    /// Returns `None` outside trading hours.
+    pub fn session_of(&self, ts_ms: i64) -> Option<i64> {
+        let local = ts_ms + self.tz_offset_ms;
+        let day = local.div_euclid(MS_PER_DAY);
+        let ms_of_day = local.rem_euclid(MS_PER_DAY);
+        let (open, close) = (self.open_ms_of_day, self.close_ms_of_day);
+
+        if open < close {
+            (ms_of_day >= open && ms_of_day < close).then_some(day)
+        } else if ms_of_day >= open {
+            Some(day)
+        } else if ms_of_day < close || open == close {
+            // Wrapped session that opened the previous local day
+            Some(day - 1)
+        } else {
+            None
+        }
+    }
+
+    /// UTC timestamp at which the session opened on local day `session` closes
+    pub fn close_ts(&self, session: i64) -> i64 {
+        let close_day = if self.open_ms_of_day < self.close_ms_of_day {
+            session
+        } else {
+            session + 1
+        };
+        close_day * MS_PER_DAY + self.close_ms_of_day - self.tz_offset_ms
+    }
+}
+
+/// Indices of the candles that start a new session
+///
+/// A candle starts a session when its `ts_open` falls in a session different
+/// from the previous in-session candle. Candles outside trading hours belong
+/// to no session and are never boundaries.
+pub fn session_boundaries(
+    candles: &[Candle],
+    tz_offset_ms: i64,
+    open_ms_of_day: i64,
+    close_ms_of_day: i64,
+) -> Vec<usize> {
+    let spec = SessionSpec::new(tz_offset_ms, open_ms_of_day, close_ms_of_day);
+    let mut boundaries = Vec::new();
+    let mut current = None;
+
+    for (idx, candle) in candles.iter().enumerate() {
+        if let Some(session) = spec.session_of(candle.ts_open) {
+            if current != Some(session) {
+                boundaries.push(idx);
+                current = Some(session);
+            }
+        }
+    }
+
+    boundaries
+}
+
+/// Adapter that inserts `ControlSignal::SessionEnd` into an event stream
+///
+/// The signal carries the session's close timestamp and is emitted just
+/// before the first event after a session closes, i.e. an event that is
+/// outside trading hours or in a later session. No signal is emitted at end
+/// of stream, since the data may simply stop mid-session. Parse errors and
+/// upstream control signals pass through untouched.
+pub struct SessionGate<I> {
+    inner: I,
+    spec: SessionSpec,
+    current: Option<i64>,
+    held: Option<MarketEvent>,
+}
+
+impl<I> SessionGate<I>
+where
+    I: Iterator<Item = Result<MarketEvent, ParseError>>,
+{
+    pub fn new(inner: I, spec: SessionSpec) -> Self {
+        Self {
+            inner,
+            spec,
+            current: None,
+            held: None,
+        }
+    }
+}
+
+impl<I> Iterator for SessionGate<I>
+where
+    I: Iterator<Item = Result<MarketEvent, ParseError>>,
+{
+    type Item = Result<MarketEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.held.take() {
+            return Some(Ok(event));
+        }
+
+        let event = match self.inner.next()? {
+            Ok(event) => event,
+            Err(e) => return Some(Err(e)),
+        };
+
+        // Control signals from upstream carry no market time of their own
+        if event.is_control() {
+            return Some(Ok(event));
+        }
+
+        let session = self.spec.session_of(event.timestamp());
+        match self.current {
+            Some(current) if session != Some(current) => {
+                self.current = session;
+                self.held = Some(event);
+                Some(Ok(MarketEvent::Control(ControlSignal::SessionEnd(
+                    self.spec.close_ts(current),
+                ))))
+            }
+            _ => {
+                self.current = session;
+                Some(Ok(event))
+            }
+        }
+    }
+}
+
+impl<I: EventParser> EventParser for SessionGate<I> {
+    fn tick_size(&self) -> f64 {
+        self.inner.tick_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3_600_000;
+    /// 2021-01-04 00:00 UTC
+    const DAY0: i64 = 1_609_718_400_000;
+    /// US Eastern (standard time)
+    const ET: i64 = -5 * HOUR;
+
+    fn candle_at(ts_open: i64) -> Candle {
+        Candle {
+            ts_open,
+            ts_close: ts_open + 60_000,
+            open_tick: 100,
+            high_tick: 100,
+            low_tick: 100,
+            close_tick: 100,
+            volume_scaled: 1_000_000,
+            trade_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_session_boundaries_regular_hours() {
+        // 9:30-16:00 ET is 14:30-21:00 UTC
+        let candles = [
+            candle_at(DAY0 + 14 * HOUR + HOUR / 2), // day 0 open
+            candle_at(DAY0 + 20 * HOUR),
+            candle_at(DAY0 + 22 * HOUR), // after close
+            candle_at(DAY0 + MS_PER_DAY + 15 * HOUR), // day 1
+            candle_at(DAY0 + MS_PER_DAY + 16 * HOUR),
+        ];
+
+        let boundaries = session_boundaries(&candles, ET, 9 * HOUR + HOUR / 2, 16 * HOUR);
+        assert_eq!(boundaries, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_session_wrapping_midnight() {
+        // 18:00-17:00 UTC futures session
+        let spec = SessionSpec::new(0, 18 * HOUR, 17 * HOUR);
+        let day0 = DAY0 / MS_PER_DAY;
+
+        assert_eq!(spec.session_of(DAY0 + 19 * HOUR), Some(day0));
+        assert_eq!(spec.session_of(DAY0 + MS_PER_DAY + 10 * HOUR), Some(day0));
+        assert_eq!(spec.session_of(DAY0 + MS_PER_DAY + 17 * HOUR + 1), None);
+        assert_eq!(spec.close_ts(day0), DAY0 + MS_PER_DAY + 17 * HOUR);
+    }
+
+    #[test]
+    fn test_session_gate_emits_session_end() {
+        let spec = SessionSpec::new(ET, 9 * HOUR + HOUR / 2, 16 * HOUR);
+        let events = [
+            candle_at(DAY0 + 15 * HOUR),
+            candle_at(DAY0 + 16 * HOUR),
+            candle_at(DAY0 + MS_PER_DAY + 15 * HOUR),
+        ]
+        .map(|c| Ok(MarketEvent::Bar(c)));
+
+        let gated: Vec<MarketEvent> = SessionGate::new(events.into_iter(), spec)
+            .map(|e| e.unwrap())
+            .collect();
+
+        assert_eq!(gated.len(), 4);
+        assert!(gated[0].is_bar());
+        assert!(gated[1].is_bar());
+        match gated[2] {
+            MarketEvent::Control(ControlSignal::SessionEnd(ts_ms)) => assert_eq!(ts_ms, DAY0 + 21 * HOUR),
+            other => panic!("expected session end, got {:?}", other),
+        }
+        assert!(gated[3].is_bar());
+    }
+}
@@ -0,0 +1,198 @@
+//! Pull-based event sources shared by file replay and live feeds
+//!
+//! Strategy code written against [`LiveCandleSource`] runs unchanged over a
+//! recorded file ([`FileSource`]) or a live feed, and [`BufferedSource`]
+//! reads ahead of either on a background thread so parsing or network waits
+//! overlap with the strategy.
+//!
+//! `recv` blocks until the next event. When the data ends a source returns
+//! [`ControlSignal::EndOfData`] once and [`SourceError::Exhausted`] after
+//! that. A parse error does not end the stream; the caller decides whether
+//! to go on.
+
+use crate::candle_parser::{self, CandleParser, ParseError};
+use crate::market_event::{CandleEventAdapter, ControlSignal, EventParser, MarketEvent};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SourceError {
+    #[error("Parse error: {0}")]
+    Parse(#[from] ParseError),
+
+    #[error("Source exhausted: end of data was already returned")]
+    Exhausted,
+
+    #[error("Source disconnected")]
+    Disconnected,
+}
+
+/// A stream of market events pulled one at a time
+pub trait LiveCandleSource {
+    /// Next event, blocking until one is available
+    fn recv(&mut self) -> Result<MarketEvent, SourceError>;
+
+    /// Whether events arrive in real time rather than being replayed from
+    /// storage
+    fn is_live(&self) -> bool;
+}
+
+impl<S: LiveCandleSource + ?Sized> LiveCandleSource for Box<S> {
+    fn recv(&mut self) -> Result<MarketEvent, SourceError> {
+        (**self).recv()
+    }
+
+    fn is_live(&self) -> bool {
+        (**self).is_live()
+    }
+}
+
+/// Replay of recorded events from any [`EventParser`]
+pub struct FileSource<P: EventParser> {
+    parser: P,
+    ended: bool,
+}
+
+impl<P: EventParser> FileSource<P> {
+    pub fn new(parser: P) -> Self {
+        Self { parser, ended: false }
+    }
+
+    pub fn into_inner(self) -> P {
+        self.parser
+    }
+}
+
+impl FileSource<CandleEventAdapter<Box<dyn CandleParser + Send>>> {
+    /// Replay a candle file, with the format chosen by extension as in
+    /// [`candle_parser::from_file_path`]
+    pub fn open(path: impl AsRef<std::path::Path>, tick_size: f64) -> Result<Self, ParseError> {
+        let parser = candle_parser::from_file_path(path, tick_size)?;
+        Ok(Self::new(CandleEventAdapter::new(parser)))
+    }
+}
+
+impl<P: EventParser> LiveCandleSource for FileSource<P> {
+    fn recv(&mut self) -> Result<MarketEvent, SourceError> {
+        if self.ended {
+            return Err(SourceError::Exhausted);
+        }
+        match self.parser.next() {
+            Some(event) => Ok(event?),
+            None => {
+                self.ended = true;
+                Ok(MarketEvent::Control(ControlSignal::EndOfData))
+            }
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
+/// Read-ahead decorator: a background thread pulls from the inner source
+/// into a bounded buffer
+///
+/// At most `capacity` events are buffered; the reader blocks when the
+/// buffer is full, so a slow consumer holds memory steady. The thread stops
+/// after the inner source's end of data, or once this source is dropped and
+/// the inner `recv` returns. A panic in the inner source surfaces as
+/// [`SourceError::Disconnected`].
+pub struct BufferedSource {
+    rx: Receiver<Result<MarketEvent, SourceError>>,
+    live: bool,
+    ended: bool,
+}
+
+impl BufferedSource {
+    pub fn new<S: LiveCandleSource + Send + 'static>(mut source: S, capacity: usize) -> Self {
+        let live = source.is_live();
+        let (tx, rx) = sync_channel(capacity);
+
+        thread::spawn(move || loop {
+            let event = source.recv();
+            let last = matches!(
+                event,
+                Ok(MarketEvent::Control(ControlSignal::EndOfData))
+                    | Err(SourceError::Exhausted | SourceError::Disconnected)
+            );
+            if tx.send(event).is_err() || last {
+                break;
+            }
+        });
+
+        Self { rx, live, ended: false }
+    }
+}
+
+impl LiveCandleSource for BufferedSource {
+    fn recv(&mut self) -> Result<MarketEvent, SourceError> {
+        if self.ended {
+            return Err(SourceError::Exhausted);
+        }
+        match self.rx.recv() {
+            Ok(event) => {
+                if matches!(event, Ok(MarketEvent::Control(ControlSignal::EndOfData))) {
+                    self.ended = true;
+                }
+                event
+            }
+            Err(_) => {
+                self.ended = true;
+                Err(SourceError::Disconnected)
+            }
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.live
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+timestamp,open,high,low,close,volume
+1609459200000,42000,42500,41500,42200,1500
+1609459260000,42200,42800,42100,42700,2000
+1609459320000,42700,43000,42600,oops,1800
+1609459380000,42900,43100,42800,43000,1700
+";
+
+    /// Bar timestamps up to the end of data, with parse errors as `None`
+    fn drain(source: &mut dyn LiveCandleSource) -> Vec<Option<i64>> {
+        let mut seen = Vec::new();
+        loop {
+            match source.recv() {
+                Ok(MarketEvent::Bar(candle)) => seen.push(Some(candle.ts_open)),
+                Ok(MarketEvent::Control(ControlSignal::EndOfData)) => break,
+                Ok(other) => panic!("unexpected event {:?}", other),
+                Err(SourceError::Parse(_)) => seen.push(None),
+                Err(e) => panic!("unexpected error {}", e),
+            }
+        }
+        assert!(matches!(source.recv(), Err(SourceError::Exhausted)));
+        seen
+    }
+
+    #[test]
+    fn test_file_source_through_trait_object() {
+        let path = std::env::temp_dir().join(format!("ag_core_source_{}.csv", std::process::id()));
+        std::fs::write(&path, CSV).unwrap();
+
+        let expected = vec![Some(1609459200000), Some(1609459260000), None, Some(1609459380000)];
+        let mut source: Box<dyn LiveCandleSource> = Box::new(FileSource::open(&path, 1.0).unwrap());
+        assert!(!source.is_live());
+        assert_eq!(drain(&mut source), expected);
+
+        let mut buffered = BufferedSource::new(FileSource::open(&path, 1.0).unwrap(), 2);
+        assert!(!buffered.is_live());
+        assert_eq!(drain(&mut buffered), expected);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
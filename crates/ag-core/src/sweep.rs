@@ -0,0 +1,163 @@
+//! Parameter sweeps that start every variation from a warm engine state
+
+use crate::{Engine, EngineError};
+
+/// Runs one backtest per parameter set, restoring a warm state before each
+///
+/// `restore` puts the engine back into its post-warmup state, typically
+/// `|engine| { engine.restore(&checkpoint); Ok(()) }`. This avoids rebuilding
+/// the engine and re-ingesting the warmup window for every variation.
+pub struct ParamSweep<F, P> {
+    restore: F,
+    params: Vec<P>,
+}
+
+impl<F, P> ParamSweep<F, P>
+where
+    F: FnMut(&mut Engine) -> Result<(), EngineError>,
+{
+    pub fn new(restore: F, params: Vec<P>) -> Self {
+        Self { restore, params }
+    }
+
+    /// Parameter sets, in run order
+    pub fn params(&self) -> &[P] {
+        &self.params
+    }
+
+    /// Run `variation` once per parameter set, collecting the results in order
+    ///
+    /// Stops at the first error from either `restore` or `variation`.
+    pub fn run<R>(
+        &mut self,
+        engine: &mut Engine,
+        mut variation: impl FnMut(&mut Engine, &P) -> Result<R, EngineError>,
+    ) -> Result<Vec<R>, EngineError> {
+        let mut results = Vec::with_capacity(self.params.len());
+
+        for params in &self.params {
+            (self.restore)(engine)?;
+            results.push(variation(engine, params)?);
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EngineConfig;
+
+    fn config() -> EngineConfig {
+        EngineConfig {
+            initial_cash: 10_000.0,
+            maker_fee_bps: 0.0,
+            taker_fee_bps: 0.0,
+            spread_bps: 0.0,
+            ..EngineConfig::default()
+        }
+    }
+
+    /// Warmup: a slow drift ending with a 1-unit long position at 100.00
+    fn warm_up(engine: &mut Engine, ticks: i64) -> Result<(), EngineError> {
+        for i in 0..ticks {
+            engine.step_tick(i, 10_000 + (i % 7), 1.0, "SELL")?;
+        }
+        engine.place_order("MARKET", "BUY", 1.0, 0.0)?;
+        engine.step_tick(ticks, 10_000, 1.0, "SELL")
+    }
+
+    /// Exit the warm position at `exit_tick`, returning realized PnL
+    fn exit_at(engine: &mut Engine, exit_tick: &i64) -> Result<f64, EngineError> {
+        let ts = engine.get_snapshot().ts_ms + 1;
+        engine.place_order("MARKET", "SELL", 1.0, 0.0)?;
+        engine.step_tick(ts, *exit_tick, 1.0, "BUY")?;
+        Ok(engine.get_snapshot().realized_pnl)
+    }
+
+    #[test]
+    fn test_sweep_runs_each_variation_from_warm_state() {
+        let mut engine = Engine::with_config(config()).unwrap();
+        warm_up(&mut engine, 100).unwrap();
+        let checkpoint = engine.checkpoint().unwrap();
+
+        let mut sweep = ParamSweep::new(
+            |engine: &mut Engine| {
+                engine.restore(&checkpoint);
+                Ok(())
+            },
+            vec![10_050, 9_950, 10_000],
+        );
+        let pnls = sweep.run(&mut engine, exit_at).unwrap();
+
+        assert_eq!(pnls.len(), 3);
+        assert!((pnls[0] - 0.5).abs() < 1e-9);
+        assert!((pnls[1] + 0.5).abs() < 1e-9);
+        assert!(pnls[2].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sweep_stops_on_error() {
+        let mut engine = Engine::with_config(config()).unwrap();
+        engine.mark_to(100.0).unwrap();
+        let checkpoint = engine.checkpoint().unwrap();
+
+        let mut sweep = ParamSweep::new(
+            |engine: &mut Engine| {
+                engine.restore(&checkpoint);
+                Ok(())
+            },
+            vec!["BUY", "HOLD", "SELL"],
+        );
+        let mut calls = 0;
+        let result = sweep.run(&mut engine, |engine, side| {
+            calls += 1;
+            engine.place_order("MARKET", side, 1.0, 0.0)
+        });
+
+        assert!(matches!(result, Err(EngineError::InvalidSide(_))));
+        assert_eq!(calls, 2);
+    }
+
+    /// Timing comparison against a cold restart; run with `cargo test -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_warm_restore_vs_cold_restart() {
+        use std::time::Instant;
+
+        let warmup_ticks = 50_000;
+        let params: Vec<i64> = (0..200).map(|i| 9_900 + i).collect();
+
+        let start = Instant::now();
+        let mut cold_results = Vec::with_capacity(params.len());
+        for exit_tick in &params {
+            let mut engine = Engine::with_config(config()).unwrap();
+            warm_up(&mut engine, warmup_ticks).unwrap();
+            cold_results.push(exit_at(&mut engine, exit_tick).unwrap());
+        }
+        let cold_elapsed = start.elapsed();
+
+        let start = Instant::now();
+        let mut engine = Engine::with_config(config()).unwrap();
+        warm_up(&mut engine, warmup_ticks).unwrap();
+        let checkpoint = engine.checkpoint().unwrap();
+        let mut sweep = ParamSweep::new(
+            |engine: &mut Engine| {
+                engine.restore(&checkpoint);
+                Ok(())
+            },
+            params,
+        );
+        let warm_results = sweep.run(&mut engine, exit_at).unwrap();
+        let warm_elapsed = start.elapsed();
+
+        assert_eq!(cold_results, warm_results);
+        println!(
+            "cold restart: {:?}, warm restore: {:?} ({:.1}x)",
+            cold_elapsed,
+            warm_elapsed,
+            cold_elapsed.as_secs_f64() / warm_elapsed.as_secs_f64()
+        );
+    }
+}
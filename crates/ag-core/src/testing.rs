@@ -0,0 +1,82 @@
+//! Deterministic end-to-end runs for golden-file regression tests
+//!
+//! The engine has no randomness: matching is integer tick arithmetic, ties
+//! in [`crate::candle_parser::merge`] are broken by source index, and replay
+//! steps bars in input order. Running the same CSV under the same config
+//! therefore yields the same equity curve bit for bit, which is what lets a
+//! committed expected curve catch silent behavior changes across refactors.
+//!
+//! Set `AG_UPDATE_GOLDEN=1` when running the golden test to rewrite the
+//! expected curve after an intended change.
+
+use crate::candle_parser::CsvCandleIter;
+use crate::{market_event, Engine, EngineConfig, EngineError};
+use std::fmt::Write;
+
+/// Replay CSV candle data (one tick per bar) on a fresh engine built from
+/// `config`, returning the `(ts_ms, equity)` curve recorded after every bar
+pub fn run_fixture(csv: &str, config: EngineConfig) -> Result<Vec<(i64, f64)>, EngineError> {
+    let mut engine = Engine::with_config(config)?;
+    engine.enable_equity_log();
+
+    let parser = CsvCandleIter::new(csv.as_bytes(), config.tick_size)?;
+    market_event::replay(parser, &mut engine)?;
+    Ok(engine.equity_curve().to_vec())
+}
+
+/// Render a curve as `ts_ms,equity` lines, with equity printed in its
+/// shortest exact form so that equal text means equal bits
+pub fn format_curve(curve: &[(i64, f64)]) -> String {
+    let mut out = String::from("ts_ms,equity\n");
+    for (ts_ms, equity) in curve {
+        let _ = writeln!(out, "{},{:?}", ts_ms, equity);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOLDEN_CANDLES: &str = include_str!("../tests/fixtures/golden_candles.csv");
+    const GOLDEN_EQUITY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/golden_equity.csv");
+
+    /// A short carried into the fixture with fees, spread and borrow cost,
+    /// so marking, accrual and the config plumbing are all exercised
+    fn golden_config() -> EngineConfig {
+        EngineConfig {
+            initial_cash: 50_000.0,
+            maker_fee_bps: 1.0,
+            taker_fee_bps: 2.0,
+            spread_bps: 2.0,
+            tick_size: 0.25,
+            initial_position: -3.0,
+            initial_entry_price: 4_210.0,
+            borrow_fee_bps_per_day: 25.0,
+            ..EngineConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_golden_equity_curve() {
+        let curve = run_fixture(GOLDEN_CANDLES, golden_config()).unwrap();
+        assert_eq!(curve.len(), 40);
+        let actual = format_curve(&curve);
+
+        if std::env::var_os("AG_UPDATE_GOLDEN").is_some() {
+            std::fs::write(GOLDEN_EQUITY_PATH, &actual).unwrap();
+        }
+        let expected = std::fs::read_to_string(GOLDEN_EQUITY_PATH).unwrap();
+        assert!(actual == expected, "equity curve differs from {}", GOLDEN_EQUITY_PATH);
+    }
+
+    #[test]
+    fn test_runs_are_repeatable() {
+        let first = run_fixture(GOLDEN_CANDLES, golden_config()).unwrap();
+        let second = run_fixture(GOLDEN_CANDLES, golden_config()).unwrap();
+        assert_eq!(
+            first.iter().map(|&(ts, e)| (ts, e.to_bits())).collect::<Vec<_>>(),
+            second.iter().map(|&(ts, e)| (ts, e.to_bits())).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! Streaming aggregate-trade parsers (Binance-style aggTrades)
+
+use crate::candle::RoundingMode;
+use crate::candle_parser::ParseError;
+use crate::market_event::AggTrade;
+use std::io::Read;
+
+/// Trait for streaming aggregate-trade parsers
+pub trait TradeParser: Iterator<Item = Result<AggTrade, ParseError>> {
+    /// Get the tick size used for quantization
+    fn tick_size(&self) -> f64;
+}
+
+/// Side encoding shared with `AggTrade::side`
+const SIDE_BUY: u8 = 0;
+const SIDE_SELL: u8 = 1;
+
+/// Map Binance's `is_buyer_maker` flag to the aggressor side
+///
+/// A buyer-maker trade means the taker sold, so it is recorded as SELL.
+#[inline]
+fn side_from_buyer_maker(is_buyer_maker: bool) -> u8 {
+    if is_buyer_maker {
+        SIDE_SELL
+    } else {
+        SIDE_BUY
+    }
+}
+
+/// Quantize and validate a raw trade
+fn build_trade(
+    ts_ms: i64,
+    price: f64,
+    qty: f64,
+    side: u8,
+    tick_size: f64,
+    rounding: RoundingMode,
+) -> Result<AggTrade, ParseError> {
+    if ts_ms <= 0 {
+        return Err(ParseError::InvalidValue {
+            field: "timestamp".to_string(),
+            value: ts_ms.to_string(),
+        });
+    }
+
+    if !price.is_finite() {
+        return Err(ParseError::InvalidValue {
+            field: "price".to_string(),
+            value: price.to_string(),
+        });
+    }
+
+    if !qty.is_finite() || qty < 0.0 {
+        return Err(ParseError::InvalidValue {
+            field: "qty".to_string(),
+            value: qty.to_string(),
+        });
+    }
+
+    Ok(AggTrade {
+        ts_ms,
+        price_tick: rounding.quantize(price, tick_size),
+        qty_scaled: (qty * 1_000_000.0).round() as i64,
+        side,
+    })
+}
+
+// ============================================================================
+// CSV Parser Implementation
+// ============================================================================
+
+/// Streaming CSV aggTrades parser
+///
+/// Expects columns `timestamp`, `price`, `qty` and either `is_buyer_maker`
+/// (true/false/1/0) or `side` (buy/sell).
+pub struct CsvTradeIter<R: Read> {
+    reader: csv::Reader<R>,
+    tick_size: f64,
+    rounding: RoundingMode,
+    header_map: TradeHeaderMap,
+}
+
+/// Maps CSV column indices to trade fields
+#[derive(Debug)]
+struct TradeHeaderMap {
+    ts_idx: usize,
+    price_idx: usize,
+    qty_idx: usize,
+    side_idx: SideColumn,
+}
+
+#[derive(Debug)]
+enum SideColumn {
+    BuyerMaker(usize),
+    Side(usize),
+}
+
+impl TradeHeaderMap {
+    fn from_headers(headers: &csv::StringRecord) -> Result<Self, ParseError> {
+        let mut ts_idx = None;
+        let mut price_idx = None;
+        let mut qty_idx = None;
+        let mut side_idx = None;
+
+        for (idx, header) in headers.iter().enumerate() {
+            let normalized = header.trim().to_lowercase();
+
+            match normalized.as_str() {
+                "timestamp" | "ts" | "time" | "transact_time" => ts_idx = Some(idx),
+                "price" | "p" => price_idx = Some(idx),
+                "qty" | "quantity" | "q" | "amount" => qty_idx = Some(idx),
+                "is_buyer_maker" | "m" => side_idx = Some(SideColumn::BuyerMaker(idx)),
+                "side" => side_idx = Some(SideColumn::Side(idx)),
+                _ => {} // Ignore unknown columns
+            }
+        }
+
+        Ok(Self {
+            ts_idx: ts_idx.ok_or_else(|| {
+                ParseError::HeaderMapping("Missing 'timestamp' column".to_string())
+            })?,
+            price_idx: price_idx.ok_or_else(|| {
+                ParseError::HeaderMapping("Missing 'price' column".to_string())
+            })?,
+            qty_idx: qty_idx.ok_or_else(|| {
+                ParseError::HeaderMapping("Missing 'qty' column".to_string())
+            })?,
+            side_idx: side_idx.ok_or_else(|| {
+                ParseError::HeaderMapping("Missing 'is_buyer_maker' or 'side' column".to_string())
+            })?,
+        })
+    }
+}
+
+impl<R: Read> CsvTradeIter<R> {
+    /// Create a new CSV aggTrades iterator
+    ///
+    /// # Arguments
+    /// * `reader` - Buffered reader for CSV data
+    /// * `tick_size` - Tick size for price quantization
+    pub fn new(reader: R, tick_size: f64) -> Result<Self, ParseError> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .flexible(false)
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        let headers = csv_reader.headers()?.clone();
+        let header_map = TradeHeaderMap::from_headers(&headers)?;
+
+        Ok(Self {
+            reader: csv_reader,
+            tick_size,
+            rounding: RoundingMode::default(),
+            header_map,
+        })
+    }
+
+    /// Set the price rounding mode
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Parse a single record into an AggTrade
+    fn parse_record(&self, record: &csv::StringRecord) -> Result<AggTrade, ParseError> {
+        let field = |idx: usize, field_name: &str| -> Result<&str, ParseError> {
+            record.get(idx).ok_or_else(|| ParseError::MissingField(field_name.to_string()))
+        };
+
+        let invalid = |field_name: &str, value: &str| ParseError::InvalidValue {
+            field: field_name.to_string(),
+            value: value.to_string(),
+        };
+
+        let ts_str = field(self.header_map.ts_idx, "timestamp")?;
+        let ts_ms = ts_str.parse::<i64>().map_err(|_| invalid("timestamp", ts_str))?;
+
+        let price_str = field(self.header_map.price_idx, "price")?;
+        let price = price_str.parse::<f64>().map_err(|_| invalid("price", price_str))?;
+
+        let qty_str = field(self.header_map.qty_idx, "qty")?;
+        let qty = qty_str.parse::<f64>().map_err(|_| invalid("qty", qty_str))?;
+
+        let side = match self.header_map.side_idx {
+            SideColumn::BuyerMaker(idx) => {
+                let value = field(idx, "is_buyer_maker")?;
+                match value.to_lowercase().as_str() {
+                    "true" | "1" => side_from_buyer_maker(true),
+                    "false" | "0" => side_from_buyer_maker(false),
+                    _ => return Err(invalid("is_buyer_maker", value)),
+                }
+            }
+            SideColumn::Side(idx) => {
+                let value = field(idx, "side")?;
+                match value.to_uppercase().as_str() {
+                    "BUY" => SIDE_BUY,
+                    "SELL" => SIDE_SELL,
+                    _ => return Err(invalid("side", value)),
+                }
+            }
+        };
+
+        build_trade(ts_ms, price, qty, side, self.tick_size, self.rounding)
+    }
+}
+
+impl<R: Read> Iterator for CsvTradeIter<R> {
+    type Item = Result<AggTrade, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = csv::StringRecord::new();
+
+        match self.reader.read_record(&mut record) {
+            Ok(true) => Some(self.parse_record(&record)),
+            Ok(false) => None, // End of file
+            Err(e) => Some(Err(ParseError::Csv(e))),
+        }
+    }
+}
+
+impl<R: Read> TradeParser for CsvTradeIter<R> {
+    fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+}
+
+// ============================================================================
+// JSON Parser Implementation
+// ============================================================================
+
+/// Streaming NDJSON aggTrades parser
+///
+/// Accepts both descriptive keys (`timestamp`, `price`, `qty`, `is_buyer_maker`)
+/// and Binance's short keys (`T`, `p`, `q`, `m`). Prices and quantities may be
+/// JSON numbers or numeric strings.
+pub struct JsonTradeIter<R: Read> {
+    deserializer: serde_json::StreamDeserializer<'static, serde_json::de::IoRead<R>, TradeJson>,
+    tick_size: f64,
+    rounding: RoundingMode,
+}
+
+/// Number that may be encoded as a JSON string (as Binance does)
+#[derive(serde::Deserialize, Debug)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(f64),
+    String(String),
+}
+
+impl NumberOrString {
+    fn to_f64(&self, field_name: &str) -> Result<f64, ParseError> {
+        match self {
+            NumberOrString::Number(n) => Ok(*n),
+            NumberOrString::String(s) => s.trim().parse::<f64>().map_err(|_| ParseError::InvalidValue {
+                field: field_name.to_string(),
+                value: s.clone(),
+            }),
+        }
+    }
+}
+
+/// JSON representation of an aggregate trade for serde
+#[derive(serde::Deserialize, Debug)]
+struct TradeJson {
+    #[serde(alias = "T", alias = "ts", alias = "time")]
+    timestamp: i64,
+
+    #[serde(alias = "p")]
+    price: NumberOrString,
+
+    #[serde(alias = "q", alias = "quantity")]
+    qty: NumberOrString,
+
+    #[serde(alias = "m")]
+    is_buyer_maker: bool,
+}
+
+impl<R: Read> JsonTradeIter<R> {
+    /// Create a new JSON aggTrades iterator
+    ///
+    /// Expects newline-delimited JSON (NDJSON) format
+    pub fn new(reader: R, tick_size: f64) -> Self {
+        Self {
+            deserializer: serde_json::Deserializer::from_reader(reader).into_iter::<TradeJson>(),
+            tick_size,
+            rounding: RoundingMode::default(),
+        }
+    }
+
+    /// Set the price rounding mode
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+}
+
+impl<R: Read> Iterator for JsonTradeIter<R> {
+    type Item = Result<AggTrade, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.deserializer.next()? {
+            Ok(trade_json) => {
+                let trade = trade_json.price.to_f64("price").and_then(|price| {
+                    let qty = trade_json.qty.to_f64("qty")?;
+                    build_trade(
+                        trade_json.timestamp,
+                        price,
+                        qty,
+                        side_from_buyer_maker(trade_json.is_buyer_maker),
+                        self.tick_size,
+                        self.rounding,
+                    )
+                });
+                Some(trade)
+            }
+            Err(e) => Some(Err(ParseError::Json(e))),
+        }
+    }
+}
+
+impl<R: Read> TradeParser for JsonTradeIter<R> {
+    fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_csv_trade_parser_basic() {
+        let csv_data = "\
+timestamp,price,qty,is_buyer_maker
+1704067200000,42150.50,0.025,true
+1704067200050,42151.00,0.018,false
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let mut parser = CsvTradeIter::new(cursor, 0.5).unwrap();
+
+        let trade1 = parser.next().unwrap().unwrap();
+        assert_eq!(trade1.ts_ms, 1704067200000);
+        assert_eq!(trade1.price_tick, 84301);
+        assert_eq!(trade1.qty_scaled, 25_000);
+        assert_eq!(trade1.side, SIDE_SELL);
+
+        let trade2 = parser.next().unwrap().unwrap();
+        assert_eq!(trade2.side, SIDE_BUY);
+
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn test_csv_trade_parser_invalid_side() {
+        let csv_data = "\
+timestamp,price,qty,side
+1704067200000,42150.50,0.025,hold
+";
+
+        let cursor = Cursor::new(csv_data.as_bytes());
+        let mut parser = CsvTradeIter::new(cursor, 0.5).unwrap();
+
+        assert!(matches!(parser.next().unwrap(), Err(ParseError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_json_trade_parser_binance_keys() {
+        let json_data = r#"
+{"a":1,"p":"42150.50","q":"0.025","f":1,"l":1,"T":1704067200000,"m":true}
+{"timestamp":1704067200050,"price":42151.0,"qty":0.018,"is_buyer_maker":false}
+"#;
+
+        let cursor = Cursor::new(json_data.as_bytes());
+        let mut parser = JsonTradeIter::new(cursor, 0.5);
+
+        let trade1 = parser.next().unwrap().unwrap();
+        assert_eq!(trade1.ts_ms, 1704067200000);
+        assert_eq!(trade1.price_tick, 84301);
+        assert_eq!(trade1.side, SIDE_SELL);
+
+        let trade2 = parser.next().unwrap().unwrap();
+        assert_eq!(trade2.price_tick, 84302);
+        assert_eq!(trade2.side, SIDE_BUY);
+
+        assert!(parser.next().is_none());
+    }
+}
@@ -1,6 +1,6 @@
 """Engine wrapper - thin Python layer over Rust/C core"""
 from dataclasses import dataclass
-from typing import List, Optional
+from typing import Callable, List, Optional
 import warnings
 
 
@@ -13,6 +13,17 @@ class EngineConfig:
     spread_bps: float = 2.0    # Spread in basis points
     spread_abs: Optional[float] = None  # Or absolute spread
     tick_size: float = 0.01
+    latency_ms: int = 0  # Orders can only fill on ticks at least this much later
+    initial_position: float = 0.0     # Starting position (negative = short)
+    initial_entry_price: float = 0.0  # Avg entry price of the starting position
+    price_band_bps: Optional[float] = None  # Reject limits this far from the last trade
+    borrow_fee_bps_per_day: float = 0.0     # Daily borrow cost of short positions, in bps
+    min_order_qty: Optional[float] = None       # Reject orders smaller than this
+    min_order_notional: Optional[float] = None  # Reject orders worth less than this
+    limit_fill_model: str = 'touch'  # 'touch' fills limits at their price, 'through' needs a better trade
+    allow_rebates: bool = False      # Accept negative fees (down to -max_rebate_bps)
+    max_fee_bps: float = 1_000.0     # Highest fee accepted (10%)
+    max_rebate_bps: float = 100.0    # Largest rebate accepted with allow_rebates (1%)
 
 
 @dataclass
@@ -32,6 +43,10 @@ class Order:
     qty: float
     price: Optional[float] = None
     order_id: Optional[int] = None
+    client_tag: int = 0  # Echoed back in fills and open orders
+    valid_until_ms: Optional[int] = None  # Cancel once the clock passes this time
+    reduce_only: bool = False  # Only shrink the position, never grow or flip it
+    time_in_force: str = 'GTC'  # 'GTC', 'IOC' or 'FOK'; IOC/FOK get one tick to fill, a FOK limit must cross the last trade
 
 
 @dataclass
@@ -44,6 +59,8 @@ class Snapshot:
     realized_pnl: float
     unrealized_pnl: float
     equity: float
+    cumulative_volume: float = 0.0  # Total quantity filled
+    turnover: float = 0.0           # Total notional filled, before fees
 
 
 class Engine:
@@ -63,13 +80,24 @@ class Engine:
                 taker_fee=config.taker_fee,
                 spread_bps=config.spread_bps,
                 tick_size=config.tick_size,
+                latency_ms=config.latency_ms,
+                initial_position=config.initial_position,
+                initial_entry_price=config.initial_entry_price,
+                price_band_bps=config.price_band_bps,
+                borrow_fee_bps_per_day=config.borrow_fee_bps_per_day,
+                min_order_qty=config.min_order_qty,
+                min_order_notional=config.min_order_notional,
+                limit_fill_model=config.limit_fill_model,
+                allow_rebates=config.allow_rebates,
+                max_fee_bps=config.max_fee_bps,
+                max_rebate_bps=config.max_rebate_bps,
             )
         except (ImportError, AttributeError) as e:
             warnings.warn(f"Rust core not available ({e}), using stub")
             self._core = None
             self._cash = config.initial_cash
-            self._position = 0.0
-            self._avg_entry = 0.0
+            self._position = config.initial_position
+            self._avg_entry = config.initial_entry_price
 
     def reset(self):
         """Reset engine to initial state"""
@@ -77,7 +105,7 @@ class Engine:
             self._core.reset()
         else:
             self._cash = self.config.initial_cash
-            self._position = 0.0
+            self._position = self.config.initial_position
         self._history.clear()
         self._trades.clear()
 
@@ -95,7 +123,17 @@ class Engine:
         snapshot.ts_ms = tick.ts_ms
         self._history.append(snapshot)
 
-    def step_batch(self, timestamps, price_ticks, qtys, sides):
+    def step_candle(self, ts_open: int, ts_close: int, open_tick: int, high_tick: int,
+                    low_tick: int, close_tick: int, volume: float, policy: str = 'close'):
+        """Process one candle (prices in ticks) as synthetic ticks: 'close' or 'ohlc'"""
+        if self._core:
+            self._core.step_candle(ts_open, ts_close, open_tick, high_tick,
+                                   low_tick, close_tick, volume, policy)
+        snapshot = self.get_snapshot()
+        snapshot.ts_ms = ts_close
+        self._history.append(snapshot)
+
+    def step_batch(self, timestamps, price_ticks, qtys, sides, on_error: str = 'abort'):
         """
         Process a batch of ticks efficiently.
 
@@ -104,6 +142,12 @@ class Engine:
             price_ticks: list or numpy array of int64 price ticks
             qtys: list or numpy array of float64 quantities
             sides: list or numpy array of uint8 sides (0=BUY, 1=SELL)
+            on_error: 'abort' (raise at the first bad tick), 'skip' (skip bad
+                ticks) or 'stop' (stop at the first bad tick without raising)
+
+        Returns:
+            BatchResult with processed, skipped and first_error_index (None
+            without the core)
         """
         if self._core:
             # Convert to list if needed (handles both lists and numpy arrays)
@@ -112,25 +156,80 @@ class Engine:
             qty_list = qtys if isinstance(qtys, list) else qtys.tolist()
             side_list = sides if isinstance(sides, list) else sides.tolist()
 
-            self._core.step_batch(ts_list, pt_list, qty_list, side_list)
+            return self._core.step_batch(ts_list, pt_list, qty_list, side_list, on_error)
         else:
             # Stub: process one by one
             for i in range(len(timestamps)):
                 # Update stub state
                 pass
 
-    def place_order(self, order: Order):
-        """Place an order"""
+    def place_order(self, order: Order) -> Optional[int]:
+        """Place an order, returning the engine-assigned order id"""
         if self._core:
-            self._core.place_order(
+            order.order_id = self._core.place_order(
                 order.order_type,
                 order.side,
                 order.qty,
                 order.price or 0.0,
+                order.client_tag,
+                order.valid_until_ms,
+                order.reduce_only,
+                order.time_in_force,
             )
+            return order.order_id
         else:
             # Stub: immediate execution
-            pass
+            return None
+
+    def place_orders(self, orders: List[Order], on_error: str = 'abort') -> List[Optional[int]]:
+        """Place many orders in one core call, returning their ids
+
+        on_error: 'abort' (raise at the first rejected order), 'skip' (None
+            in place of rejected orders) or 'stop' (ids up to the first rejection)
+        """
+        if self._core:
+            ids = self._core.place_orders(
+                [order.order_type for order in orders],
+                [order.side for order in orders],
+                [order.qty for order in orders],
+                [order.price or 0.0 for order in orders],
+                on_error,
+            )
+            for order, order_id in zip(orders, ids):
+                order.order_id = order_id
+            return ids
+        return [None] * len(orders)
+
+    def cancel_order(self, order_id: int):
+        """Cancel an open order by its engine-assigned id"""
+        if self._core:
+            self._core.cancel_order(order_id)
+
+    def advance_clock(self, ts_ms: int):
+        """Move the engine clock without a trade (expires GTD orders)"""
+        if self._core:
+            self._core.advance_clock(ts_ms)
+
+    def finalize(self, final_price: Optional[float] = None) -> Optional[dict]:
+        """Cancel dangling orders at the end of a run, closing the position at final_price if given;
+        returns the final snapshot dict with 'cancelled' orders"""
+        if self._core:
+            return self._core.finalize(final_price)
+        return None
+
+    def replay_file(self, path: str, progress_every: int = 10_000,
+                    on_progress: Optional[Callable[[int, int, Optional[float]], None]] = None) -> int:
+        """Replay a candle file as one tick per bar, calling on_progress(events, ts_ms, eta_secs)"""
+        if self._core:
+            return self._core.replay_file(path, progress_every, on_progress)
+        return 0
+
+    def run_file(self, path: str, default_side: str = 'BUY') -> Optional[dict]:
+        """Run a candle file through the core as one tick per bar at the close, with
+        default_side as the aggressor; returns {'events', 'final_equity'}"""
+        if self._core:
+            return self._core.run_file(path, default_side)
+        return None
 
     def get_snapshot(self) -> Snapshot:
         """Get current engine state"""
@@ -144,6 +243,8 @@ class Engine:
                 realized_pnl=data['realized_pnl'],
                 unrealized_pnl=data['unrealized_pnl'],
                 equity=data['equity'],
+                cumulative_volume=data['cumulative_volume'],
+                turnover=data['turnover'],
             )
         else:
             return Snapshot(
@@ -156,10 +257,148 @@ class Engine:
                 equity=self._cash,
             )
 
+    def position(self) -> float:
+        """Current position, without building a full snapshot"""
+        if self._core:
+            return self._core.position()
+        return self._position
+
+    def cash(self) -> float:
+        """Current cash, without building a full snapshot"""
+        if self._core:
+            return self._core.cash()
+        return self._cash
+
+    def equity(self) -> float:
+        """Current equity, without building a full snapshot"""
+        if self._core:
+            return self._core.equity()
+        return self._cash
+
+    def avg_entry_price(self) -> float:
+        """Average entry price, without building a full snapshot"""
+        if self._core:
+            return self._core.avg_entry_price()
+        return self._avg_entry
+
+    def pnl_at_price(self, price: float) -> float:
+        """Realized plus unrealized PnL if the position were marked at price"""
+        if self._core:
+            return self._core.pnl_at_price(price)
+        return self._position * (price - self._avg_entry)
+
+    def equity_at_price(self, price: float) -> float:
+        """Equity if the position were marked at price"""
+        if self._core:
+            return self._core.equity_at_price(price)
+        return self._cash + self._position * (price - self._avg_entry)
+
+    def unrealized_at(self, price: float) -> float:
+        """Unrealized PnL if the position were marked at price"""
+        if self._core:
+            return self._core.unrealized_at(price)
+        return self._position * (price - self._avg_entry)
+
+    def mark_to(self, price: float) -> Snapshot:
+        """Value the position at an external mark price without a trade"""
+        if self._core:
+            self._core.mark_to(price)
+        return self.get_snapshot()
+
+    def get_config(self) -> dict:
+        """Parameters the core engine is running with, as Engine constructor kwargs"""
+        if self._core:
+            return self._core.get_config()
+        return dict(vars(self.config))
+
     def get_history(self) -> List[Snapshot]:
         """Get full snapshot history"""
         return self._history.copy()
 
     def get_trades(self) -> List[dict]:
-        """Get executed trades"""
+        """Get executed trades (order_id, client_tag, ts_ms, side, qty, effective price, fee)"""
+        if self._core:
+            return self._core.get_fills()
         return self._trades.copy()
+
+    def enable_equity_log(self, max_points: Optional[int] = None, every_n: Optional[int] = None,
+                          every_ms: Optional[int] = None):
+        """Record (ts_ms, equity) in the core after every tick (or every_n ticks / every_ms of
+        simulated time), compacting to keep at most max_points"""
+        if self._core:
+            self._core.enable_equity_log(max_points, every_n, every_ms)
+
+    def get_equity_curve(self, max_points: Optional[int] = None) -> List[tuple]:
+        """Get the recorded equity curve, min/max-downsampled to max_points if given"""
+        if self._core:
+            return self._core.get_equity_curve(max_points)
+        return []
+
+    def enable_drawdown_tracking(self):
+        """Track drawdown in the core from the equity after every tick, without storing the curve"""
+        if self._core:
+            self._core.enable_drawdown_tracking()
+
+    def current_drawdown(self) -> Optional[float]:
+        """Decline of equity from its peak, as a fraction (None unless tracking)"""
+        if self._core:
+            return self._core.current_drawdown()
+        return None
+
+    def max_drawdown(self) -> Optional[float]:
+        """Largest drawdown so far, as a fraction (None unless tracking)"""
+        if self._core:
+            return self._core.max_drawdown()
+        return None
+
+    def enable_queue_tracking(self):
+        """Estimate queue positions of limit orders placed from now on"""
+        if self._core:
+            self._core.enable_queue_tracking()
+
+    def queue_position(self, order_id: int) -> Optional[float]:
+        """Estimated quantity ahead of a resting limit order at its price level"""
+        if self._core:
+            return self._core.queue_position(order_id)
+        return None
+
+    def apply_funding(self, rate: float) -> float:
+        """Pay (rate > 0, long) or receive funding at the last price; returns the cash change"""
+        if self._core:
+            return self._core.apply_funding(rate)
+        return 0.0
+
+    def set_funding_schedule(self, payments: List[tuple]):
+        """Apply funding automatically at each (ts_ms, rate) as the clock passes it"""
+        if self._core:
+            self._core.set_funding_schedule(payments)
+
+    def get_funding_events(self) -> List[dict]:
+        """Get applied funding payments (ts_ms, rate, mark_price, position, amount)"""
+        if self._core:
+            return self._core.get_funding_events()
+        return []
+
+    def get_rejected_orders(self) -> List[dict]:
+        """Get delayed orders refused when latency released them (order_id, client_tag, ts_ms, reason)"""
+        if self._core:
+            return self._core.get_rejected_orders()
+        return []
+
+    def get_open_orders(self) -> List[dict]:
+        """Get unfilled orders (order_id, client_tag, order_type, side, qty, price, pending, reduce_only)"""
+        if self._core:
+            return self._core.get_open_orders()
+        return []
+
+    def get_book(self, levels: int = 10) -> dict:
+        """Resting limit depth as {'bids': [(price, qty)], 'asks': [...]}, best level first"""
+        if self._core:
+            return self._core.get_book(levels)
+        return {'bids': [], 'asks': []}
+
+    def benchmark(self, n: int = 100_000) -> Optional[dict]:
+        """Time n synthetic ticks on a scratch engine (ticks_per_sec, avg_ns_per_tick)"""
+        if self._core:
+            return self._core.benchmark(n)
+        return None
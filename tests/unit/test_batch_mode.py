@@ -10,7 +10,7 @@ import numpy as np
 
 sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', '..', 'python'))
 
-from ag_backtester.engine import Engine, EngineConfig, Tick
+from ag_backtester.engine import Engine, EngineConfig, Order, Tick
 
 
 class TestBatchProcessing:
@@ -138,7 +138,8 @@ class TestBatchProcessing:
         )
         engine = Engine(config)
 
-        # Place order first
+        # Place order first; a market buy needs a price to be costed at
+        engine.mark_to(100.0)
         engine.place_order(Order(order_type='MARKET', side='BUY', qty=2.5, price=100.0))
 
         # Process batch to fill order
@@ -198,5 +199,50 @@ class TestBatchProcessing:
             )
 
 
+    def test_batch_error_policies(self):
+        """A bad side mid-batch raises, is skipped, or stops the batch."""
+        config = EngineConfig(initial_cash=10000.0, spread_bps=0.0, tick_size=0.01)
+        batch = dict(
+            timestamps=[1000, 1001, 1002, 1003],
+            price_ticks=[10000, 10010, 10020, 10030],
+            qtys=[1.0, 1.0, 1.0, 1.0],
+            sides=[0, 1, 5, 0],  # 5 is not a valid side
+        )
+
+        with pytest.raises(Exception):
+            Engine(config).step_batch(**batch)
+
+        result = Engine(config).step_batch(**batch, on_error='skip')
+        assert (result.processed, result.skipped, result.first_error_index) == (3, 1, 2)
+
+        result = Engine(config).step_batch(**batch, on_error='stop')
+        assert (result.processed, result.skipped, result.first_error_index) == (2, 0, 2)
+
+    def test_batch_result_is_typed(self):
+        """step_batch returns an inspectable BatchResult, even when nothing fails."""
+        config = EngineConfig(initial_cash=10000.0, spread_bps=0.0, tick_size=0.01)
+
+        result = Engine(config).step_batch([1000, 1001], [10000, 10010], [1.0, 1.0], [0, 1])
+        assert type(result).__name__ == 'BatchResult'
+        assert (result.processed, result.skipped, result.first_error_index) == (2, 0, None)
+        assert repr(result) == 'BatchResult(processed=2, skipped=0, first_error_index=None)'
+
+    def test_place_orders_batch(self):
+        """place_orders submits a list of orders in one call and returns their ids."""
+        config = EngineConfig(initial_cash=10000.0, spread_bps=0.0, tick_size=0.01)
+        engine = Engine(config)
+
+        orders = [Order('LIMIT', 'BUY', 1.0, price=99.0 - i) for i in range(5)]
+        ids = engine.place_orders(orders)
+        assert ids == [1, 2, 3, 4, 5]
+        assert [order.order_id for order in orders] == ids
+        assert len(engine.get_open_orders()) == 5
+
+        bad = [Order('LIMIT', 'BUY', 1.0, price=90.0), Order('LIMIT', 'HOLD', 1.0, price=90.0)]
+        assert engine.place_orders(bad, on_error='skip') == [6, None]
+        with pytest.raises(RuntimeError):
+            engine.place_orders(bad)
+
+
 if __name__ == '__main__':
     pytest.main([__file__, '-v'])
@@ -0,0 +1,127 @@
+"""Tests for the streaming CandleParser exposed by the Rust core."""
+
+import pytest
+import sys
+import os
+
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', '..', 'python'))
+
+try:
+    from ag_backtester import _ag_core
+except ImportError:
+    _ag_core = None
+
+pytestmark = pytest.mark.skipif(_ag_core is None, reason="Rust core not built")
+
+
+CSV_DATA = """timestamp,open,high,low,close,volume
+1609459200000,42000.5,42500.0,41500.0,42200.0,1500.5
+1609459260000,42200.0,42800.0,42100.0,42700.0,2000.3
+"""
+
+
+class TestCandleParser:
+    """Iterate candles from Python using the Rust streaming parser."""
+
+    def test_iterates_candles_as_dicts(self, tmp_path):
+        path = tmp_path / "candles.csv"
+        path.write_text(CSV_DATA)
+
+        candles = list(_ag_core.CandleParser(str(path), 0.5))
+
+        assert len(candles) == 2
+        assert candles[0]['ts_open'] == 1609459200000
+        assert candles[0]['open'] == pytest.approx(42000.5)
+        assert candles[1]['close'] == pytest.approx(42700.0)
+        assert candles[1]['volume'] == pytest.approx(2000.3)
+
+    def test_parse_error_raises(self, tmp_path):
+        path = tmp_path / "bad.csv"
+        path.write_text("timestamp,open,high,low,close,volume\n1609459200000,abc,1,1,1,1\n")
+
+        with pytest.raises(ValueError):
+            list(_ag_core.CandleParser(str(path), 0.5))
+
+    def test_limit_stops_early(self, tmp_path):
+        path = tmp_path / "candles.csv"
+        path.write_text(CSV_DATA)
+
+        candles = list(_ag_core.CandleParser(str(path), 0.5, limit=1))
+
+        assert len(candles) == 1
+        assert candles[0]['ts_open'] == 1609459200000
+
+    def test_missing_file_raises(self, tmp_path):
+        with pytest.raises(OSError):
+            _ag_core.CandleParser(str(tmp_path / "missing.csv"), 0.5)
+
+
+class TestSummarizeCandles:
+    """Dataset statistics computed by the Rust core."""
+
+    def test_summary_of_file(self, tmp_path):
+        path = tmp_path / "candles.csv"
+        path.write_text(CSV_DATA)
+
+        summary = _ag_core.summarize_candles(str(path), 0.5)
+
+        assert summary['count'] == 2
+        assert summary['first_ts_ms'] == 1609459200000
+        assert summary['min_price'] == pytest.approx(41500.0)
+        assert summary['max_price'] == pytest.approx(42800.0)
+        assert summary['total_volume'] == pytest.approx(3500.8)
+        assert summary['avg_range'] == pytest.approx(850.0)
+
+class TestParseCandlesBytes:
+    """Parsing candle data already in memory."""
+
+    def test_csv_bytes_to_columns(self):
+        columns = _ag_core.parse_candles_bytes(CSV_DATA.encode(), "csv", 0.5)
+
+        assert columns['ts_open'] == [1609459200000, 1609459260000]
+        assert columns['open'] == pytest.approx([42000.5, 42200.0])
+        assert columns['close'] == pytest.approx([42200.0, 42700.0])
+        assert columns['volume'] == pytest.approx([1500.5, 2000.3])
+        assert columns['trade_count'] == [None, None]
+
+    def test_unknown_format_raises(self):
+        with pytest.raises(ValueError):
+            _ag_core.parse_candles_bytes(CSV_DATA.encode(), "xlsx", 0.5)
+
+
+class TestReplayProgress:
+    """Progress callbacks while replaying a candle file through the engine."""
+
+    def test_callback_fires_every_n_candles(self, tmp_path):
+        path = tmp_path / "candles.csv"
+        rows = [f"{1609459200000 + i * 60000},100.0,101.0,99.0,100.0,10" for i in range(10)]
+        path.write_text("timestamp,open,high,low,close,volume\n" + "\n".join(rows) + "\n")
+
+        calls = []
+        engine = _ag_core.Engine(tick_size=0.5)
+        processed = engine.replay_file(str(path), 4, lambda n, ts, eta: calls.append((n, ts, eta)))
+
+        assert processed == 10
+        assert [n for n, _, _ in calls] == [4, 8]
+        assert calls[1][1] == 1609459200000 + 7 * 60000
+
+
+class TestRunFile:
+    """Running a whole candle file through the engine in one call."""
+
+    def test_returns_summary(self, tmp_path):
+        path = tmp_path / "candles.csv"
+        path.write_text(CSV_DATA)
+
+        engine = _ag_core.Engine(tick_size=0.5)
+        summary = engine.run_file(str(path), "SELL")
+
+        assert summary['events'] == 2
+        assert isinstance(summary['final_equity'], float)
+
+    def test_errors_name_the_file(self, tmp_path):
+        path = tmp_path / "bad.csv"
+        path.write_text("timestamp,open,high,low,close,volume\n1609459200000,abc,1,1,1,1\n")
+
+        with pytest.raises(ValueError, match="bad.csv"):
+            _ag_core.Engine(tick_size=0.5).run_file(str(path))
@@ -0,0 +1,35 @@
+"""Tests for the indicators exposed by the Rust core."""
+
+import pytest
+import sys
+import os
+
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', '..', 'python'))
+
+try:
+    from ag_backtester import _ag_core
+except ImportError:
+    _ag_core = None
+
+pytestmark = pytest.mark.skipif(_ag_core is None, reason="Rust core not built")
+
+
+class TestDonchianChannel:
+    """Rolling high/low channel in tick space."""
+
+    def test_none_until_window_full(self):
+        channel = _ag_core.DonchianChannel(3)
+        assert channel.period == 3
+        assert channel.update(110, 90) is None
+        assert channel.update(105, 95) is None
+        assert channel.update(101, 99) == (110, 90)
+
+    def test_extremes_leave_window(self):
+        channel = _ag_core.DonchianChannel(2)
+        channel.update(110, 90)
+        channel.update(105, 95)
+        assert channel.update(101, 99) == (105, 95)
+
+    def test_rejects_zero_period(self):
+        with pytest.raises(ValueError):
+            _ag_core.DonchianChannel(0)